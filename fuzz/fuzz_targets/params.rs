@@ -0,0 +1,29 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use marallys_auth_patcher::config::Config;
+use marallys_auth_patcher::params::{Identity, LaunchSpec};
+
+const IDENTITY: Identity = Identity {
+    name: "Steve",
+    uuid: "4b5c2a1e9f3d4e6a8b7c0d1e2f3a4b5c",
+    access_token: "token",
+};
+
+// Whatever the launcher sends on stdin, reading and patching it must not panic, and a
+// patched block must log the game in as the account.
+fuzz_target!(|data: &[u8]| {
+    let text = String::from_utf8_lossy(data);
+    let mut spec = LaunchSpec::parse(&text);
+    let _ = (spec.username(), spec.uuid(), spec.access_token());
+
+    let mut config = Config::default();
+    config.game.quick_play = Some("play.example.com:25570".into());
+    config
+        .param_rules
+        .insert("--width".into(), "{name}".into());
+    if spec.patch(&IDENTITY, "client", &config).is_ok() {
+        let patched = LaunchSpec::parse(&spec.serialize());
+        assert_eq!(patched.username(), Some(IDENTITY.name));
+    }
+});
@@ -0,0 +1,51 @@
+/*
+ * C interface of marallys_auth_patcher, built with
+ *   cargo rustc --release --lib --features ffi --crate-type cdylib \
+ *     --config 'profile.release.panic="unwind"'
+ * (or --crate-type staticlib). A panic inside a call then returns 1 instead of
+ * aborting. Strings are UTF-8 and NUL-terminated.
+ *
+ * Functions returning int return 0 on success, otherwise the exit code the patcher
+ * would exit with (see "Exit codes" in the README); mmcai_last_error() then says why.
+ */
+#ifndef MMCAI_H
+#define MMCAI_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/*
+ * Signs in to the authlib-injector server at api_url. On success *session_json is
+ * {"accessToken", "clientToken", "expiredDate", "profile": {"id", "name"}}, to be
+ * freed with mmcai_string_free(). The patcher's config file is not read: retries and
+ * the proxy are the defaults, and no certificate pins are checked.
+ */
+int mmcai_authenticate(const char *api_url, const char *username, const char *password,
+                       char **session_json);
+
+/*
+ * Patches a param block (what the launcher writes to the game's stdin, up to "launch")
+ * for the account, like the patcher does; client_token is the session's "clientToken",
+ * which replaces --clientId. On success *patched is the patched block, to be freed with
+ * mmcai_string_free().
+ */
+int mmcai_patch_params(const char *params, const char *username, const char *uuid,
+                       const char *access_token, const char *client_token, char **patched);
+
+/*
+ * The last error on the calling thread, as text or as JSON ({"code", "stage",
+ * "message", ...}), or NULL. Owned by the library, valid until the next call on the
+ * thread.
+ */
+const char *mmcai_last_error(void);
+const char *mmcai_last_error_json(void);
+
+/* Frees a string returned through an out parameter. NULL is ignored. */
+void mmcai_string_free(char *string);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* MMCAI_H */
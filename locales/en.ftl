@@ -0,0 +1,233 @@
+# Messages of the patcher, in Fluent syntax. Keep ru.ftl in sync.
+
+## Login
+injector-found = authlib-injector found at { $path }, logging in...
+auth-success = Successfully authenticated as { $name }
+pass-through = Pass-through mode: starting the game without logging in
+launch-aborted = The launcher aborted the launch
+profile-pick = Which profile do you want to play as?
+compat-classic-deprecated = --compat classic is deprecated and will be removed. Write the wrapper command in the current order instead: { $command }
+prelaunch-done = Logged in as { $name } for the launch; the environment file is { $path }
+prelaunch-used = Using the login of the pre-launch command ({ $name })
+server-unreachable-trying = { $url } is unreachable ({ $error }), trying { $next }
+rate-limited-waiting = The server is rate limiting logins, waiting { $seconds }s before trying again
+request-retry = Request failed ({ $reason }), retrying in { $delay }s (attempt { $attempt }/{ $attempts })
+api-location = { $url } points to the API at { $location }
+api-moved = The server moved its API to { $location }, it will be used from the next launch
+api-location-insecure = Not following the API location { $location }: only HTTPS or the same server is trusted with the password
+api-location-stale = The remembered API location { $url } does not answer, looking it up again
+instance-api-url-ignored = Ignoring api_url={ $url } in instance.cfg: it is only used together with the instance's own username and password
+metadata-cached = Cannot fetch API metadata ({ $reason }), using the cached copy
+server-maintenance = the auth server reports that it is under maintenance
+server-notice = Server notice: { $line }
+tls-insecure = TLS certificate validation is DISABLED (danger_accept_invalid_certs = true).
+tls-insecure-warning = anyone on your network can read your password. Never use this outside of a test server.
+texture-signature-mismatch = The skin's signature from { $url } doesn't match the server's key: the session server and the API metadata come from different servers (a misconfigured proxy?), the game will show the default skin
+texture-signature-missing = { $url } sends the skin without a signature, the game will show the default skin
+
+## Network diagnostics, when the auth server cannot be reached
+netdiag-header = Network diagnostics for { $host }:
+netdiag-dns = DNS lookup
+netdiag-tcp = TCP connection to port { $port }
+netdiag-tls = TLS handshake
+netdiag-http = HTTP request to /
+netdiag-ok = ok
+netdiag-failed = FAILED
+netdiag-timeout = no answer in time
+netdiag-no-addresses = no addresses found
+netdiag-dns-failed = The server's name cannot be resolved: check your internet connection and DNS settings.
+netdiag-tcp-failed = The server's address is known but it doesn't accept connections: the server is probably down, or a firewall blocks it.
+netdiag-tls-failed = The connection works but the secure handshake fails: an antivirus, proxy or captive portal may be intercepting it, or the server's certificate is broken.
+netdiag-http-failed = The server accepts connections but doesn't answer requests: it is probably overloaded or misconfigured.
+netdiag-all-ok = The server can be reached now, the problem was probably temporary.
+dialog-username = Username for { $server }:
+dialog-password = Password of { $username } for { $server }:
+dialog-credentials = Log in to { $server }
+
+## Offline launches
+offline-session-expired = The cached session has expired, cannot launch offline
+offline-no-metadata = No cached API metadata, cannot launch offline
+offline-launch = the auth server is unreachable, launching with the session cached { $minutes } minutes ago
+session-save-failed = Cannot save the session for offline launches: { $error }
+
+## Launching
+metadata-argfile = API metadata is too large for the command line, passing it via { $path }
+metadata-not-prefetched = API metadata is too large for the command line and this Java is too old for an argument file, authlib-injector will fetch it itself
+java-detected = INST_JAVA is not set, using Java { $version } from { $path }
+memory-replaced = the launcher passed { $previous }, using { $configured } from { $setting } instead
+memory-min-above-max = memory.min ({ $min }) is larger than memory.max ({ $max }), Java will refuse to start
+cpu-affinity-unsupported = process.cpu_affinity is not supported on this system
+cpu-affinity-out-of-range = process.cpu_affinity: CPU { $cpu } is beyond what the system can address and is ignored
+timings = Timings: { $stages }
+game-crashed = The game crashed:
+relaunching = Relaunching the game ({ $attempt }/{ $max })
+
+## Diagnostics
+trace-enabled = Tracing HTTP requests to { $path }
+params-recorded = Recorded the launch params to { $path }
+dump-written = Wrote the launch command and params to { $path }
+audit-write-failed = Cannot write the login audit log { $path }: { $error }
+playtime-write-failed = Cannot record the playtime in { $path }: { $error }
+metrics-write-failed = Cannot write the metrics file { $path }: { $error }
+webhook-status = webhook { $url } answered { $status }
+webhook-failed = cannot send webhook to { $url }: { $error }
+webhook-timeout = webhook { $url } timed out
+journald-unsupported = logging.journald is only supported on Linux
+event-log-unsupported = logging.event_log is only supported on Windows
+
+## stats
+stats-empty = No playtime recorded yet.
+stats-total = Total playtime: { $duration }
+stats-per-week = Per week:
+
+## account
+account-added = Saved the account { $name }
+account-removed = Removed the account { $name }
+account-default = { $name } is now the default account
+account-none = No accounts stored yet.
+account-pick = Which account should log in?
+account-pick-save-failed = Cannot remember the picked account: { $error }
+account-save-failed = Cannot update the stored account: { $error }
+account-password = Password of { $name } (leave empty to be asked at each launch)
+account-keyring-failed = Cannot keep the password in the system credential store ({ $error }); it will be asked for at launch
+account-keyring-move-failed = Cannot move the password of { $name } to the system credential store ({ $error }); it stays in accounts.json
+password-old = Current password of { $name }:
+password-new = New password of { $name }:
+password-repeat = New password of { $name } again:
+password-changed = Changed the password of { $name }
+token-obtained = obtained { $age } ago
+token-expires = expires in { $time }
+token-expired = expired { $time } ago
+token-valid = valid
+token-invalid = expired or revoked, log in again
+token-none = no cached login
+token-unknown = cannot check ({ $reason })
+
+## whoami
+whoami-server = Server: { $url }
+whoami-skin = Skin: { $texture }
+whoami-cloak = Cloak: { $texture }
+whoami-full-skin = Full skin: { $texture }
+whoami-none = none
+whoami-unknown = unknown
+whoami-expires = Token expires: { $time }
+whoami-last-login = Previous login: { $time }
+whoami-last-login-unknown = Previous login: not recorded (enable [audit] to keep track)
+
+## skin
+skin-uploaded = Uploaded the skin of { $name }
+skin-wrong-size = it is { $width }×{ $height }, skins are 64×64 or 64×32 (or HD multiples of those)
+skin-downloaded = Saved the skin of { $name } to { $path }
+skin-preview-saved = Saved the front view of { $name }'s skin to { $path }
+skin-render-saved = Saved the rendered skin of { $name } to { $path }
+browser-open-failed = Cannot open { $url } in the browser: { $error }
+skin-synced = Gave { $name } the skin of { $source } ({ $model } model)
+skin-saved = Saved the skin of { $name } as { $skin }
+skin-applied = Changed the skin of { $name } to { $skin }
+skin-library-empty = No skins saved yet.
+skin-reset-confirm = Reset the skin of { $name } to the default one?
+cape-remove-confirm = Take off the cape of { $name }?
+confirm-no = No, keep it
+confirm-yes = Yes
+skin-reset = Reset the skin of { $name } to the default one
+skin-reset-cancelled = Nothing changed (pass --yes to skip the question)
+cape-uploaded = Uploaded the cape of { $name }
+cape-removed = Removed the cape of { $name }
+cape-none = { $name } wears no cape
+
+## import
+import-added = Imported { $account } ({ $name })
+import-updated = Updated { $account } ({ $name })
+import-done = { $count } account(s) imported into { $path }
+import-no-passwords = Prism doesn't store passwords: write - as the password in the wrapper command to be asked for it.
+export-done = Wrote { $name }'s session to { $path }. Start Prism after this, or it overwrites the file when it closes.
+
+## Errors
+error = Error
+error-exit-code = exit code { $code }
+
+hint-credentials = Check the username and password in the launcher's account settings.
+hint-unreachable = Check your internet connection and the API URL, or try again later.
+hint-invalid-metadata = Check that the API URL points to the authlib-injector API, not a web page.
+hint-rate-limited = Wait a minute before logging in again.
+hint-unknown-backend = Set server.backend to one of the available backends.
+hint-invalid-endpoint = Give the endpoint relative to the API URL, like ../../auth/signin, or as a full URL.
+hint-missing-api-url = Pass the API URL or set server.api_url.
+hint-pin-mismatch = If the server has a new certificate, update http.pinned_certificates and http.pinned_public_keys.
+hint-invalid-proxy = Check http.proxy, or the HTTPS_PROXY and ALL_PROXY variables.
+hint-injector-not-found = Download authlib-injector and put its jar next to mmcai_rs.
+
+## Error messages, one per MmcaiError variant
+error-invalid-argument = Usage: { $program } [options] <username> <password> <api url>
+error-unknown-option = Unknown option { $option }.
+error-missing-option-value = Option { $option } requires a value.
+error-invalid-option-value = Invalid value { $value } for option { $option }.
+error-cannot-run-directly = Looks like you have entered a valid command, but you can't run mmcai_rs directly! Put your command in "Wrapper command" in Prism Launcher.
+error-authlib-injector-not-found = authlib-injector not found in the same directory as mmcai_rs.
+error-yggdrasil-hello-failed = Cannot reach the authentication server.
+error-invalid-metadata = { $api_url } did not return valid authlib-injector API metadata ({ $reason }). Is the API URL correct? Response: { $excerpt }
+error-server-unhealthy = Cannot use the auth server at { $host }: { $problem }.
+error-rate-limited = The server is rate limiting logins, try again in { $retry_after } seconds.
+error-yggdrasil-auth-failed = The login request failed. Server response: { $response }
+error-invalid-auth-response = Cannot understand the auth server's answer ({ $message }). Server response: { $response }
+error-invalid-credentials = Wrong username or password. The server says: { $message }
+error-credentials-not-given = No username or password: none was given or stored, and asking for them was cancelled or no terminal or dialog is available here.
+error-account-not-activated = The account is not activated yet: confirm your email address with the link the server sent you, then try again.{ $see } The server says: { $message }
+error-account-blocked = The account is banned. Contact the server's administrators if you think this is a mistake.{ $see } The server says: { $message }
+error-account-locked = The account is locked after too many failed logins. Wait before trying again, or reset your password.{ $see } The server says: { $message }
+error-login-rejected = The auth server refused the login (status { $status_code }): { $message }
+error-unknown-profile = The account has no profile named { $name }; its profiles are: { $available }
+error-reqwest-client-build-failed = Cannot build reqwest client. This should not happen. Please report this issue to the developers.
+error-read-config-failed = Cannot read config file { $path }.
+error-invalid-config = Invalid config file { $path }: { $message }
+error-unknown-backend = Unknown server.backend { $name } in config. Use one of: { $available }.
+error-invalid-endpoint = Cannot use server.endpoints.{ $endpoint }: { $message }.
+error-invalid-proxy = Invalid proxy { $url } in config. Use a URL like socks5://host:1080 or http://host:3128.
+error-read-certificate-failed = Cannot read CA certificate file { $path }.
+error-invalid-certificate = No valid PEM certificate found in { $path }.
+error-certificate-pin-mismatch = The certificate of { $host } does not match the pinned certificate/public key. Someone may be intercepting your connection, so your password was not sent.
+error-certificate-pin-unavailable = Cannot check the certificate of { $host } against the configured pins. Certificate pinning requires an https:// API URL.
+error-open-trace-file-failed = Cannot open HTTP trace file { $path }.
+error-runtime-build-failed = Cannot start the async runtime. This should not happen. Please report this issue to the developers.
+error-read-minecraft-params-failed = Cannot read Minecraft params. This should not happen. Please report this issue to the developers.
+error-invalid-minecraft-params = The launcher sent no value for { $param }. This should not happen. Please report this issue to the developers.
+error-params-timed-out = The launcher sent no launch params for { $seconds } seconds. Raise watchdog.params_timeout if it is just slow.
+error-launch-aborted = The launcher aborted the launch.
+error-write-minecraft-params-failed = Cannot write Minecraft params. This should not happen. Please report this issue to the developers.
+error-read-params-file-failed = Cannot read launch params from { $path }.
+error-write-params-record-failed = Cannot record the launch params to { $path }.
+error-write-launch-dump-failed = Cannot write the launch dump to { $path }.
+error-open-log-file-failed = Cannot open the game log file { $path }.
+error-open-events-failed = Cannot open { $target } for events.
+error-connect-journald-failed = Cannot connect to journald.
+error-open-event-log-failed = Cannot open the Windows event log.
+error-read-accounts-failed = Cannot read the accounts file { $path }.
+error-invalid-accounts = The accounts file { $path } is invalid: { $message }
+error-unknown-account = There is no stored account { $account }. See the accounts with `account list`.
+error-command-usage = Usage: { $usage }
+error-missing-api-url = No API URL given to the client.
+error-account-without-server = The account { $account } has no API URL. Add it again with one.
+error-passwords-differ = The new passwords don't match.
+error-read-texture-failed = Cannot read the texture { $path }.
+error-invalid-texture = { $path } is not a PNG image.
+error-texture-request-failed = The texture request to the auth server failed.
+error-texture-rejected = The auth server refused the texture change (status { $status_code }): { $message }
+error-no-skin = The server reports no skin for { $name }.
+error-unreadable-skin = The skin image cannot be read: { $reason }
+error-invalid-skin-name = { $name } can't be a skin name: use letters, digits, - and _.
+error-unknown-skin = There is no saved skin named { $name }.
+error-texture-forbidden = The auth server doesn't allow this texture change: { $reason }
+error-source-profile-not-found = There is no player named { $name } to take the skin from.
+error-write-texture-failed = Cannot write the texture { $path }.
+error-write-accounts-failed = Cannot write the accounts file { $path }.
+error-open-wrapper-log-failed = Cannot open the wrapper log file { $path }.
+error-write-arg-file-failed = Cannot write the JVM argument file { $path }.
+error-write-prelaunch-failed = Cannot write the pre-launch login to { $path }.
+error-working-directory-not-found = The working directory { $path } does not exist.
+error-spawn-process-failed = Cannot start Minecraft. This should not happen. Please report this issue to the developers.
+error-wait-process-failed = Cannot wait for Minecraft to exit. This should not happen. Please report this issue to the developers.
+error-game-unresponsive = The game printed nothing for { $seconds } seconds and was stopped. Check the -javaagent path and the JVM arguments.
+error-stdin-unavailable = Cannot write Minecraft params. Stdin is unavailable. This should not happen. Please report this issue to the developers.
+error-java-executable-not-found = Cannot find Java: INST_JAVA is not set and no working Java new enough for this Minecraft version was found in JAVA_HOME, PATH or Prism's Java runtimes. Set JAVA_HOME, or run the patcher from Prism.
+error-other = Unknown error. This should not happen. Please report this issue to the developers.
+error-see = See { $url }
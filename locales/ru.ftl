@@ -0,0 +1,233 @@
+# Сообщения патчера в синтаксисе Fluent. Ключи совпадают с en.ftl.
+
+## Вход
+injector-found = authlib-injector найден: { $path }, выполняется вход...
+auth-success = Вход выполнен: { $name }
+pass-through = Режим без входа: игра запускается с параметрами лаунчера
+launch-aborted = Лаунчер отменил запуск
+profile-pick = За какой профиль играть?
+compat-classic-deprecated = --compat classic устарел и будет удалён. Укажите аргументы в новом порядке: { $command }
+prelaunch-done = Вход выполнен: { $name }; файл окружения: { $path }
+prelaunch-used = Используется вход из команды перед запуском ({ $name })
+server-unreachable-trying = { $url } недоступен ({ $error }), пробуем { $next }
+rate-limited-waiting = Сервер ограничивает частоту входов, ждём { $seconds } с перед новой попыткой
+request-retry = Запрос не удался ({ $reason }), повтор через { $delay } с (попытка { $attempt }/{ $attempts })
+api-location = { $url } указывает на API по адресу { $location }
+api-moved = Сервер перенёс API на { $location }, новый адрес будет использован со следующего запуска
+api-location-insecure = Адрес API { $location } проигнорирован: пароль доверяется только HTTPS или тому же серверу
+api-location-stale = Запомненный адрес API { $url } не отвечает, адрес определяется заново
+instance-api-url-ignored = api_url={ $url } из instance.cfg не используется: он действует только вместе с собственными username и password экземпляра
+metadata-cached = Не удалось получить метаданные API ({ $reason }), используется сохранённая копия
+server-maintenance = сервер авторизации сообщает о техническом обслуживании
+server-notice = Сообщение сервера: { $line }
+tls-insecure = Проверка TLS-сертификатов ОТКЛЮЧЕНА (danger_accept_invalid_certs = true).
+tls-insecure-warning = любой в вашей сети может прочитать ваш пароль. Используйте это только на тестовом сервере.
+texture-signature-mismatch = Подпись скина от { $url } не совпадает с ключом сервера: сервер сессий и метаданные API относятся к разным серверам (неверно настроен прокси?), в игре будет стандартный скин
+texture-signature-missing = { $url } отдаёт скин без подписи, в игре будет стандартный скин
+
+## Диагностика сети, когда сервер авторизации недоступен
+netdiag-header = Диагностика сети для { $host }:
+netdiag-dns = Поиск в DNS
+netdiag-tcp = TCP-подключение к порту { $port }
+netdiag-tls = TLS-рукопожатие
+netdiag-http = HTTP-запрос к /
+netdiag-ok = в порядке
+netdiag-failed = ОШИБКА
+netdiag-timeout = нет ответа вовремя
+netdiag-no-addresses = адреса не найдены
+netdiag-dns-failed = Имя сервера не удаётся найти: проверьте подключение к интернету и настройки DNS.
+netdiag-tcp-failed = Адрес сервера известен, но он не принимает подключения: вероятно, сервер выключен или подключение блокирует брандмауэр.
+netdiag-tls-failed = Подключение есть, но защищённое соединение не устанавливается: его может перехватывать антивирус, прокси или страница входа в сеть, либо сертификат сервера неисправен.
+netdiag-http-failed = Сервер принимает подключения, но не отвечает на запросы: вероятно, он перегружен или неправильно настроен.
+netdiag-all-ok = Сейчас сервер доступен, вероятно, проблема была временной.
+dialog-username = Имя пользователя на { $server }:
+dialog-password = Пароль { $username } на { $server }:
+dialog-credentials = Вход на { $server }
+
+## Запуск без сети
+offline-session-expired = Сохранённая сессия истекла, запуск без сети невозможен
+offline-no-metadata = Нет сохранённых метаданных API, запуск без сети невозможен
+offline-launch = сервер авторизации недоступен, запуск с сессией, сохранённой { $minutes } мин. назад
+session-save-failed = Не удалось сохранить сессию для запуска без сети: { $error }
+
+## Запуск
+metadata-argfile = Метаданные API слишком велики для командной строки, они передаются через { $path }
+metadata-not-prefetched = Метаданные API слишком велики для командной строки, а эта Java слишком стара для файла аргументов, authlib-injector загрузит их сам
+java-detected = INST_JAVA не задана, используется Java { $version } из { $path }
+memory-replaced = лаунчер передал { $previous }, вместо этого используется { $configured } из { $setting }
+memory-min-above-max = memory.min ({ $min }) больше, чем memory.max ({ $max }), Java не запустится
+cpu-affinity-unsupported = process.cpu_affinity не поддерживается в этой системе
+cpu-affinity-out-of-range = process.cpu_affinity: процессор { $cpu } вне диапазона, доступного системе, и пропускается
+timings = Время этапов: { $stages }
+game-crashed = Игра аварийно завершилась:
+relaunching = Повторный запуск игры ({ $attempt }/{ $max })
+
+## Диагностика
+trace-enabled = HTTP-запросы записываются в { $path }
+params-recorded = Параметры запуска записаны в { $path }
+dump-written = Команда запуска и параметры записаны в { $path }
+audit-write-failed = Не удалось записать журнал входов { $path }: { $error }
+playtime-write-failed = Не удалось записать игровое время в { $path }: { $error }
+metrics-write-failed = Не удалось записать файл метрик { $path }: { $error }
+webhook-status = вебхук { $url } ответил { $status }
+webhook-failed = не удалось отправить вебхук на { $url }: { $error }
+webhook-timeout = вебхук { $url } не ответил вовремя
+journald-unsupported = logging.journald поддерживается только в Linux
+event-log-unsupported = logging.event_log поддерживается только в Windows
+
+## stats
+stats-empty = Игровое время ещё не записано.
+stats-total = Всего сыграно: { $duration }
+stats-per-week = По неделям:
+
+## account
+account-added = Аккаунт { $name } сохранён
+account-removed = Аккаунт { $name } удалён
+account-default = { $name } теперь аккаунт по умолчанию
+account-none = Сохранённых аккаунтов пока нет.
+account-pick = Под каким аккаунтом войти?
+account-pick-save-failed = Не удалось запомнить выбранный аккаунт: { $error }
+account-save-failed = Не удалось обновить сохранённый аккаунт: { $error }
+account-password = Пароль { $name } (оставьте пустым, чтобы вводить его при каждом запуске)
+account-keyring-failed = Не удалось сохранить пароль в системном хранилище ({ $error }); он будет запрошен при запуске
+account-keyring-move-failed = Не удалось перенести пароль { $name } в системное хранилище ({ $error }); он остаётся в accounts.json
+password-old = Текущий пароль { $name }:
+password-new = Новый пароль { $name }:
+password-repeat = Новый пароль { $name } ещё раз:
+password-changed = Пароль { $name } изменён
+token-obtained = получен { $age } назад
+token-expires = истекает через { $time }
+token-expired = истёк { $time } назад
+token-valid = действителен
+token-invalid = истёк или отозван, войдите заново
+token-none = нет сохранённого входа
+token-unknown = не удалось проверить ({ $reason })
+
+## whoami
+whoami-server = Сервер: { $url }
+whoami-skin = Скин: { $texture }
+whoami-cloak = Плащ: { $texture }
+whoami-full-skin = Полный скин: { $texture }
+whoami-none = нет
+whoami-unknown = неизвестно
+whoami-expires = Токен истекает: { $time }
+whoami-last-login = Предыдущий вход: { $time }
+whoami-last-login-unknown = Предыдущий вход: не записан (включите [audit], чтобы сохранять его)
+
+## skin
+skin-uploaded = Скин { $name } загружен
+skin-wrong-size = его размер { $width }×{ $height }, а скины бывают 64×64 или 64×32 (или HD-скины кратного размера)
+skin-downloaded = Скин { $name } сохранён в { $path }
+skin-preview-saved = Вид скина { $name } спереди сохранён в { $path }
+skin-render-saved = Изображение скина { $name } сохранено в { $path }
+browser-open-failed = Не удалось открыть { $url } в браузере: { $error }
+skin-synced = Скин { $name } заменён скином { $source } (модель { $model })
+skin-saved = Скин { $name } сохранён как { $skin }
+skin-applied = Скин { $name } заменён на { $skin }
+skin-library-empty = Сохранённых скинов пока нет.
+skin-reset-confirm = Сбросить скин { $name } на стандартный?
+cape-remove-confirm = Снять плащ { $name }?
+confirm-no = Нет, оставить
+confirm-yes = Да
+skin-reset = Скин { $name } сброшен на стандартный
+skin-reset-cancelled = Ничего не изменено (--yes пропускает вопрос)
+cape-uploaded = Плащ { $name } загружен
+cape-removed = Плащ { $name } снят
+cape-none = У { $name } нет плаща
+
+## import
+import-added = Импортирован { $account } ({ $name })
+import-updated = Обновлён { $account } ({ $name })
+import-done = Импортировано аккаунтов: { $count }, сохранены в { $path }
+import-no-passwords = Prism не хранит пароли: укажите - вместо пароля в команде-обёртке, чтобы ввести его при запуске.
+export-done = Сессия { $name } записана в { $path }. Запускайте Prism после этого, иначе при закрытии он перезапишет файл.
+
+## Ошибки. Краткое описание по коду выхода, подробности выводятся следом на английском.
+error = Ошибка
+error-exit-code = код выхода { $code }
+
+hint-credentials = Проверьте имя пользователя и пароль в настройках аккаунта лаунчера.
+hint-unreachable = Проверьте подключение к интернету и адрес API или попробуйте позже.
+hint-invalid-metadata = Проверьте, что адрес API ведёт на API authlib-injector, а не на веб-страницу.
+hint-rate-limited = Подождите минуту, прежде чем входить снова.
+hint-unknown-backend = Укажите в server.backend один из доступных вариантов.
+hint-invalid-endpoint = Укажите адрес относительно адреса API, например ../../auth/signin, или полностью.
+hint-missing-api-url = Передайте адрес API или задайте server.api_url.
+hint-pin-mismatch = Если у сервера новый сертификат, обновите http.pinned_certificates и http.pinned_public_keys.
+hint-invalid-proxy = Проверьте http.proxy или переменные HTTPS_PROXY и ALL_PROXY.
+hint-injector-not-found = Скачайте authlib-injector и положите его jar рядом с mmcai_rs.
+
+## Error messages, one per MmcaiError variant
+error-invalid-argument = Использование: { $program } [параметры] <имя пользователя> <пароль> <адрес API>
+error-unknown-option = Неизвестный параметр { $option }.
+error-missing-option-value = Параметру { $option } нужно значение.
+error-invalid-option-value = Неверное значение { $value } параметра { $option }.
+error-cannot-run-directly = Похоже, команда верная, но mmcai_rs нельзя запускать напрямую! Укажите её в поле «Команда-обёртка» в Prism Launcher.
+error-authlib-injector-not-found = authlib-injector не найден в папке mmcai_rs.
+error-yggdrasil-hello-failed = Сервер авторизации недоступен.
+error-invalid-metadata = { $api_url } не вернул метаданные API authlib-injector ({ $reason }). Верен ли адрес API? Ответ: { $excerpt }
+error-server-unhealthy = Сервер авторизации { $host } не работает: { $problem }.
+error-rate-limited = Сервер ограничивает частоту входов, попробуйте через { $retry_after } с.
+error-yggdrasil-auth-failed = Не удалось выполнить запрос входа. Ответ сервера: { $response }
+error-invalid-auth-response = Не удалось разобрать ответ сервера авторизации ({ $message }). Ответ сервера: { $response }
+error-invalid-credentials = Неверное имя пользователя или пароль. Ответ сервера: { $message }
+error-credentials-not-given = Нет имени пользователя или пароля: они не заданы и не сохранены, а запрос был отменён или здесь нет ни терминала, ни окна для ввода.
+error-account-not-activated = Аккаунт ещё не активирован: подтвердите адрес почты по ссылке из письма сервера и попробуйте снова.{ $see } Ответ сервера: { $message }
+error-account-blocked = Аккаунт заблокирован. Если это ошибка, обратитесь к администрации сервера.{ $see } Ответ сервера: { $message }
+error-account-locked = Аккаунт временно заблокирован после слишком многих неудачных входов. Подождите перед новой попыткой или сбросьте пароль.{ $see } Ответ сервера: { $message }
+error-login-rejected = Сервер авторизации отклонил вход (статус { $status_code }): { $message }
+error-unknown-profile = У аккаунта нет профиля { $name }; его профили: { $available }
+error-reqwest-client-build-failed = Не удалось создать HTTP-клиент. Такого не должно происходить, сообщите об этом разработчикам.
+error-read-config-failed = Не удалось прочитать файл настроек { $path }.
+error-invalid-config = Ошибка в файле настроек { $path }: { $message }
+error-unknown-backend = Неизвестный server.backend { $name } в настройках. Доступны: { $available }.
+error-invalid-endpoint = Нельзя использовать server.endpoints.{ $endpoint }: { $message }.
+error-invalid-proxy = Неверный прокси { $url } в настройках. Укажите адрес вида socks5://host:1080 или http://host:3128.
+error-read-certificate-failed = Не удалось прочитать файл сертификата CA { $path }.
+error-invalid-certificate = В { $path } нет действительного сертификата PEM.
+error-certificate-pin-mismatch = Сертификат { $host } не совпадает с закреплённым сертификатом или открытым ключом. Соединение может перехватываться, поэтому пароль не был отправлен.
+error-certificate-pin-unavailable = Не удалось сверить сертификат { $host } с закреплёнными. Для закрепления сертификата нужен адрес API с https://.
+error-open-trace-file-failed = Не удалось открыть файл трассировки HTTP { $path }.
+error-runtime-build-failed = Не удалось запустить асинхронную среду выполнения. Такого не должно происходить, сообщите об этом разработчикам.
+error-read-minecraft-params-failed = Не удалось прочитать параметры Minecraft. Такого не должно происходить, сообщите об этом разработчикам.
+error-invalid-minecraft-params = Лаунчер не передал значение { $param }. Такого не должно происходить, сообщите об этом разработчикам.
+error-params-timed-out = Лаунчер не передал параметры запуска за { $seconds } с. Если он просто медленный, увеличьте watchdog.params_timeout.
+error-launch-aborted = Лаунчер отменил запуск.
+error-write-minecraft-params-failed = Не удалось передать параметры Minecraft. Такого не должно происходить, сообщите об этом разработчикам.
+error-read-params-file-failed = Не удалось прочитать параметры запуска из { $path }.
+error-write-params-record-failed = Не удалось записать параметры запуска в { $path }.
+error-write-launch-dump-failed = Не удалось записать команду запуска в { $path }.
+error-open-log-file-failed = Не удалось открыть журнал игры { $path }.
+error-open-events-failed = Не удалось открыть { $target } для вывода событий.
+error-connect-journald-failed = Не удалось подключиться к journald.
+error-open-event-log-failed = Не удалось открыть журнал событий Windows.
+error-read-accounts-failed = Не удалось прочитать файл с аккаунтами { $path }.
+error-invalid-accounts = Ошибка в файле с аккаунтами { $path }: { $message }
+error-unknown-account = Сохранённого аккаунта { $account } нет. Список аккаунтов: `account list`.
+error-command-usage = Использование: { $usage }
+error-missing-api-url = Клиенту не указан адрес API.
+error-account-without-server = У аккаунта { $account } нет адреса API. Добавьте его заново с адресом.
+error-passwords-differ = Новые пароли не совпадают.
+error-read-texture-failed = Не удалось прочитать текстуру { $path }.
+error-invalid-texture = { $path } не является изображением PNG.
+error-texture-request-failed = Не удалось отправить запрос текстуры на сервер авторизации.
+error-texture-rejected = Сервер авторизации отклонил изменение текстуры (статус { $status_code }): { $message }
+error-no-skin = Сервер не сообщил скин { $name }.
+error-unreadable-skin = Не удалось прочитать изображение скина: { $reason }
+error-invalid-skin-name = { $name } не может быть именем скина: используйте буквы, цифры, - и _.
+error-unknown-skin = Сохранённого скина { $name } нет.
+error-texture-forbidden = Сервер авторизации не разрешает это изменение текстуры: { $reason }
+error-source-profile-not-found = Игрока { $name }, у которого нужно взять скин, нет.
+error-write-texture-failed = Не удалось записать текстуру { $path }.
+error-write-accounts-failed = Не удалось записать файл с аккаунтами { $path }.
+error-open-wrapper-log-failed = Не удалось открыть журнал патчера { $path }.
+error-write-arg-file-failed = Не удалось записать файл аргументов JVM { $path }.
+error-write-prelaunch-failed = Не удалось сохранить вход для команды перед запуском в { $path }.
+error-working-directory-not-found = Рабочая папка { $path } не существует.
+error-spawn-process-failed = Не удалось запустить Minecraft. Такого не должно происходить, сообщите об этом разработчикам.
+error-wait-process-failed = Не удалось дождаться завершения Minecraft. Такого не должно происходить, сообщите об этом разработчикам.
+error-game-unresponsive = Игра ничего не выводила { $seconds } с и была остановлена. Проверьте путь в -javaagent и аргументы JVM.
+error-stdin-unavailable = Не удалось передать параметры Minecraft: стандартный ввод недоступен. Такого не должно происходить, сообщите об этом разработчикам.
+error-java-executable-not-found = Java не найдена: INST_JAVA не задан, а в JAVA_HOME, PATH и средах Java от Prism нет рабочей Java, подходящей для этой версии Minecraft. Задайте JAVA_HOME или запускайте патчер из Prism.
+error-other = Неизвестная ошибка. Такого не должно происходить, сообщите об этом разработчикам.
+error-see = Подробнее: { $url }
@@ -0,0 +1,524 @@
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::errors::MmcaiError;
+use crate::i18n::tr;
+use crate::{paths, picker, secrets, session, Result};
+
+const ACCOUNTS_FILE_NAME: &str = "accounts.json";
+/// Written instead of the username in the wrapper command to use a stored account:
+/// `@name`, or just `@` for the default one.
+pub const ACCOUNT_PREFIX: char = '@';
+
+const USAGE: &str = "account add <name> <username> [<api url>] | account list [--check] | account remove <name> | account set-default <name> | account change-password <name> [<2fa code>]";
+
+/// An account the patcher knows.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Account {
+    /// What the account is selected by, e.g. `@alt` in the wrapper command.
+    #[serde(default)]
+    pub name: String,
+    /// The login sent to the auth server.
+    pub username: String,
+    /// The auth server; the one from the wrapper command is used when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_url: Option<String>,
+    /// Asked for in a dialog at launch when not stored. Stored passwords are kept in the
+    /// OS credential store; only files written by older versions have them here, and
+    /// they are moved out on the next load.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    /// The Minecraft profile the account had when it was last seen.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<AccountProfile>,
+    /// Sent with every login, so the server sees the same client each launch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_token: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AccountProfile {
+    pub id: String,
+    pub name: String,
+}
+
+impl Account {
+    /// An account given by username in the wrapper command rather than stored.
+    pub fn unstored(username: String, password: String, api_url: String) -> Account {
+        Account {
+            name: String::new(),
+            username,
+            api_url: Some(api_url),
+            password: Some(password),
+            profile: None,
+            client_token: None,
+        }
+    }
+
+    /// What messages and events call the account: its name, or the username of one that
+    /// isn't stored.
+    pub fn alias(&self) -> &str {
+        if self.name.is_empty() {
+            &self.username
+        } else {
+            &self.name
+        }
+    }
+
+    pub fn api_url(&self) -> &str {
+        self.api_url.as_deref().unwrap_or_default()
+    }
+
+    pub fn password(&self) -> &str {
+        self.password.as_deref().unwrap_or_default()
+    }
+
+    /// What the account's cached session is kept under: the username and the server,
+    /// which together are the account on the server.
+    pub fn cache_key(&self) -> String {
+        format!("{}\n{}", self.username.to_lowercase(), self.api_url())
+    }
+
+    /// What the account's password is kept under in the OS credential store.
+    pub fn secret_key(&self) -> String {
+        format!("{}@{}", self.username.to_lowercase(), self.api_url())
+    }
+
+    fn is_same(&self, other: &Account) -> bool {
+        self.username.eq_ignore_ascii_case(&other.username) && self.api_url == other.api_url
+    }
+
+    /// The name for an account that wasn't given one: its player's name, or its login.
+    fn default_name(&self) -> String {
+        self.profile
+            .as_ref()
+            .map_or(&self.username, |profile| &profile.name)
+            .clone()
+    }
+}
+
+/// `accounts.json` in the per-user data directory.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct Accounts {
+    pub accounts: Vec<Account>,
+    /// The name of the account `@` selects.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    /// The account last picked for each instance, picked first next time.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub last_picked: BTreeMap<String, String>,
+}
+
+impl Accounts {
+    pub fn path() -> Option<PathBuf> {
+        Some(paths::data_dir()?.join(ACCOUNTS_FILE_NAME))
+    }
+
+    /// The stored accounts; none when the file doesn't exist yet.
+    pub fn load(path: &Path) -> Result<Accounts> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Accounts::default()),
+            Err(source) => {
+                return Err(MmcaiError::ReadAccountsFailed {
+                    path: path.to_path_buf(),
+                    source,
+                })
+            }
+        };
+        serde_json::from_str(&text).map_err(|e| MmcaiError::InvalidAccounts {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        })
+    }
+
+    /// Readable by the current user only, since it holds login names and tokens.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        serde_json::to_vec_pretty(self)
+            .map_err(io::Error::from)
+            .and_then(|contents| session::write_private(path, &contents))
+            .map_err(|source| MmcaiError::WriteAccountsFailed {
+                path: path.to_path_buf(),
+                source,
+            })
+    }
+
+    /// Adds the account, or updates the stored one with the same username and server.
+    /// What the update doesn't say (name, password, profile) is kept. Returns whether
+    /// it was new.
+    pub fn upsert(&mut self, mut account: Account) -> bool {
+        match self
+            .accounts
+            .iter_mut()
+            .find(|known| known.is_same(&account))
+        {
+            Some(known) => {
+                if account.name.is_empty() {
+                    account.name = known.name.clone();
+                }
+                account.password = account.password.or(known.password.take());
+                account.profile = account.profile.or(known.profile.take());
+                account.client_token = account.client_token.or(known.client_token.take());
+                *known = account;
+                false
+            }
+            None => {
+                if account.name.is_empty() {
+                    account.name = account.default_name();
+                }
+                self.accounts.push(account);
+                true
+            }
+        }
+    }
+
+    pub fn find(&self, name: &str) -> Option<&Account> {
+        self.accounts
+            .iter()
+            .find(|account| account.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Account> {
+        let index = self
+            .accounts
+            .iter()
+            .position(|account| account.name.eq_ignore_ascii_case(name))?;
+        if self
+            .default
+            .as_deref()
+            .is_some_and(|default| default.eq_ignore_ascii_case(name))
+        {
+            self.default = None;
+        }
+        Some(self.accounts.remove(index))
+    }
+
+    /// The default account, or the only one.
+    pub fn default_account(&self) -> Option<&Account> {
+        match &self.default {
+            Some(name) => self.find(name),
+            None if self.accounts.len() == 1 => self.accounts.first(),
+            None => None,
+        }
+    }
+}
+
+pub fn store_path() -> Result<PathBuf> {
+    // without a home directory there is nowhere to keep the accounts
+    Accounts::path().ok_or_else(|| MmcaiError::ReadAccountsFailed {
+        path: ACCOUNTS_FILE_NAME.into(),
+        source: io::ErrorKind::NotFound.into(),
+    })
+}
+
+/// The stored account `selector` asks for when it is `@name` or `@`, `None` for a
+/// plain username. Without a default account, `@` lets the user pick one.
+pub fn lookup(selector: &str, instance: Option<&str>) -> Result<Option<Account>> {
+    let Some(name) = selector.strip_prefix(ACCOUNT_PREFIX) else {
+        return Ok(None);
+    };
+    let path = store_path()?;
+    let mut accounts = load_migrated(&path)?;
+    let mut account = match name {
+        "" if accounts.default_account().is_none() && !accounts.accounts.is_empty() => {
+            pick(&mut accounts, &path, instance)?
+        }
+        "" => accounts.default_account().cloned(),
+        name => accounts.find(name).cloned(),
+    }
+    .ok_or_else(|| MmcaiError::UnknownAccount(selector.to_string()))?;
+    if account.password.is_none() {
+        account.password = secrets::get(&account.secret_key());
+    }
+    Ok(Some(account))
+}
+
+/// The stored accounts, after moving passwords written by older versions into the OS
+/// credential store. Those the store doesn't take stay where they are.
+fn load_migrated(path: &Path) -> Result<Accounts> {
+    let mut accounts = Accounts::load(path)?;
+    let mut moved = false;
+    for account in &mut accounts.accounts {
+        let Some(password) = &account.password else {
+            continue;
+        };
+        match secrets::set(&account.secret_key(), password) {
+            Ok(()) => {
+                account.password = None;
+                moved = true;
+            }
+            Err(e) => warn!(
+                "{}",
+                tr!(
+                    "account-keyring-move-failed",
+                    name = account.name,
+                    error = e
+                )
+            ),
+        }
+    }
+    if moved {
+        accounts.save(path)?;
+    }
+    Ok(accounts)
+}
+
+/// Asks which account to log in with, starting at the one last picked for the instance.
+fn pick(accounts: &mut Accounts, path: &Path, instance: Option<&str>) -> Result<Option<Account>> {
+    let instance = instance.unwrap_or_default().to_string();
+    let items: Vec<String> = accounts
+        .accounts
+        .iter()
+        .map(|account| format!("{} ({})", account.name, account.username))
+        .collect();
+    let selected = accounts
+        .last_picked
+        .get(&instance)
+        .and_then(|name| {
+            accounts
+                .accounts
+                .iter()
+                .position(|account| account.name == *name)
+        })
+        .unwrap_or(0);
+    let picked = picker::pick(&tr!("account-pick"), &items, selected)
+        .and_then(|index| accounts.accounts.get(index))
+        .cloned()
+        .ok_or(MmcaiError::CredentialsNotGiven)?;
+    accounts.last_picked.insert(instance, picked.name.clone());
+    // only costs the preselection next time
+    if let Err(e) = accounts.save(path) {
+        warn!("{}", tr!("account-pick-save-failed", error = e));
+    }
+    Ok(Some(picked))
+}
+
+/// Stores the client token and profile of a stored account after it logged in, so the
+/// next login sends the same token. Failing only costs that, so it is just reported.
+pub fn remember(account: &Account, client_token: &str, profile: AccountProfile) {
+    if account.name.is_empty() {
+        return;
+    }
+    let Some(path) = Accounts::path() else {
+        return;
+    };
+    let saved = Accounts::load(&path).and_then(|mut accounts| {
+        let Some(stored) = accounts
+            .accounts
+            .iter_mut()
+            .find(|stored| stored.name == account.name)
+        else {
+            return Ok(());
+        };
+        if stored.client_token.as_deref() == Some(client_token)
+            && stored.profile.as_ref() == Some(&profile)
+        {
+            return Ok(());
+        }
+        stored.client_token = Some(client_token.to_string());
+        stored.profile = Some(profile);
+        accounts.save(&path)
+    });
+    if let Err(e) = saved {
+        warn!("{}", tr!("account-save-failed", error = e));
+    }
+}
+
+/// Replaces the stored password of the account, if it has one stored.
+pub fn update_password(name: &str, password: &str) -> Result<()> {
+    let path = store_path()?;
+    let accounts = load_migrated(&path)?;
+    let Some(account) = accounts
+        .accounts
+        .iter()
+        .find(|account| account.name == name)
+    else {
+        return Ok(());
+    };
+    let key = account.secret_key();
+    if account.password.is_none() && secrets::get(&key).is_none() {
+        return Ok(());
+    }
+    secrets::set(&key, password).map_err(|source| MmcaiError::WriteAccountsFailed {
+        path: path.clone(),
+        source,
+    })?;
+    // a password the credential store didn't take before is in the file
+    if account.password.is_some() {
+        let mut accounts = accounts;
+        for account in &mut accounts.accounts {
+            if account.name == name {
+                account.password = None;
+            }
+        }
+        accounts.save(&path)?;
+    }
+    Ok(())
+}
+
+/// `mmcai_rs --cmd account ...`: manages the stored accounts.
+pub fn command(args: &[String]) -> Result<()> {
+    let usage = || MmcaiError::CommandUsage(format!("{} --cmd {}", args[0], USAGE));
+    let path = store_path()?;
+    let mut accounts = load_migrated(&path)?;
+    let args: Vec<&str> = args[2..].iter().map(String::as_str).collect();
+    match args[..] {
+        ["add", name, username, ref rest @ ..] if rest.len() <= 1 => {
+            accounts.remove(name);
+            let account = Account {
+                name: name.to_string(),
+                username: username.to_string(),
+                api_url: rest.first().map(|api_url| api_url.to_string()),
+                password: None,
+                profile: None,
+                client_token: None,
+            };
+            // asked for rather than taken from the command line, which other users
+            // and the shell history can see; left empty, it is asked for at launch
+            let password = picker::ask_secret(&tr!("account-password", name = name))
+                .filter(|password| !password.is_empty());
+            if let Some(password) = password {
+                if let Err(e) = secrets::set(&account.secret_key(), &password) {
+                    warn!("{}", tr!("account-keyring-failed", error = e));
+                }
+            }
+            accounts.upsert(account);
+            accounts.save(&path)?;
+            println!("{}", tr!("account-added", name = name));
+        }
+        ["list"] => print!("{}", format_list(&accounts)),
+        ["remove", name] => {
+            let removed = accounts
+                .remove(name)
+                .ok_or_else(|| MmcaiError::UnknownAccount(name.to_string()))?;
+            secrets::delete(&removed.secret_key());
+            accounts.save(&path)?;
+            println!("{}", tr!("account-removed", name = name));
+        }
+        ["set-default", name] => {
+            let name = accounts
+                .find(name)
+                .ok_or_else(|| MmcaiError::UnknownAccount(name.to_string()))?
+                .name
+                .clone();
+            println!("{}", tr!("account-default", name = name));
+            accounts.default = Some(name);
+            accounts.save(&path)?;
+        }
+        _ => return Err(usage()),
+    }
+    Ok(())
+}
+
+fn format_list(accounts: &Accounts) -> String {
+    if accounts.accounts.is_empty() {
+        return format!("{}\n", tr!("account-none"));
+    }
+    list_lines(accounts)
+        .into_iter()
+        .map(|line| line + "\n")
+        .collect()
+}
+
+/// One line per account, the default one marked with `*`.
+pub fn list_lines(accounts: &Accounts) -> Vec<String> {
+    let default = accounts.default_account().map(|account| &account.name);
+    accounts
+        .accounts
+        .iter()
+        .map(|account| {
+            let marker = if Some(&account.name) == default {
+                "*"
+            } else {
+                " "
+            };
+            let mut line = format!("{} {}: {}", marker, account.name, account.username);
+            if let Some(profile) = &account.profile {
+                line += &format!(" ({})", profile.name);
+            }
+            if let Some(api_url) = &account.api_url {
+                line += &format!(" @ {}", api_url);
+            }
+            line
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(username: &str, profile: Option<&str>) -> Account {
+        Account {
+            name: String::new(),
+            username: username.into(),
+            api_url: Some("https://auth.example.com/api".into()),
+            password: None,
+            profile: profile.map(|name| AccountProfile {
+                id: "0f8d".into(),
+                name: name.into(),
+            }),
+            client_token: None,
+        }
+    }
+
+    #[test]
+    fn test_upsert() {
+        let mut accounts = Accounts::default();
+        assert!(accounts.upsert(account("steve@example.com", Some("Steve"))));
+        assert_eq!(accounts.accounts[0].name, "Steve");
+        let renamed = Account {
+            name: "main".into(),
+            password: Some("hunter2".into()),
+            ..account("Steve@Example.com", None)
+        };
+        assert!(!accounts.upsert(renamed));
+        assert!(!accounts.upsert(account("steve@example.com", None)));
+        assert_eq!(accounts.accounts.len(), 1);
+        let stored = &accounts.accounts[0];
+        assert_eq!(stored.name, "main");
+        assert_eq!(stored.password.as_deref(), Some("hunter2"));
+        assert_eq!(stored.profile.as_ref().unwrap().name, "Steve");
+    }
+
+    #[test]
+    fn test_default_account() {
+        let mut accounts = Accounts::default();
+        accounts.upsert(account("steve@example.com", Some("Steve")));
+        assert_eq!(accounts.default_account().unwrap().name, "Steve");
+        accounts.upsert(account("alex@example.com", Some("Alex")));
+        assert_eq!(accounts.default_account(), None);
+        accounts.default = Some("alex".into());
+        assert_eq!(accounts.default_account().unwrap().name, "Alex");
+        assert!(accounts.remove("ALEX").is_some());
+        assert_eq!(accounts.default, None);
+        assert_eq!(
+            format_list(&accounts),
+            "* Steve: steve@example.com (Steve) @ https://auth.example.com/api\n"
+        );
+    }
+
+    #[test]
+    fn test_load_and_save() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let path = dir.path().join("nested").join(ACCOUNTS_FILE_NAME);
+        assert_eq!(Accounts::load(&path).unwrap(), Accounts::default());
+
+        let mut accounts = Accounts::default();
+        accounts.upsert(account("steve@example.com", Some("Steve")));
+        accounts.save(&path).unwrap();
+        assert_eq!(Accounts::load(&path).unwrap(), accounts);
+
+        fs::write(&path, "{").unwrap();
+        assert!(matches!(
+            Accounts::load(&path),
+            Err(MmcaiError::InvalidAccounts { .. })
+        ));
+    }
+}
@@ -0,0 +1,128 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use uuid::Uuid;
+
+use crate::errors::MmcaiError;
+use crate::Result;
+
+/// `CreateProcess` limits the whole command line to 32767 UTF-16 units.
+const WINDOWS_COMMAND_LINE_LIMIT: usize = 32_767;
+/// Linux limits every single argument to `MAX_ARG_STRLEN` (32 pages).
+const UNIX_ARGUMENT_LIMIT: usize = 131_072;
+/// Room for quoting and anything else the estimate doesn't account for.
+const SAFETY_MARGIN: usize = 2048;
+
+/// Whether `program` and `args` can be passed on the command line of this OS.
+pub fn fits_command_line(program: &str, args: &[String]) -> bool {
+    if cfg!(windows) {
+        let length: usize = std::iter::once(program)
+            .chain(args.iter().map(String::as_str))
+            .map(|arg| arg.encode_utf16().count() + 3) // quotes and separator
+            .sum();
+        length + SAFETY_MARGIN <= WINDOWS_COMMAND_LINE_LIMIT
+    } else {
+        args.iter()
+            .all(|arg| arg.len() + SAFETY_MARGIN <= UNIX_ARGUMENT_LIMIT)
+    }
+}
+
+/// Whether Java `major_version` reads `@argfile`s, which arrived in Java 9.
+pub fn is_supported(major_version: u32) -> bool {
+    major_version >= 9
+}
+
+/// A java `@argfile` in the temp directory, removed again when dropped.
+pub struct ArgFile {
+    path: PathBuf,
+}
+
+impl ArgFile {
+    pub fn create(args: &[String]) -> Result<ArgFile> {
+        let path = env::temp_dir().join(format!("mmcai_rs-{}.args", Uuid::new_v4()));
+        let contents: Vec<String> = args.iter().map(|arg| quote(arg)).collect();
+        fs::write(&path, contents.join("\n")).map_err(|source| MmcaiError::WriteArgFileFailed {
+            path: path.clone(),
+            source,
+        })?;
+        Ok(ArgFile { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The argument that makes java read the file, e.g. `@/tmp/mmcai_rs-....args`.
+    pub fn argument(&self) -> String {
+        format!("@{}", self.path.display())
+    }
+}
+
+impl Drop for ArgFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Quotes an argument for a java argfile, where whitespace separates arguments and
+/// backslashes escape inside quotes.
+fn quote(arg: &str) -> String {
+    if !arg.is_empty()
+        && !arg
+            .contains(|c: char| c.is_whitespace() || c == '"' || c == '\'' || c == '\\' || c == '#')
+    {
+        return arg.to_string();
+    }
+    let escaped = arg.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("\"{}\"", escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fits_command_line() {
+        let small = vec!["-Xmx4G".to_string(); 10];
+        assert!(fits_command_line("java", &small));
+        let huge = vec![format!(
+            "-Dauthlibinjector.yggdrasil.prefetched={}",
+            "A".repeat(200_000)
+        )];
+        assert!(!fits_command_line("java", &huge));
+    }
+
+    #[test]
+    fn test_is_supported() {
+        assert!(!is_supported(8));
+        assert!(is_supported(9));
+        assert!(is_supported(21));
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("-Xmx4G"), "-Xmx4G");
+        assert_eq!(quote(""), "\"\"");
+        assert_eq!(
+            quote("C:\\Program Files\\x"),
+            "\"C:\\\\Program Files\\\\x\""
+        );
+        assert_eq!(quote("say \"hi\""), "\"say \\\"hi\\\"\"");
+    }
+
+    #[test]
+    fn test_arg_file() {
+        let args = vec!["-Da=b".to_string(), "-Dpath=with space".to_string()];
+        let arg_file = ArgFile::create(&args).unwrap();
+        let path = arg_file.path().to_path_buf();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "-Da=b\n\"-Dpath=with space\""
+        );
+        assert_eq!(arg_file.argument(), format!("@{}", path.display()));
+        drop(arg_file);
+        assert!(!path.exists());
+    }
+}
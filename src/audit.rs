@@ -0,0 +1,161 @@
+use std::{
+    fs,
+    io::{self, Write},
+    net::IpAddr,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use tracing::warn;
+
+use crate::errors::MmcaiError;
+use crate::i18n::tr;
+use crate::{dates, paths};
+
+const AUDIT_FILE_NAME: &str = "audit.log";
+
+/// How an authentication attempt ended.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    Success,
+    /// The server answered and refused the credentials.
+    Rejected,
+    RateLimited,
+    Unreachable,
+    /// Launched with the cached session instead of logging in.
+    Offline,
+    Failed,
+}
+
+impl Outcome {
+    pub fn of_error(error: &MmcaiError) -> Outcome {
+        match error {
+            MmcaiError::InvalidCredentials { .. }
+            | MmcaiError::AccountNotActivated { .. }
+            | MmcaiError::AccountBlocked { .. }
+            | MmcaiError::AccountLocked { .. }
+            | MmcaiError::LoginRejected { .. } => Outcome::Rejected,
+            MmcaiError::RateLimited { .. } => Outcome::RateLimited,
+            e if e.is_unreachable() => Outcome::Unreachable,
+            _ => Outcome::Failed,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Outcome::Success => "success",
+            Outcome::Rejected => "rejected",
+            Outcome::RateLimited => "rate_limited",
+            Outcome::Unreachable => "unreachable",
+            Outcome::Offline => "offline",
+            Outcome::Failed => "failed",
+        }
+    }
+}
+
+/// One authentication attempt, as written to the audit log.
+pub struct Entry<'a> {
+    pub account: &'a str,
+    pub server: &'a str,
+    pub outcome: Outcome,
+    /// The address the signin response came from, when the connection exposes it.
+    pub server_ip: Option<IpAddr>,
+}
+
+/// `audit.log` in the per-user data directory.
+pub fn path() -> Option<PathBuf> {
+    Some(paths::data_dir()?.join(AUDIT_FILE_NAME))
+}
+
+/// Appends the attempt to the audit log. Failing to do so is reported but never stops
+/// the launch.
+pub fn record(entry: &Entry) {
+    let Some(path) = path() else {
+        return;
+    };
+    if let Err(e) = append(&path, &format(SystemTime::now(), entry)) {
+        let path = format!("{:?}", path);
+        warn!("{}", tr!("audit-write-failed", path = path, error = e));
+    }
+}
+
+/// When the account last logged in successfully, as recorded in the audit log.
+pub fn last_success(account: &str) -> Option<String> {
+    let log = fs::read_to_string(path()?).ok()?;
+    last_success_in(&log, account).map(str::to_string)
+}
+
+fn last_success_in<'a>(log: &'a str, account: &str) -> Option<&'a str> {
+    log.lines().rev().find_map(|line| {
+        let fields: Vec<&str> = line.split('\t').collect();
+        match fields[..] {
+            [time, logged, _, outcome, ..]
+                if logged.eq_ignore_ascii_case(account) && outcome == Outcome::Success.name() =>
+            {
+                Some(time)
+            }
+            _ => None,
+        }
+    })
+}
+
+fn append(path: &Path, line: &str) -> io::Result<()> {
+    let mut file = paths::append_private(path)?;
+    writeln!(file, "{}", line)
+}
+
+/// Tab-separated, so the line survives account names with spaces.
+fn format(time: SystemTime, entry: &Entry) -> String {
+    let server_ip = entry
+        .server_ip
+        .map_or_else(|| "-".to_string(), |ip| ip.to_string());
+    format!(
+        "{}\t{}\t{}\t{}\t{}",
+        dates::format_iso8601(time),
+        entry.account.replace(['\t', '\n', '\r'], " "),
+        entry.server,
+        entry.outcome.name(),
+        server_ip
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        let time = dates::from_civil(2025, 4, 1, 12, 30, 0).unwrap();
+        let entry = Entry {
+            account: "steve@example.com",
+            server: "https://auth.example.com/api/yggdrasil",
+            outcome: Outcome::Success,
+            server_ip: Some("203.0.113.7".parse().unwrap()),
+        };
+        assert_eq!(
+            format(time, &entry),
+            "2025-04-01T12:30:00Z\tsteve@example.com\thttps://auth.example.com/api/yggdrasil\tsuccess\t203.0.113.7"
+        );
+
+        let entry = Entry {
+            account: "tab\tbed",
+            outcome: Outcome::of_error(&MmcaiError::RateLimited { retry_after: 5 }),
+            server_ip: None,
+            ..entry
+        };
+        assert!(format(time, &entry)
+            .ends_with("\ttab bed\thttps://auth.example.com/api/yggdrasil\trate_limited\t-"));
+    }
+
+    #[test]
+    fn test_last_success() {
+        let log = "2025-04-01T12:30:00Z\tsteve@example.com\thttps://a\tsuccess\t-\n\
+                   2025-04-02T08:00:00Z\tSteve@example.com\thttps://a\trejected\t-\n\
+                   2025-04-03T09:00:00Z\talex@example.com\thttps://a\tsuccess\t-\n";
+        assert_eq!(
+            last_success_in(log, "steve@example.com"),
+            Some("2025-04-01T12:30:00Z")
+        );
+        assert_eq!(last_success_in(log, "herobrine"), None);
+    }
+}
@@ -0,0 +1,740 @@
+use std::io::{self, IsTerminal};
+use std::net::IpAddr;
+
+use base64::prelude::*;
+use futures_util::future;
+use reqwest::header;
+use serde::Serialize;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::accounts::{Account, AccountProfile};
+use crate::backend::Credentials;
+use crate::client::{Session, YggdrasilClient};
+use crate::config::Config;
+use crate::errors::MmcaiError;
+use crate::http::HttpClient;
+use crate::i18n::tr;
+use crate::instance::Instance;
+use crate::model::{AuthData, AuthRequest, AuthResponse, Profile};
+use crate::session::CachedSession;
+use crate::timing::{Stage, Timings};
+use crate::{
+    accounts, audit, dates, dialog, health, instance, metadata, netdiag, notice, picker, pinning,
+    retry, session, skin, Result,
+};
+
+#[derive(Debug)]
+pub struct LoginResult {
+    /// The API URL that answered, which is also the one handed to authlib-injector.
+    pub api_url: String,
+    /// `None` when prefetching is disabled and authlib-injector fetches the metadata itself.
+    pub prefetched_data: Option<String>,
+    pub access_token: String,
+    pub expired_date: Option<String>,
+    pub selected_profile: Profile,
+    /// Only known after logging in with the server, not for a reused session.
+    pub textures: skin::Textures,
+    /// Where the signin response came from, for the audit log.
+    pub server_ip: Option<IpAddr>,
+}
+
+/// Written instead of the username or password in the wrapper command to be asked for
+/// it in a dialog, so the password doesn't have to be kept in Prism's settings.
+pub const ASK_CREDENTIAL: &str = "-";
+
+/// The account with its username and password asked for when they are `-` or not
+/// known, like a stored account without a password in the credential store: on the
+/// terminal when stdin is one, in a dialog otherwise, since Prism starts the wrapper
+/// without a console.
+pub fn credentials(account: Account) -> Result<Account> {
+    fn given(value: Option<&str>) -> Option<&str> {
+        value.filter(|value| !value.is_empty() && *value != ASK_CREDENTIAL)
+    }
+    let (username, password) = (
+        given(Some(account.username.as_str())),
+        given(account.password.as_deref()),
+    );
+    if username.is_some() && password.is_some() {
+        return Ok(account);
+    }
+    let api_url = account.api_url();
+    let server = reqwest::Url::parse(api_url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_else(|| api_url.to_string());
+    let asked = if io::stdin().is_terminal() {
+        ask_on_terminal(&server, username, password)
+    } else {
+        dialog::ask_credentials(&server, username, password)
+    };
+    let (username, password) = asked.ok_or(MmcaiError::CredentialsNotGiven)?;
+    Ok(Account {
+        username,
+        password: Some(password),
+        ..account
+    })
+}
+
+fn ask_on_terminal(
+    server: &str,
+    username: Option<&str>,
+    password: Option<&str>,
+) -> Option<(String, String)> {
+    let username = match username {
+        Some(username) => username.to_string(),
+        None => {
+            eprint!("{} ", tr!("dialog-username", server = server));
+            let mut line = String::new();
+            io::stdin().read_line(&mut line).ok()?;
+            Some(line.trim().to_string()).filter(|line| !line.is_empty())?
+        }
+    };
+    let password = match password {
+        Some(password) => password.to_string(),
+        None => picker::ask_secret(&tr!(
+            "dialog-password",
+            username = username,
+            server = server
+        ))?,
+    };
+    Some((username, password))
+}
+
+/// The account to log in with, from the wrapper command's `<username> <password>
+/// <api url>` or its `--account`. instance.cfg's `[mmcai]` section wins over the
+/// wrapper command, and a stored account (`@name`, its `account` setting or the
+/// instance's entry in the config's `[instances]`) fills in what it has.
+///
+/// instance.cfg travels with exported instances, so its `api_url` is only used for
+/// the instance's own `username` and `password`: a shared instance must not send the
+/// user's password to a server of its choosing.
+pub fn wrapper_account(
+    arguments: &[String],
+    selector: Option<&str>,
+    instance: Option<&Instance>,
+    config: &Config,
+) -> Result<Account> {
+    let setting = |key, argument: &String| {
+        let setting = instance.and_then(|instance| instance.setting(key));
+        setting.map_or_else(|| argument.clone(), str::to_string)
+    };
+    let stored = |name: &str| {
+        let name = name.trim_start_matches(accounts::ACCOUNT_PREFIX);
+        format!("{}{}", accounts::ACCOUNT_PREFIX, name)
+    };
+    let instance_name = instance::name(instance);
+    let username = match instance.and_then(|instance| instance.setting("account")) {
+        Some(name) => stored(name),
+        None if instance
+            .and_then(|instance| instance.setting("username"))
+            .is_some() =>
+        {
+            setting("username", &arguments[0])
+        }
+        None => config
+            .instance_account(instance::id().as_deref(), instance_name.as_deref())
+            .or_else(|| selector.map(stored))
+            .unwrap_or_else(|| arguments[0].clone()),
+    };
+    let password = setting("password", &arguments[1]);
+    let instance_api_url = instance.and_then(|instance| instance.setting("api_url"));
+    let ignore_instance_api_url = || {
+        if let Some(api_url) = instance_api_url {
+            warn!("{}", tr!("instance-api-url-ignored", url = api_url));
+        }
+        arguments[2].clone()
+    };
+    Ok(
+        match accounts::lookup(&username, instance_name.as_deref())? {
+            // the stored password only ever goes to the account's own server
+            Some(account) => Account {
+                password: account.password.or(Some(password)),
+                api_url: account.api_url.or_else(|| Some(ignore_instance_api_url())),
+                ..account
+            },
+            None => {
+                let own_credentials = instance.is_some_and(|instance| {
+                    instance.setting("username").is_some() && instance.setting("password").is_some()
+                });
+                let api_url = match instance_api_url {
+                    Some(api_url) if own_credentials => api_url.to_string(),
+                    _ => ignore_instance_api_url(),
+                };
+                Account::unstored(username, password, api_url)
+            }
+        },
+    )
+}
+
+/// The stored account's server, or the one of a backend bound to a single service.
+pub fn stored_api_url(account: &Account, config: &Config) -> Result<String> {
+    match &account.api_url {
+        Some(api_url) => Ok(api_url.clone()),
+        None => config
+            .backend()?
+            .default_api_url()
+            .map(str::to_string)
+            .ok_or_else(|| MmcaiError::AccountWithoutServer(account.name.clone())),
+    }
+}
+
+pub fn generate_client_token() -> String {
+    Uuid::new_v4().to_string()
+}
+
+/// The headers of requests to the server's `/auth` endpoints.
+pub fn auth_headers() -> header::HeaderMap {
+    let mut headers = header::HeaderMap::new();
+    headers.insert(
+        "User-Agent",
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:138.0) Gecko/20100101 Firefox/138.0"
+            .parse()
+            .unwrap(),
+    );
+    headers.insert("Accept", "application/json".parse().unwrap());
+    headers.insert("Accept-Language", "en-US,en;q=0.5".parse().unwrap());
+    headers.insert("Content-Type", "application/json".parse().unwrap());
+    headers
+}
+
+pub async fn yggdrasil_login(
+    username: &str,
+    password: &str,
+    client_token: &str,
+    api_url: &str,
+    http: &HttpClient,
+    config: &Config,
+    timings: &Timings<'_>,
+) -> Result<LoginResult> {
+    if config.server.health_check {
+        health::check(http, api_url).await?;
+    }
+    let api_root = metadata::resolve_api_root(http, api_url).await?;
+    let login = |api_root| {
+        login_at(
+            username,
+            password,
+            client_token,
+            api_root,
+            http,
+            config,
+            timings,
+        )
+    };
+    match login(&api_root).await {
+        // the remembered API location may be out of date, look it up again
+        Err(e) if e.is_unreachable() && metadata::forget_api_root(api_url) => {
+            warn!("{}", tr!("api-location-stale", url = api_root));
+            let api_root = metadata::resolve_api_root(http, api_url).await?;
+            login(&api_root).await
+        }
+        result => result,
+    }
+}
+
+async fn login_at(
+    username: &str,
+    password: &str,
+    client_token: &str,
+    api_url: &str,
+    http: &HttpClient,
+    config: &Config,
+    timings: &Timings<'_>,
+) -> Result<LoginResult> {
+    let backend = config.backend()?;
+    let client = YggdrasilClient::for_api_root(http, api_url, client_token, config);
+
+    // 1. Fetch the metadata for -Dauthlibinjector.yggdrasil.prefetched
+    let get_prefetched_data = || async {
+        if !config.server.prefetch {
+            return Ok(None);
+        }
+        let prefetched_data_text = metadata::fetch_metadata(http, api_url, config).await?;
+        if let Some(notice) = notice::from_metadata(&prefetched_data_text) {
+            notice.print();
+        }
+        Result::Ok(Some(BASE64_STANDARD.encode(prefetched_data_text)))
+    };
+
+    // 2. Log in with the configured backend, POST /auth/signin by default
+    let credentials = Credentials { username, password };
+    let perform_authentication = || backend.authenticate(&client, &credentials);
+
+    // The two requests are independent, so they run in parallel. Certificate pins are
+    // checked in each connection's handshake, so the password needn't wait for them.
+    let (prefetched_data, session) = future::try_join(
+        timings.measure(Stage::Prefetch, get_prefetched_data()),
+        timings.measure(Stage::Signin, perform_authentication()),
+    )
+    .await?;
+
+    let mut selected_profile = select_profile(&session, config.server.profile.as_deref()).await?;
+    // the token is bound to the server's choice, another profile needs its own
+    let session = if uuid_eq(&selected_profile.id, &session.selected_profile.id) {
+        session
+    } else {
+        client.select_profile(&session, &selected_profile).await?
+    };
+    selected_profile.id = config
+        .game
+        .uuid_format
+        .apply(&selected_profile.id)
+        .ok_or_else(|| MmcaiError::InvalidAuthResponse {
+            message: "the profile id is not a UUID".into(),
+            response: selected_profile.id.clone(),
+        })?;
+    Ok(LoginResult {
+        api_url: api_url.to_string(),
+        prefetched_data,
+        access_token: session.access_token,
+        expired_date: session.expired_date,
+        selected_profile,
+        textures: session.textures.unwrap_or_default(),
+        server_ip: session.server_ip,
+    })
+}
+
+/// Posts the request to the server's `/auth/signin` and returns the account data, with
+/// the address the answer came from.
+pub async fn signin(
+    http: &HttpClient,
+    signin_url: &str,
+    headers: header::HeaderMap,
+    auth_body: &AuthRequest<'_>,
+    strict: bool,
+) -> Result<(AuthData, Option<IpAddr>)> {
+    let signin_response = http
+        .send(|client| {
+            client
+                .post(signin_url)
+                .headers(headers.clone())
+                .json(auth_body)
+        })
+        .await;
+    let response = signin_response.map_err(|e| {
+        pinning::classify(e, |source| MmcaiError::YggdrasilAuthFailed {
+            source,
+            response: "<request failed, no response body>".into(),
+        })
+    })?;
+    retry::check_rate_limit(&response)?;
+    let server_ip = response.remote_addr().map(|address| address.ip());
+    let body = response
+        .text()
+        .await
+        .map_err(|source| MmcaiError::YggdrasilAuthFailed {
+            source,
+            response: "<failed to read response body>".into(),
+        })?;
+    let auth_response = AuthResponse::parse(&body, strict)?;
+    Ok((auth_response.into_data()?, server_ip))
+}
+
+/// The profile to play as: the preferred one (by name or UUID) when given, otherwise
+/// the one the user picks when the account has several. The server's choice is used
+/// for accounts with a single profile, and when the user closes the picker.
+async fn select_profile(session: &Session, preference: Option<&str>) -> Result<Profile> {
+    let default = session.selected_profile.clone();
+    let profiles = &session.available_profiles;
+    if let Some(preference) = preference {
+        let mut candidates = std::iter::once(&default).chain(profiles);
+        return candidates
+            .find(|profile| {
+                profile.name.eq_ignore_ascii_case(preference) || uuid_eq(&profile.id, preference)
+            })
+            .cloned()
+            .ok_or_else(|| MmcaiError::UnknownProfile {
+                name: preference.to_string(),
+                available: profile_names(&default, profiles),
+            });
+    }
+    if profiles.len() < 2 {
+        return Ok(default);
+    }
+    let items: Vec<String> = profiles
+        .iter()
+        .map(|profile| profile.name.clone())
+        .collect();
+    let selected = profiles
+        .iter()
+        .position(|profile| uuid_eq(&profile.id, &default.id))
+        .unwrap_or(0);
+    // the picker waits for the user, so it mustn't hold up the params reader
+    let picked =
+        tokio::task::spawn_blocking(move || picker::pick(&tr!("profile-pick"), &items, selected))
+            .await
+            .ok()
+            .flatten();
+    Ok(picked
+        .and_then(|index| profiles.get(index))
+        .cloned()
+        .unwrap_or(default))
+}
+
+/// The account's profile names for messages, the server's choice first.
+fn profile_names(default: &Profile, profiles: &[Profile]) -> String {
+    let mut names = vec![default.name.as_str()];
+    for profile in profiles {
+        if !names.contains(&profile.name.as_str()) {
+            names.push(&profile.name);
+        }
+    }
+    names.join(", ")
+}
+
+/// UUIDs compare the same with and without dashes, in any case.
+fn uuid_eq(a: &str, b: &str) -> bool {
+    a.replace('-', "").eq_ignore_ascii_case(&b.replace('-', ""))
+}
+
+/// Tries the API URL from the command line, then every configured fallback, until one
+/// of them can be reached. A server that answers and rejects the login ends the search.
+pub async fn login_with_fallback(
+    account: &Account,
+    client_token: &str,
+    api_urls: &[&str],
+    http: &HttpClient,
+    config: &Config,
+    timings: &Timings<'_>,
+) -> Result<LoginResult> {
+    let configured_api_url = api_urls.first().ok_or(MmcaiError::Other)?;
+    let mut api_urls = api_urls.iter().peekable();
+    while let Some(api_url) = api_urls.next() {
+        let result = yggdrasil_login(
+            &account.username,
+            account.password(),
+            client_token,
+            api_url,
+            http,
+            config,
+            timings,
+        )
+        .await;
+        if config.audit.enabled {
+            audit::record(&audit::Entry {
+                account: &account.username,
+                server: api_url,
+                outcome: match &result {
+                    Ok(_) => audit::Outcome::Success,
+                    Err(e) => audit::Outcome::of_error(e),
+                },
+                server_ip: result.as_ref().ok().and_then(|result| result.server_ip),
+            });
+        }
+        match result {
+            Err(e) if e.is_unreachable() => match api_urls.peek() {
+                Some(next) => {
+                    warn!(
+                        "{}",
+                        tr!(
+                            "server-unreachable-trying",
+                            url = api_url,
+                            error = e,
+                            next = next
+                        )
+                    )
+                }
+                None => {
+                    if config.server.diagnose_network {
+                        if let Some(diagnosis) = netdiag::diagnose(http, configured_api_url).await {
+                            diagnosis.print();
+                        }
+                    }
+                    let offline = offline_login(account, config);
+                    if offline.is_some() && config.audit.enabled {
+                        audit::record(&audit::Entry {
+                            account: &account.username,
+                            server: configured_api_url,
+                            outcome: audit::Outcome::Offline,
+                            server_ip: None,
+                        });
+                    }
+                    return offline.ok_or(e);
+                }
+            },
+            Ok(login_result) => {
+                let profile = &login_result.selected_profile;
+                accounts::remember(
+                    account,
+                    client_token,
+                    AccountProfile {
+                        id: profile.id.clone(),
+                        name: profile.name.clone(),
+                    },
+                );
+                if config.offline.enabled {
+                    session::store(
+                        &account.cache_key(),
+                        &CachedSession {
+                            api_url: login_result.api_url.clone(),
+                            access_token: login_result.access_token.clone(),
+                            uuid: login_result.selected_profile.id.clone(),
+                            name: login_result.selected_profile.name.clone(),
+                            expires_at: session::parse_expiry(login_result.expired_date.as_deref()),
+                            obtained_at: dates::unix_now(),
+                        },
+                    );
+                }
+                return Ok(login_result);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Err(MmcaiError::Other)
+}
+
+/// Rebuilds the login from the cached session and metadata while the auth server is
+/// unreachable, if the offline fallback is enabled and the session is still usable.
+fn offline_login(account: &Account, config: &Config) -> Option<LoginResult> {
+    if !config.offline.enabled {
+        return None;
+    }
+    let session = session::load(&account.cache_key())?;
+    if !session.is_usable(config.offline.max_age_hours * 3600, dates::unix_now()) {
+        info!("{}", tr!("offline-session-expired"));
+        return None;
+    }
+    let prefetched_data = if config.server.prefetch {
+        let Some(metadata) = metadata::cached_metadata(&session.api_url) else {
+            info!("{}", tr!("offline-no-metadata"));
+            return None;
+        };
+        Some(BASE64_STANDARD.encode(metadata))
+    } else {
+        None
+    };
+
+    let minutes = dates::unix_now().saturating_sub(session.obtained_at) / 60;
+    warn!("{}", tr!("offline-launch", minutes = minutes));
+    Some(LoginResult {
+        api_url: session.api_url,
+        prefetched_data,
+        access_token: session.access_token,
+        expired_date: None,
+        selected_profile: Profile {
+            // cached with the format of the time
+            id: config
+                .game
+                .uuid_format
+                .apply(&session.uuid)
+                .unwrap_or(session.uuid),
+            name: session.name,
+        },
+        textures: skin::Textures::default(),
+        server_ip: None,
+    })
+}
+
+/// The server's password change, next to its signin endpoint. `code` is the one-time
+/// code of accounts with two-factor authentication.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangePasswordRequest<'a> {
+    pub login: &'a str,
+    pub old_password: &'a str,
+    pub new_password: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<&'a str>,
+}
+
+/// Logs in with a stored account (`@` or `None` for the default one), for commands
+/// that need its access token rather than a launch.
+pub async fn login_stored(
+    selector: Option<&str>,
+    http: &HttpClient,
+    config: &Config,
+    timings: &Timings<'_>,
+) -> Result<(Account, LoginResult)> {
+    let name = selector.unwrap_or_default();
+    let selector = format!(
+        "{}{}",
+        accounts::ACCOUNT_PREFIX,
+        name.trim_start_matches(accounts::ACCOUNT_PREFIX)
+    );
+    let account = accounts::lookup(&selector, instance::name(None).as_deref())?
+        .ok_or_else(|| MmcaiError::UnknownAccount(selector.clone()))?;
+    let api_url = stored_api_url(&account, config)?;
+    let account = credentials(Account {
+        api_url: Some(api_url),
+        ..account
+    })?;
+    let client_token = account
+        .client_token
+        .clone()
+        .unwrap_or_else(generate_client_token);
+    let login_result = login_with_fallback(
+        &account,
+        &client_token,
+        &[account.api_url()],
+        http,
+        config,
+        timings,
+    )
+    .await?;
+    Ok((account, login_result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_client_token() {
+        let client_token = generate_client_token();
+        assert_eq!(client_token.len(), 36);
+    }
+
+    #[test]
+    fn test_credentials_given() {
+        let account = Account::unstored(
+            "steve".into(),
+            "hunter2".into(),
+            "https://auth.example.com/api".into(),
+        );
+        assert_eq!(credentials(account.clone()).unwrap(), account);
+        assert_eq!(account.alias(), "steve");
+        assert_eq!(account.cache_key(), "steve\nhttps://auth.example.com/api");
+    }
+
+    #[test]
+    fn test_wrapper_account_instance_api_url() {
+        let arguments: Vec<String> = ["steve", "hunter2", "https://auth.example.com/api"]
+            .map(String::from)
+            .to_vec();
+        let instance = |settings: &[(&str, &str)]| Instance {
+            name: None,
+            java_path: None,
+            mmcai: settings
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        };
+        let api_url = |instance: &Instance| {
+            wrapper_account(&arguments, None, Some(instance), &Config::default())
+                .unwrap()
+                .api_url()
+                .to_string()
+        };
+
+        let evil = ("api_url", "https://evil.example/api");
+        assert_eq!(api_url(&instance(&[evil])), "https://auth.example.com/api");
+        assert_eq!(
+            api_url(&instance(&[("username", "alex"), evil])),
+            "https://auth.example.com/api"
+        );
+        assert_eq!(
+            api_url(&instance(&[("username", "alex"), ("password", "-"), evil])),
+            "https://evil.example/api"
+        );
+    }
+
+    #[test]
+    fn test_wrapper_account_instance_api_url_stored() {
+        let arguments: Vec<String> = ["steve", "hunter2", "https://auth.example.com/api"]
+            .map(String::from)
+            .to_vec();
+        let mut accounts = accounts::Accounts::default();
+        for (name, api_url) in [
+            ("nourl", None),
+            ("withurl", Some("https://own.example/api")),
+        ] {
+            accounts.accounts.push(Account {
+                name: name.into(),
+                api_url: api_url.map(String::from),
+                password: None,
+                ..Account::unstored("steve".into(), String::new(), String::new())
+            });
+        }
+        accounts.save(&accounts::store_path().unwrap()).unwrap();
+
+        let api_url = |settings: &[(&str, &str)]| {
+            let instance = Instance {
+                name: None,
+                java_path: None,
+                mmcai: settings
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect(),
+            };
+            wrapper_account(&arguments, None, Some(&instance), &Config::default())
+                .unwrap()
+                .api_url()
+                .to_string()
+        };
+
+        // the stored account's password must not go to the instance's server
+        let evil = ("api_url", "https://evil.example/api");
+        assert_eq!(
+            api_url(&[("username", "@nourl"), ("password", "x"), evil]),
+            "https://auth.example.com/api"
+        );
+        assert_eq!(
+            api_url(&[
+                ("account", "nourl"),
+                ("username", "alex"),
+                ("password", "x"),
+                evil
+            ]),
+            "https://auth.example.com/api"
+        );
+        assert_eq!(
+            api_url(&[("username", "@withurl"), ("password", "x"), evil]),
+            "https://own.example/api"
+        );
+    }
+
+    #[test]
+    fn test_stored_api_url() {
+        let account = Account {
+            api_url: None,
+            ..Account::unstored("steve".into(), "hunter2".into(), String::new())
+        };
+        let mut config = Config::default();
+        config.server.backend = "marallys".into();
+        assert!(matches!(
+            stored_api_url(&account, &config),
+            Err(MmcaiError::AccountWithoutServer(_))
+        ));
+        config.server.backend = "elyby".into();
+        assert_eq!(
+            stored_api_url(&account, &config).unwrap(),
+            "https://authserver.ely.by/api/authlib-injector"
+        );
+    }
+
+    #[test]
+    fn test_select_profile() {
+        let body = r#"{"data":{"uuid":"a1","name":"Steve","accessToken":"t","availableProfiles":[
+            {"id":"a1","name":"Steve"},{"id":"0f8d-b2","name":"Alex"}]}}"#;
+        let auth_data = AuthResponse::parse(body, true)
+            .unwrap()
+            .into_data()
+            .unwrap();
+        let session = Session::from_auth_data(auth_data, "c".into(), None);
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let select = |preference| runtime.block_on(select_profile(&session, Some(preference)));
+        assert_eq!(select("alex").unwrap().id, "0f8d-b2");
+        assert_eq!(select("0F8DB2").unwrap().name, "Alex");
+        assert!(matches!(
+            select("Herobrine"),
+            Err(MmcaiError::UnknownProfile { available, .. }) if available == "Steve, Alex"
+        ));
+    }
+
+    #[test]
+    fn test_change_password_request() {
+        let request = ChangePasswordRequest {
+            login: "steve",
+            old_password: "hunter2",
+            new_password: "correct horse",
+            code: None,
+        };
+        assert_eq!(
+            serde_json::to_string(&request).unwrap(),
+            r#"{"login":"steve","oldPassword":"hunter2","newPassword":"correct horse"}"#
+        );
+    }
+}
@@ -0,0 +1,332 @@
+use std::{collections::BTreeMap, fmt};
+
+use futures_util::future::BoxFuture;
+use reqwest::Url;
+
+use crate::client::{Session, Status, YggdrasilClient};
+use crate::config::Config;
+use crate::errors::MmcaiError;
+use crate::model::AuthRequest;
+use crate::Result;
+
+const MARALLYS_SIGNIN: &str = "../../auth/signin";
+const MARALLYS_CHANGE_PASSWORD: &str = "../../auth/change-password";
+/// What Marallys takes as `accessToken` when signing in without a session.
+const MARALLYS_SIGNIN_ACCESS_TOKEN: &str = "null";
+const ELYBY_API_URL: &str = "https://authserver.ely.by/api/authlib-injector";
+
+/// The endpoints some backends use besides the standard Yggdrasil ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Endpoint {
+    Signin,
+    ChangePassword,
+}
+
+impl Endpoint {
+    /// The key in `[server.endpoints]`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Endpoint::Signin => "signin",
+            Endpoint::ChangePassword => "change_password",
+        }
+    }
+}
+
+pub struct Credentials<'a> {
+    pub username: &'a str,
+    pub password: &'a str,
+}
+
+/// A way of logging in to an auth server. The wrapper picks one by `server.backend`;
+/// launchers using the crate can register their own, e.g. for Microsoft accounts.
+///
+/// Only `authenticate` differs between the built-in backends: tokens are refreshed,
+/// validated and invalidated with the standard Yggdrasil endpoints unless overridden.
+pub trait AuthBackend: Send + Sync {
+    /// The name `server.backend` selects the backend by.
+    fn name(&self) -> &'static str;
+
+    /// The API URL to use when none is given, for backends bound to one service.
+    fn default_api_url(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Where the endpoint is for the usual layout of the backend's servers, as a template
+    /// like in `[server.endpoints]`. `None` when the backend doesn't have it.
+    fn endpoint(&self, endpoint: Endpoint) -> Option<&'static str> {
+        let _ = endpoint;
+        None
+    }
+
+    fn authenticate<'a>(
+        &'a self,
+        client: &'a YggdrasilClient,
+        credentials: &'a Credentials<'a>,
+    ) -> BoxFuture<'a, Result<Session>>;
+
+    fn refresh<'a>(
+        &'a self,
+        client: &'a YggdrasilClient,
+        session: &'a Session,
+    ) -> BoxFuture<'a, Result<Session>> {
+        Box::pin(client.refresh(session))
+    }
+
+    fn validate<'a>(
+        &'a self,
+        client: &'a YggdrasilClient,
+        session: &'a Session,
+    ) -> BoxFuture<'a, Status> {
+        Box::pin(client.validate(&session.access_token, Some(&session.client_token)))
+    }
+
+    fn invalidate<'a>(
+        &'a self,
+        client: &'a YggdrasilClient,
+        session: &'a Session,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(client.invalidate(&session.access_token, &session.client_token))
+    }
+}
+
+/// Marallys' `/auth/signin`, which answers with the skin and cloak as well.
+pub struct Marallys;
+
+impl Marallys {
+    /// The signin request, with the `accessToken` from `server.signin_access_token`, or
+    /// the placeholder the server expects.
+    fn signin_request<'a>(config: &'a Config, credentials: &'a Credentials<'a>) -> AuthRequest<'a> {
+        let access_token = config
+            .server
+            .signin_access_token
+            .as_deref()
+            .unwrap_or(MARALLYS_SIGNIN_ACCESS_TOKEN);
+        AuthRequest::new(credentials.username, credentials.password).with_access_token(access_token)
+    }
+}
+
+impl AuthBackend for Marallys {
+    fn name(&self) -> &'static str {
+        "marallys"
+    }
+
+    fn endpoint(&self, endpoint: Endpoint) -> Option<&'static str> {
+        Some(match endpoint {
+            Endpoint::Signin => MARALLYS_SIGNIN,
+            Endpoint::ChangePassword => MARALLYS_CHANGE_PASSWORD,
+        })
+    }
+
+    fn authenticate<'a>(
+        &'a self,
+        client: &'a YggdrasilClient,
+        credentials: &'a Credentials<'a>,
+    ) -> BoxFuture<'a, Result<Session>> {
+        Box::pin(async move {
+            let endpoint = template(self, client.config(), Endpoint::Signin)?;
+            let request = Marallys::signin_request(client.config(), credentials);
+            client.signin(endpoint, &request).await
+        })
+    }
+}
+
+/// Any authlib-injector server implementing the standard Yggdrasil API.
+pub struct Yggdrasil;
+
+impl AuthBackend for Yggdrasil {
+    fn name(&self) -> &'static str {
+        "yggdrasil"
+    }
+
+    fn authenticate<'a>(
+        &'a self,
+        client: &'a YggdrasilClient,
+        credentials: &'a Credentials<'a>,
+    ) -> BoxFuture<'a, Result<Session>> {
+        Box::pin(client.authserver_authenticate(credentials.username, credentials.password))
+    }
+}
+
+/// Ely.by, a standard Yggdrasil server at a well-known URL.
+pub struct ElyBy;
+
+impl AuthBackend for ElyBy {
+    fn name(&self) -> &'static str {
+        "elyby"
+    }
+
+    fn default_api_url(&self) -> Option<&'static str> {
+        Some(ELYBY_API_URL)
+    }
+
+    fn authenticate<'a>(
+        &'a self,
+        client: &'a YggdrasilClient,
+        credentials: &'a Credentials<'a>,
+    ) -> BoxFuture<'a, Result<Session>> {
+        Yggdrasil.authenticate(client, credentials)
+    }
+}
+
+/// The template for the endpoint: the one in `[server.endpoints]`, or the backend's.
+pub fn template<'a>(
+    backend: &dyn AuthBackend,
+    config: &'a Config,
+    endpoint: Endpoint,
+) -> Result<&'a str> {
+    let configured = match endpoint {
+        Endpoint::Signin => &config.server.endpoints.signin,
+        Endpoint::ChangePassword => &config.server.endpoints.change_password,
+    };
+    configured
+        .as_deref()
+        .or(backend.endpoint(endpoint))
+        .ok_or_else(|| MmcaiError::InvalidEndpoint {
+            endpoint: endpoint.name().to_string(),
+            message: format!("the {} backend has none, so it must be set", backend.name()),
+        })
+}
+
+/// The URL of an endpoint template for the API root. Relative templates resolve like links
+/// in a page at the root taken as a directory, so the root's last segments can be
+/// stepped out of with `..` whatever the server's path prefix.
+pub fn resolve(api_root: &str, endpoint: Endpoint, template: &str) -> Result<String> {
+    let invalid = |message: String| MmcaiError::InvalidEndpoint {
+        endpoint: endpoint.name().to_string(),
+        message,
+    };
+    let base = Url::parse(&format!("{}/", api_root.trim_end_matches('/'))).map_err(|e| {
+        invalid(format!(
+            "the API URL {:?} is not a valid URL ({})",
+            api_root, e
+        ))
+    })?;
+    let url = base
+        .join(template)
+        .map_err(|e| invalid(format!("{:?} is not a valid URL or path ({})", template, e)))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(invalid(format!("{:?} is not an http(s) URL", template)));
+    }
+    Ok(url.to_string())
+}
+
+/// The backends by name.
+pub struct Registry {
+    backends: BTreeMap<&'static str, Box<dyn AuthBackend>>,
+}
+
+impl Default for Registry {
+    /// The built-in backends.
+    fn default() -> Self {
+        let mut registry = Registry::empty();
+        registry.register(Box::new(Marallys));
+        registry.register(Box::new(Yggdrasil));
+        registry.register(Box::new(ElyBy));
+        registry
+    }
+}
+
+impl fmt::Debug for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.backends.keys()).finish()
+    }
+}
+
+impl Registry {
+    pub fn empty() -> Registry {
+        Registry {
+            backends: BTreeMap::new(),
+        }
+    }
+
+    /// Adds the backend, replacing the one registered under the same name.
+    pub fn register(&mut self, backend: Box<dyn AuthBackend>) {
+        self.backends.insert(backend.name(), backend);
+    }
+
+    pub fn get(&self, name: &str) -> Result<&dyn AuthBackend> {
+        self.backends
+            .get(name)
+            .map(Box::as_ref)
+            .ok_or_else(|| MmcaiError::UnknownBackend {
+                name: name.to_string(),
+                available: self.names().join(", "),
+            })
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.backends.keys().copied().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry() {
+        let registry = Registry::default();
+        assert_eq!(registry.names(), ["elyby", "marallys", "yggdrasil"]);
+        assert_eq!(
+            registry.get("elyby").unwrap().default_api_url(),
+            Some(ELYBY_API_URL)
+        );
+        assert!(matches!(
+            registry.get("mojang"),
+            Err(MmcaiError::UnknownBackend { available, .. }) if available == "elyby, marallys, yggdrasil"
+        ));
+    }
+
+    #[test]
+    fn test_endpoints() {
+        let root = "https://auth.example.com/api/v1/integrations/authlib/minecraft";
+        let url = |template| resolve(root, Endpoint::Signin, template);
+        assert_eq!(
+            url(MARALLYS_SIGNIN).unwrap(),
+            "https://auth.example.com/api/v1/integrations/auth/signin"
+        );
+        assert_eq!(
+            url("/auth/signin").unwrap(),
+            "https://auth.example.com/auth/signin"
+        );
+        assert_eq!(url("signin").unwrap(), format!("{}/signin", root));
+        assert_eq!(
+            url("https://login.example.com/signin?v=2").unwrap(),
+            "https://login.example.com/signin?v=2"
+        );
+        assert!(matches!(
+            url("file:///etc/passwd"),
+            Err(MmcaiError::InvalidEndpoint { endpoint, .. }) if endpoint == "signin"
+        ));
+
+        let mut config = Config::default();
+        assert_eq!(
+            template(&Marallys, &config, Endpoint::ChangePassword).unwrap(),
+            MARALLYS_CHANGE_PASSWORD
+        );
+        assert!(template(&Yggdrasil, &config, Endpoint::Signin).is_err());
+        config.server.endpoints.signin = Some("/login".into());
+        assert_eq!(
+            template(&Yggdrasil, &config, Endpoint::Signin).unwrap(),
+            "/login"
+        );
+    }
+
+    #[test]
+    fn test_signin_request() {
+        let credentials = Credentials {
+            username: "steve",
+            password: "hunter2",
+        };
+        let mut config = Config::default();
+        assert_eq!(
+            Marallys::signin_request(&config, &credentials).access_token,
+            Some("null")
+        );
+        config.server.signin_access_token = Some(String::new());
+        assert_eq!(
+            Marallys::signin_request(&config, &credentials).access_token,
+            Some("")
+        );
+    }
+}
@@ -0,0 +1,276 @@
+use std::path::PathBuf;
+
+use crate::errors::MmcaiError;
+use crate::Result;
+
+pub use crate::launcher::Launcher;
+
+/// How the error the wrapper exits with is printed (`--error-format`).
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum ErrorFormat {
+    #[default]
+    Text,
+    /// One JSON object on stderr, for launcher integrations.
+    Json,
+}
+
+/// Argument orders of other wrappers, accepted with `--compat` while people migrate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Compat {
+    /// The original script's `<api url> <username> <password>`, optionally followed by
+    /// a placeholder in front of the Java command.
+    Classic,
+}
+
+/// Options given before the positional `<username> <password> <api url>` arguments.
+#[derive(Debug, Default)]
+pub struct Options {
+    pub trace_http: Option<PathBuf>,
+    pub quick_play: Option<String>,
+    /// The profile to play as when the account has several.
+    pub profile: Option<String>,
+    /// The stored account to log in with, instead of the username argument.
+    pub account: Option<String>,
+    /// Keep all state next to the executable instead of in the user's directories.
+    pub portable: bool,
+    /// Read the param block from this file instead of stdin.
+    pub params_file: Option<PathBuf>,
+    /// Save the param block the launcher sent, with credentials redacted.
+    pub record_params: Option<PathBuf>,
+    /// Write the final command line and patched params to this file.
+    pub dump_launch: Option<PathBuf>,
+    /// Don't redact tokens in the launch dump.
+    pub unsafe_full: bool,
+    /// Working directory of the game.
+    pub cwd: Option<PathBuf>,
+    /// Java to start the game with, instead of `INST_JAVA` or a detected one.
+    pub java: Option<PathBuf>,
+    /// The authlib-injector jar, instead of the one next to the executable.
+    pub authlib_injector: Option<PathBuf>,
+    /// How the game's params arrive; detected from the command line when not given.
+    pub launcher: Option<Launcher>,
+    /// Accept another wrapper's argument order (deprecated).
+    pub compat: Option<Compat>,
+    /// Skip the login and start the game with the launcher's params as they are.
+    pub pass_through: bool,
+    /// Run the subcommand named by the first argument (`stats`, `account`, ...) instead of
+    /// launching, so a username like `skin` is never taken for one.
+    pub command: bool,
+    /// The next wrapper command, one word per `--exec-next`.
+    pub exec_next: Vec<String>,
+    /// Write progress events as NDJSON to this file descriptor (a handle on Windows).
+    pub events_fd: Option<i32>,
+    /// Write progress events as NDJSON to this file.
+    pub events_file: Option<PathBuf>,
+    pub error_format: ErrorFormat,
+    /// Don't show a message box for the error when stderr isn't a terminal.
+    pub no_error_dialog: bool,
+    /// How many times `-v` was given.
+    pub verbosity: u8,
+}
+
+/// Splits leading `--option`s off the command line. The returned arguments keep the
+/// program name in front, so they can be validated like before options existed.
+pub fn parse_options(args: &[String]) -> Result<(Options, Vec<String>)> {
+    let mut options = Options::default();
+    let mut rest = args.iter();
+    let mut positional: Vec<String> = rest.next().into_iter().cloned().collect();
+
+    let mut rest = rest.peekable();
+    while let Some(arg) = rest.next_if(|arg| arg.starts_with("--") || is_verbosity_flag(arg)) {
+        let mut value = |name: &str| {
+            rest.next()
+                .cloned()
+                .ok_or_else(|| MmcaiError::MissingOptionValue(name.to_string()))
+        };
+        match arg.as_str() {
+            "--" => break,
+            "--trace-http" => options.trace_http = Some(PathBuf::from(value(arg)?)),
+            "--quick-play" => options.quick_play = Some(value(arg)?),
+            "--profile" => options.profile = Some(value(arg)?),
+            "--account" => options.account = Some(value(arg)?),
+            "--params-file" => options.params_file = Some(PathBuf::from(value(arg)?)),
+            "--record-params" => options.record_params = Some(PathBuf::from(value(arg)?)),
+            "--dump-launch" => options.dump_launch = Some(PathBuf::from(value(arg)?)),
+            "--unsafe-full" => options.unsafe_full = true,
+            "--no-error-dialog" => options.no_error_dialog = true,
+            "--pass-through" => options.pass_through = true,
+            "--portable" => options.portable = true,
+            "--cmd" => options.command = true,
+            "--cwd" => options.cwd = Some(PathBuf::from(value(arg)?)),
+            "--java" => options.java = Some(PathBuf::from(value(arg)?)),
+            "--authlib-injector" => options.authlib_injector = Some(PathBuf::from(value(arg)?)),
+            "--exec-next" => options.exec_next.push(value(arg)?),
+            "--events-fd" => {
+                let fd = value(arg)?;
+                options.events_fd = Some(
+                    fd.parse()
+                        .map_err(|_| MmcaiError::InvalidOptionValue(arg.clone(), fd))?,
+                )
+            }
+            "--launcher" => {
+                let name = value(arg)?;
+                options.launcher = Some(
+                    Launcher::parse(&name)
+                        .ok_or_else(|| MmcaiError::InvalidOptionValue(arg.clone(), name))?,
+                )
+            }
+            "--compat" => {
+                let name = value(arg)?;
+                options.compat = match name.as_str() {
+                    "classic" => Some(Compat::Classic),
+                    _ => return Err(MmcaiError::InvalidOptionValue(arg.clone(), name)),
+                }
+            }
+            "--error-format" => {
+                let format = value(arg)?;
+                options.error_format = match format.as_str() {
+                    "text" => ErrorFormat::Text,
+                    "json" => ErrorFormat::Json,
+                    _ => return Err(MmcaiError::InvalidOptionValue(arg.clone(), format)),
+                }
+            }
+            "--events-file" => options.events_file = Some(PathBuf::from(value(arg)?)),
+            "--verbose" => options.verbosity = options.verbosity.saturating_add(1),
+            arg if is_verbosity_flag(arg) => {
+                options.verbosity = options.verbosity.saturating_add(arg.len() as u8 - 1)
+            }
+            _ => return Err(MmcaiError::UnknownOption(arg.clone())),
+        }
+    }
+
+    positional.extend(rest.cloned());
+    Ok((options, positional))
+}
+
+/// Reorders `prog <api url> <username> <password> [placeholder] java ...` into
+/// `prog <username> <password> <api url> java ...`.
+pub fn from_classic(mut args: Vec<String>) -> Vec<String> {
+    if args.len() < 4 {
+        return args;
+    }
+    args[1..4].rotate_left(1);
+    // a placeholder is anything that isn't Java, in front of a Java executable
+    let is_java = |arg: &String| {
+        arg.rsplit(['/', '\\'])
+            .next()
+            .is_some_and(|name| name.to_ascii_lowercase().starts_with("java"))
+    };
+    if args.len() > 5 && !is_java(&args[4]) && is_java(&args[5]) {
+        args.remove(4);
+    }
+    args
+}
+
+/// `-v`, `-vv`, ...; the only short options, so a username like `-steve` stays positional.
+fn is_verbosity_flag(arg: &str) -> bool {
+    arg.len() > 1 && arg.starts_with('-') && arg[1..].chars().all(|c| c == 'v')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let (options, positional) = parse_options(&args(&[
+            "mmcai_rs",
+            "--trace-http",
+            "trace.log",
+            "--quick-play",
+            "play.marallys.com",
+            "--unsafe-full",
+            "--no-error-dialog",
+            "--pass-through",
+            "--launcher",
+            "atlauncher",
+            "--exec-next",
+            "mangohud",
+            "--exec-next",
+            "gamemoderun",
+            "--error-format",
+            "json",
+            "--java",
+            "/opt/jdk/bin/java",
+            "-vv",
+            "user",
+            "pass",
+        ]))
+        .unwrap();
+        assert_eq!(options.trace_http, Some(PathBuf::from("trace.log")));
+        assert_eq!(options.quick_play.as_deref(), Some("play.marallys.com"));
+        assert!(options.unsafe_full);
+        assert!(options.no_error_dialog);
+        assert!(options.pass_through);
+        assert_eq!(options.launcher, Some(Launcher::AtLauncher));
+        assert_eq!(options.exec_next, args(&["mangohud", "gamemoderun"]));
+        assert_eq!(options.verbosity, 2);
+        assert_eq!(options.error_format, ErrorFormat::Json);
+        assert_eq!(options.java, Some(PathBuf::from("/opt/jdk/bin/java")));
+        assert_eq!(positional, args(&["mmcai_rs", "user", "pass"]));
+
+        // options are only recognized in front of the positional arguments
+        let (options, positional) =
+            parse_options(&args(&["mmcai_rs", "user", "--trace-http", "x"])).unwrap();
+        assert!(options.trace_http.is_none());
+        assert_eq!(positional, args(&["mmcai_rs", "user", "--trace-http", "x"]));
+
+        let (options, positional) = parse_options(&args(&["mmcai_rs", "-vanilla"])).unwrap();
+        assert_eq!(options.verbosity, 0);
+        assert_eq!(positional, args(&["mmcai_rs", "-vanilla"]));
+
+        let (_, positional) = parse_options(&args(&["mmcai_rs", "--", "--user"])).unwrap();
+        assert_eq!(positional, args(&["mmcai_rs", "--user"]));
+
+        assert!(matches!(
+            parse_options(&args(&["mmcai_rs", "--trace-http"])),
+            Err(MmcaiError::MissingOptionValue(_))
+        ));
+        assert!(matches!(
+            parse_options(&args(&["mmcai_rs", "--events-fd", "three"])),
+            Err(MmcaiError::InvalidOptionValue(..))
+        ));
+        assert!(matches!(
+            parse_options(&args(&["mmcai_rs", "--nope", "user"])),
+            Err(MmcaiError::UnknownOption(_))
+        ));
+    }
+
+    #[test]
+    fn test_from_classic() {
+        assert_eq!(
+            from_classic(args(&[
+                "mmcai_rs",
+                "https://auth.example.com/api",
+                "steve",
+                "hunter2",
+                "/usr/bin/java",
+                "-Xmx2G",
+            ])),
+            args(&[
+                "mmcai_rs",
+                "steve",
+                "hunter2",
+                "https://auth.example.com/api",
+                "/usr/bin/java",
+                "-Xmx2G",
+            ])
+        );
+        assert_eq!(
+            from_classic(args(&[
+                "mmcai_rs",
+                "https://auth.example.com/api",
+                "steve",
+                "hunter2",
+                "%WRAPPER%",
+                "C:\\Java\\bin\\javaw.exe",
+                "-Xmx2G",
+            ]))[4],
+            "C:\\Java\\bin\\javaw.exe"
+        );
+    }
+}
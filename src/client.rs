@@ -0,0 +1,497 @@
+#[cfg(feature = "async")]
+use std::time::Duration;
+use std::{net::IpAddr, sync::OnceLock};
+
+#[cfg(feature = "async")]
+use base64::prelude::*;
+use reqwest::{header, Response};
+use serde::{Deserialize, Serialize};
+
+use crate::auth;
+use crate::backend::{self, Endpoint};
+#[cfg(feature = "async")]
+use crate::backend::{AuthBackend, Credentials, Marallys};
+use crate::config::Config;
+use crate::errors::MmcaiError;
+use crate::http::HttpClient;
+use crate::model::{AuthData, AuthRequest, Profile};
+use crate::{metadata, response, retry, tokens, Result};
+
+pub use crate::skin::Textures;
+pub use crate::tokens::Status;
+
+#[cfg(feature = "blocking")]
+pub mod blocking;
+
+/// A client for one authlib-injector server: logs in, keeps tokens alive and fetches the
+/// API metadata, with the same HTTP settings, retries and error mapping as the wrapper.
+///
+/// [`AuthBackend`](crate::backend::AuthBackend)s are handed one to log in with. Launchers build their own with
+/// `YggdrasilClient::builder()`, which needs the `async` feature.
+pub struct YggdrasilClient {
+    http: HttpClient,
+    api_url: String,
+    /// Where the API location indication of `api_url` leads, once followed.
+    api_root: OnceLock<String>,
+    config: Config,
+    client_token: String,
+    custom_user_agent: bool,
+}
+
+/// Builds a [`YggdrasilClient`].
+///
+/// ```no_run
+/// # async fn example() -> marallys_auth_patcher::Result<()> {
+/// use std::time::Duration;
+/// use marallys_auth_patcher::client::YggdrasilClient;
+///
+/// let client = YggdrasilClient::builder()
+///     .api_url("https://auth.example.com/api/authlib/minecraft")
+///     .user_agent("my-launcher/1.0")
+///     .timeout(Duration::from_secs(10))
+///     .build()?;
+/// let session = client.authenticate("steve", "hunter2").await?;
+/// println!("{} ({})", session.selected_profile.name, session.selected_profile.id);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "async")]
+#[derive(Default)]
+pub struct YggdrasilClientBuilder {
+    api_url: Option<String>,
+    user_agent: Option<String>,
+    timeout: Option<Duration>,
+    config: Config,
+}
+
+#[cfg(feature = "async")]
+impl YggdrasilClientBuilder {
+    /// The authlib-injector API root, as given to the wrapper.
+    pub fn api_url(mut self, api_url: impl Into<String>) -> Self {
+        self.api_url = Some(api_url.into());
+        self
+    }
+
+    /// Sent with every request instead of the browser-like one the wrapper uses to sign in.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// How long a whole request may take, in whole seconds.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Takes the `[http]`, `[retry]` and `[server]` settings from a wrapper config.
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn build(self) -> Result<YggdrasilClient> {
+        let api_url = self.api_url.ok_or(MmcaiError::MissingApiUrl)?;
+        let mut config = self.config;
+        if let Some(timeout) = self.timeout {
+            config.http.request_timeout = timeout.as_secs_f64().ceil().max(1.0) as u64;
+        }
+        let http = match &self.user_agent {
+            Some(user_agent) => HttpClient::with_user_agent(&config, user_agent)?,
+            None => HttpClient::new(&config, None)?,
+        };
+        Ok(YggdrasilClient {
+            http,
+            api_url: api_url.trim_end_matches('/').to_string(),
+            api_root: OnceLock::new(),
+            config,
+            client_token: auth::generate_client_token(),
+            custom_user_agent: self.user_agent.is_some(),
+        })
+    }
+}
+
+/// A signed in account, as `authenticate` and `refresh` return it.
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub access_token: String,
+    pub client_token: String,
+    pub expired_date: Option<String>,
+    pub selected_profile: Profile,
+    /// Every profile of the account; empty when the server only knows the selected one.
+    pub available_profiles: Vec<Profile>,
+    /// Only known right after signing in.
+    pub textures: Option<Textures>,
+    /// Where the signin answer came from.
+    pub server_ip: Option<IpAddr>,
+}
+
+impl Session {
+    pub(crate) fn from_auth_data(
+        data: AuthData,
+        client_token: String,
+        server_ip: Option<IpAddr>,
+    ) -> Session {
+        Session {
+            access_token: data.access_token,
+            client_token,
+            expired_date: data.expired_date,
+            selected_profile: Profile {
+                id: data.uuid,
+                name: data.name,
+            },
+            available_profiles: data.available_profiles,
+            textures: Some(Textures {
+                skin_url: data.texture_skin_url,
+                skin_guid: data.texture_skin_guid,
+                cloak_url: data.texture_cloak_url,
+                cloak_guid: data.texture_cloak_guid,
+                full_skin_url: data.full_skin_url,
+            }),
+            server_ip,
+        }
+    }
+}
+
+/// The server's API metadata, as handed to authlib-injector.
+#[cfg(feature = "async")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Metadata {
+    pub json: String,
+}
+
+#[cfg(feature = "async")]
+impl Metadata {
+    /// `meta.serverName`, when the server sets one.
+    pub fn server_name(&self) -> Option<String> {
+        let value: serde_json::Value = serde_json::from_str(&self.json).ok()?;
+        Some(value.pointer("/meta/serverName")?.as_str()?.to_string())
+    }
+
+    /// The value of `-Dauthlibinjector.yggdrasil.prefetched`.
+    pub fn prefetched(&self) -> String {
+        BASE64_STANDARD.encode(&self.json)
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenRequest<'a> {
+    access_token: &'a str,
+    client_token: &'a str,
+}
+
+/// `/authserver/refresh`, which can also switch the token to another of the account's
+/// profiles.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RefreshRequest<'a> {
+    access_token: &'a str,
+    client_token: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    selected_profile: Option<&'a Profile>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Agent<'a> {
+    name: &'a str,
+    version: i32,
+}
+impl Default for Agent<'_> {
+    fn default() -> Self {
+        Agent {
+            name: "Minecraft",
+            version: 1,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AuthenticateRequest<'a> {
+    agent: Agent<'a>,
+    username: &'a str,
+    password: &'a str,
+    client_token: &'a str,
+}
+
+/// The answer to `/authserver/authenticate` and `/authserver/refresh`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenResponse {
+    access_token: String,
+    client_token: Option<String>,
+    selected_profile: Option<Profile>,
+    #[serde(default)]
+    available_profiles: Vec<Profile>,
+}
+
+/// A standard Yggdrasil error body.
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct ErrorResponse {
+    error: Option<String>,
+    error_message: Option<String>,
+}
+
+impl YggdrasilClient {
+    #[cfg(feature = "async")]
+    pub fn builder() -> YggdrasilClientBuilder {
+        YggdrasilClientBuilder::default()
+    }
+
+    /// A client sharing the wrapper's HTTP client, for an API root it already resolved.
+    pub(crate) fn for_api_root(
+        http: &HttpClient,
+        api_root: &str,
+        client_token: &str,
+        config: &Config,
+    ) -> Self {
+        let api_url = api_root.trim_end_matches('/').to_string();
+        YggdrasilClient {
+            http: http.clone(),
+            api_root: OnceLock::from(api_url.clone()),
+            api_url,
+            config: config.clone(),
+            client_token: client_token.to_string(),
+            custom_user_agent: false,
+        }
+    }
+
+    #[cfg(feature = "async")]
+    pub fn api_url(&self) -> &str {
+        &self.api_url
+    }
+
+    /// The settings the client was built with.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    #[cfg(feature = "async")]
+    /// Signs in with `/auth/signin` like the `marallys` backend. The selected profile is
+    /// the one the server picks.
+    pub async fn authenticate(&self, login: &str, password: &str) -> Result<Session> {
+        let credentials = Credentials {
+            username: login,
+            password,
+        };
+        Marallys.authenticate(self, &credentials).await
+    }
+
+    /// Posts the request to the signin endpoint, given as a template like in
+    /// `[server.endpoints]`, for servers that take other fields than Marallys does.
+    pub async fn signin(&self, endpoint: &str, request: &AuthRequest<'_>) -> Result<Session> {
+        let api_root = self.api_root().await?;
+        let signin_url = backend::resolve(&api_root, Endpoint::Signin, endpoint)?;
+        let mut headers = auth::auth_headers();
+        if self.custom_user_agent {
+            headers.remove(header::USER_AGENT);
+        }
+        let strict = self.config.server.strict_responses;
+        let (data, server_ip) =
+            auth::signin(&self.http, &signin_url, headers, request, strict).await?;
+        Ok(Session::from_auth_data(
+            data,
+            self.client_token.clone(),
+            server_ip,
+        ))
+    }
+
+    /// Signs in with the standard Yggdrasil `/authserver/authenticate`, which
+    /// authlib-injector servers other than Marallys implement.
+    pub async fn authserver_authenticate(&self, username: &str, password: &str) -> Result<Session> {
+        let request = AuthenticateRequest {
+            agent: Agent::default(),
+            username,
+            password,
+            client_token: &self.client_token,
+        };
+        let (response, server_ip) = match self.post("authserver/authenticate", &request).await {
+            Ok(response) => response,
+            Err(MmcaiError::LoginRejected {
+                status_code: 401 | 403,
+                message,
+            }) => return Err(MmcaiError::InvalidCredentials { message }),
+            Err(e) => return Err(e),
+        };
+        let answer = token_response(response).await?;
+        let selected_profile = answer
+            .selected_profile
+            .or_else(|| answer.available_profiles.first().cloned())
+            .ok_or_else(|| MmcaiError::InvalidAuthResponse {
+                message: "the account has no profile".into(),
+                response: String::new(),
+            })?;
+        Ok(Session {
+            access_token: answer.access_token,
+            client_token: answer
+                .client_token
+                .unwrap_or_else(|| self.client_token.clone()),
+            expired_date: None,
+            selected_profile,
+            available_profiles: answer.available_profiles,
+            textures: None,
+            server_ip,
+        })
+    }
+
+    /// Trades the session's token for a fresh one with `/authserver/refresh`.
+    pub async fn refresh(&self, session: &Session) -> Result<Session> {
+        self.refresh_as(session, None).await
+    }
+
+    /// Trades the session's token for one bound to another of the account's profiles,
+    /// with `/authserver/refresh`. The game's joins are checked against the profile of
+    /// the token, so a token of the server's default profile can't play as another.
+    pub async fn select_profile(&self, session: &Session, profile: &Profile) -> Result<Session> {
+        self.refresh_as(session, Some(profile)).await
+    }
+
+    async fn refresh_as(&self, session: &Session, profile: Option<&Profile>) -> Result<Session> {
+        let request = RefreshRequest {
+            access_token: &session.access_token,
+            client_token: &session.client_token,
+            selected_profile: profile,
+        };
+        let (response, server_ip) = self.post("authserver/refresh", &request).await?;
+        let refreshed = token_response(response).await?;
+        Ok(Session {
+            access_token: refreshed.access_token,
+            client_token: refreshed
+                .client_token
+                .unwrap_or_else(|| session.client_token.clone()),
+            expired_date: None,
+            selected_profile: refreshed
+                .selected_profile
+                .or_else(|| profile.cloned())
+                .unwrap_or_else(|| session.selected_profile.clone()),
+            available_profiles: session.available_profiles.clone(),
+            textures: None,
+            server_ip,
+        })
+    }
+
+    /// Whether the server still accepts the token, with `/authserver/validate`.
+    pub async fn validate(&self, access_token: &str, client_token: Option<&str>) -> Status {
+        match self.api_root().await {
+            Ok(api_url) => {
+                tokens::validate_token(&self.http, &api_url, access_token, client_token).await
+            }
+            Err(e) => Status::Unknown(e.to_string()),
+        }
+    }
+
+    /// Revokes the token with `/authserver/invalidate`.
+    pub async fn invalidate(&self, access_token: &str, client_token: &str) -> Result<()> {
+        let request = TokenRequest {
+            access_token,
+            client_token,
+        };
+        self.post("authserver/invalidate", &request)
+            .await
+            .map(|_| ())
+    }
+
+    #[cfg(feature = "async")]
+    /// The API metadata, revalidating the cached copy like a launch does.
+    pub async fn prefetch_metadata(&self) -> Result<Metadata> {
+        let api_url = self.api_root().await?;
+        let json = metadata::fetch_metadata(&self.http, &api_url, &self.config).await?;
+        Ok(Metadata { json })
+    }
+
+    /// Follows the server's API location indication, like the wrapper does.
+    async fn api_root(&self) -> Result<String> {
+        if let Some(root) = self.api_root.get() {
+            return Ok(root.clone());
+        }
+        let root = metadata::resolve_api_root(&self.http, &self.api_url).await?;
+        Ok(self
+            .api_root
+            .get_or_init(|| root.trim_end_matches('/').to_string())
+            .clone())
+    }
+
+    /// Posts JSON to a Yggdrasil endpoint, with the server's error message when it fails.
+    async fn post<T: Serialize>(
+        &self,
+        endpoint: &str,
+        request: &T,
+    ) -> Result<(Response, Option<IpAddr>)> {
+        let url = format!("{}/{}", self.api_root().await?, endpoint);
+        let response = self
+            .http
+            .send(|client| client.post(&url).json(request))
+            .await
+            .map_err(auth_failed)?;
+        retry::check_rate_limit(&response)?;
+        let server_ip = response.remote_addr().map(|address| address.ip());
+        if response.status().is_success() {
+            return Ok((response, server_ip));
+        }
+        let status_code = response.status().as_u16();
+        let body = response.text().await.unwrap_or_default();
+        let error: ErrorResponse = serde_json::from_str(&body).unwrap_or_default();
+        Err(MmcaiError::LoginRejected {
+            status_code,
+            message: error
+                .error_message
+                .or(error.error)
+                .unwrap_or_else(|| response::body_excerpt(&body)),
+        })
+    }
+}
+
+async fn token_response(response: Response) -> Result<TokenResponse> {
+    let body = response.text().await.map_err(auth_failed)?;
+    serde_json::from_str(&body).map_err(|e| MmcaiError::InvalidAuthResponse {
+        message: e.to_string(),
+        response: response::body_excerpt(&body),
+    })
+}
+
+fn auth_failed(source: reqwest::Error) -> MmcaiError {
+    MmcaiError::YggdrasilAuthFailed {
+        source,
+        response: "<request failed, no response body>".into(),
+    }
+}
+
+#[cfg(all(test, feature = "async"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder() {
+        assert!(matches!(
+            YggdrasilClient::builder().build(),
+            Err(MmcaiError::MissingApiUrl)
+        ));
+        let client = YggdrasilClient::builder()
+            .api_url("https://auth.example.com/api/authlib/minecraft/")
+            .user_agent("my-launcher/1.0")
+            .timeout(Duration::from_millis(1500))
+            .build()
+            .unwrap();
+        assert_eq!(
+            client.api_url(),
+            "https://auth.example.com/api/authlib/minecraft"
+        );
+        assert_eq!(client.config.http.request_timeout, 2);
+        assert_eq!(client.client_token.len(), 36);
+    }
+
+    #[test]
+    fn test_metadata() {
+        let metadata = Metadata {
+            json: r#"{"meta":{"serverName":"Marallys"}}"#.into(),
+        };
+        assert_eq!(metadata.server_name().as_deref(), Some("Marallys"));
+        assert_eq!(
+            BASE64_STANDARD.decode(metadata.prefetched()).unwrap(),
+            metadata.json.as_bytes()
+        );
+    }
+}
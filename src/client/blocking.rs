@@ -0,0 +1,127 @@
+use std::time::Duration;
+
+use tokio::runtime::{Builder, Runtime};
+
+use super::{Metadata, Session, Status};
+use crate::config::Config;
+use crate::errors::MmcaiError;
+use crate::model::{AuthRequest, Profile};
+use crate::Result;
+
+/// [`super::YggdrasilClient`] for launchers without an async runtime: every call blocks
+/// on a runtime of the client's own. Calling it from within an async runtime panics; use
+/// the async client there.
+pub struct YggdrasilClient {
+    inner: super::YggdrasilClient,
+    runtime: Runtime,
+}
+
+#[derive(Default)]
+pub struct YggdrasilClientBuilder {
+    inner: super::YggdrasilClientBuilder,
+}
+
+impl YggdrasilClientBuilder {
+    pub fn api_url(self, api_url: impl Into<String>) -> Self {
+        YggdrasilClientBuilder {
+            inner: self.inner.api_url(api_url),
+        }
+    }
+
+    pub fn user_agent(self, user_agent: impl Into<String>) -> Self {
+        YggdrasilClientBuilder {
+            inner: self.inner.user_agent(user_agent),
+        }
+    }
+
+    pub fn timeout(self, timeout: Duration) -> Self {
+        YggdrasilClientBuilder {
+            inner: self.inner.timeout(timeout),
+        }
+    }
+
+    pub fn config(self, config: Config) -> Self {
+        YggdrasilClientBuilder {
+            inner: self.inner.config(config),
+        }
+    }
+
+    pub fn build(self) -> Result<YggdrasilClient> {
+        let runtime = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(MmcaiError::RuntimeBuildFailed)?;
+        Ok(YggdrasilClient {
+            inner: self.inner.build()?,
+            runtime,
+        })
+    }
+}
+
+impl YggdrasilClient {
+    pub fn builder() -> YggdrasilClientBuilder {
+        YggdrasilClientBuilder::default()
+    }
+
+    pub fn api_url(&self) -> &str {
+        self.inner.api_url()
+    }
+
+    pub fn authenticate(&self, login: &str, password: &str) -> Result<Session> {
+        self.runtime
+            .block_on(self.inner.authenticate(login, password))
+    }
+
+    pub fn signin(&self, endpoint: &str, request: &AuthRequest<'_>) -> Result<Session> {
+        self.runtime.block_on(self.inner.signin(endpoint, request))
+    }
+
+    pub fn authserver_authenticate(&self, username: &str, password: &str) -> Result<Session> {
+        self.runtime
+            .block_on(self.inner.authserver_authenticate(username, password))
+    }
+
+    pub fn refresh(&self, session: &Session) -> Result<Session> {
+        self.runtime.block_on(self.inner.refresh(session))
+    }
+
+    pub fn select_profile(&self, session: &Session, profile: &Profile) -> Result<Session> {
+        self.runtime
+            .block_on(self.inner.select_profile(session, profile))
+    }
+
+    pub fn validate(&self, access_token: &str, client_token: Option<&str>) -> Status {
+        self.runtime
+            .block_on(self.inner.validate(access_token, client_token))
+    }
+
+    pub fn invalidate(&self, access_token: &str, client_token: &str) -> Result<()> {
+        self.runtime
+            .block_on(self.inner.invalidate(access_token, client_token))
+    }
+
+    pub fn prefetch_metadata(&self) -> Result<Metadata> {
+        self.runtime.block_on(self.inner.prefetch_metadata())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder() {
+        let client = YggdrasilClient::builder()
+            .api_url("https://auth.example.com/api/authlib/minecraft")
+            .build()
+            .unwrap();
+        assert_eq!(
+            client.api_url(),
+            "https://auth.example.com/api/authlib/minecraft"
+        );
+        assert!(matches!(
+            YggdrasilClient::builder().build(),
+            Err(MmcaiError::MissingApiUrl)
+        ));
+    }
+}
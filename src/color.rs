@@ -0,0 +1,94 @@
+use std::{
+    env,
+    io::{self, IsTerminal},
+    sync::OnceLock,
+};
+
+use serde::Deserialize;
+
+/// `logging.color`: whether the wrapper's console lines are colored.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorMode {
+    /// Only when the output is a terminal and `NO_COLOR` is not set.
+    #[default]
+    Auto,
+    /// Also when the output is piped, e.g. into a log view that renders ANSI colors.
+    Always,
+    Never,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Color {
+    Red,
+    Green,
+    Yellow,
+}
+
+impl Color {
+    fn code(self) -> &'static str {
+        match self {
+            Color::Red => "31",
+            Color::Green => "32",
+            Color::Yellow => "33",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+static MODE: OnceLock<ColorMode> = OnceLock::new();
+
+/// Sets the mode from the config. Output before that uses [`ColorMode::Auto`].
+pub fn init(mode: ColorMode) {
+    let _ = MODE.set(mode);
+}
+
+/// Whether lines written to `stream` should be colored.
+pub fn enabled(stream: Stream) -> bool {
+    let is_terminal = match stream {
+        Stream::Stdout => io::stdout().is_terminal(),
+        Stream::Stderr => io::stderr().is_terminal(),
+    };
+    let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    decide(
+        MODE.get().copied().unwrap_or_default(),
+        is_terminal,
+        no_color,
+    )
+}
+
+fn decide(mode: ColorMode, is_terminal: bool, no_color: bool) -> bool {
+    match mode {
+        ColorMode::Auto => is_terminal && !no_color,
+        ColorMode::Always => true,
+        ColorMode::Never => false,
+    }
+}
+
+/// Wraps `text` in the escape codes for `color` when `stream` is colored.
+pub fn paint(text: &str, color: Color, stream: Stream) -> String {
+    if enabled(stream) {
+        format!("\x1b[{}m{}\x1b[0m", color.code(), text)
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decide() {
+        assert!(decide(ColorMode::Auto, true, false));
+        assert!(!decide(ColorMode::Auto, true, true));
+        assert!(!decide(ColorMode::Auto, false, false));
+        assert!(decide(ColorMode::Always, false, true));
+        assert!(!decide(ColorMode::Never, true, false));
+    }
+}
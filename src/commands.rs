@@ -0,0 +1,533 @@
+use std::{
+    io::{self, IsTerminal},
+    path::PathBuf,
+    process,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use tracing::{info, warn};
+
+use crate::accounts::Account;
+use crate::auth::{
+    auth_headers, credentials, generate_client_token, login_stored, login_with_fallback,
+    stored_api_url, wrapper_account, ChangePasswordRequest, LoginResult,
+};
+use crate::backend::{self, Endpoint};
+use crate::cli::{Compat, ErrorFormat};
+use crate::color::{Color, Stream};
+use crate::config::Config;
+use crate::errors::MmcaiError;
+use crate::events::EventSink;
+use crate::http::HttpClient;
+use crate::i18n::tr;
+use crate::injector::find_authlib_injector;
+use crate::instance::Instance;
+use crate::launch::launch_game;
+use crate::model::AuthResponse;
+use crate::prelaunch::Handoff;
+use crate::timing::Timings;
+use crate::{
+    accounts, audit, cli, color, dates, dialog, i18n, launch, logging, metadata, metrics, paths,
+    picker, playtime, prelaunch, prism, response, retry, session, skin, tokens, Result,
+};
+
+const COMMANDS: &str = "<stats|account|import|export|prelaunch|skin|whoami> ...";
+
+fn validate_args(args: &[String]) -> Result<()> {
+    match args.len() {
+        len if len < 4 => Err(MmcaiError::InvalidArgument(args[0].to_owned())),
+        4 => Err(MmcaiError::CannotRunDirectly),
+        _ => Ok(()),
+    }
+}
+
+pub fn exit_with_error(e: MmcaiError, options: &cli::Options) -> ! {
+    // no effect once the config was read; earlier errors use the language from LANG
+    i18n::init(None);
+    tracing::error!(target: logging::FATAL_TARGET, exit_code = e.exit_code(), "{}", e);
+    match options.error_format {
+        ErrorFormat::Text => {
+            let headline = format!("{}: {}", tr!("error"), e);
+            eprintln!("{}\n{:?}", paint_error(&headline), e)
+        }
+        ErrorFormat::Json => eprintln!("{}", e.to_json()),
+    }
+    // launcher integrations asking for JSON read stderr themselves
+    let unseen = options.error_format == ErrorFormat::Text && !io::stderr().is_terminal();
+    if unseen && !options.no_error_dialog {
+        dialog::show_error(&dialog_message(&e));
+    }
+    process::exit(e.exit_code());
+}
+
+fn dialog_message(e: &MmcaiError) -> String {
+    format!(
+        "{}\n\n({})",
+        e.full_message(),
+        tr!("error-exit-code", code = e.exit_code())
+    )
+}
+
+fn paint_error(text: &str) -> String {
+    color::paint(text, Color::Red, Stream::Stderr)
+}
+
+/// The subcommands, only looked for after `--cmd`.
+async fn run_command(options: &cli::Options, args: &[String]) -> Result<()> {
+    match args.get(1).map(String::as_str) {
+        Some("stats") if args.len() == 2 => {
+            i18n::init(None);
+            playtime::print_stats();
+            Ok(())
+        }
+        Some("import") if (3..=4).contains(&args.len()) && args[2] == "prism" => {
+            i18n::init(None);
+            prism::import_accounts(args.get(3).map(PathBuf::from))
+        }
+        Some("export") if (6..=7).contains(&args.len()) && args[2] == "prism" => {
+            export_to_prism(options, args).await
+        }
+        Some("account") if (4..=5).contains(&args.len()) && args[2] == "change-password" => {
+            change_password(options, args).await
+        }
+        Some("account") if args.len() == 4 && args[2] == "list" && args[3] == "--check" => {
+            let config = Config::load()?;
+            i18n::init(config.language.as_deref());
+            color::init(config.logging.color);
+            tokens::print_accounts(&HttpClient::new(&config, None)?).await
+        }
+        Some("account") => {
+            i18n::init(None);
+            accounts::command(args)
+        }
+        Some("prelaunch") if args.len() == 5 => prelaunch(options, args).await,
+        Some("skin") if args.len() >= 3 => skin_command(options, args).await,
+        Some("whoami") if args.len() == 5 => whoami(options, args).await,
+        _ => {
+            i18n::init(None);
+            Err(MmcaiError::CommandUsage(format!(
+                "{} --cmd {}",
+                args[0], COMMANDS
+            )))
+        }
+    }
+}
+
+pub async fn run(options: &cli::Options, args: Vec<String>) -> Result<()> {
+    paths::init(options.portable);
+    if options.command {
+        return run_command(options, &args).await;
+    }
+
+    let args = match options.compat {
+        Some(Compat::Classic) => cli::from_classic(args),
+        None => args,
+    };
+    validate_args(&args)?;
+
+    let mut config = Config::load()?;
+    i18n::init(config.language.as_deref());
+    logging::init(options.verbosity, &config.logging)?;
+    if options.compat.is_some() {
+        let command = format!("{} {} <password> {}", args[0], args[1], args[3]);
+        warn!("{}", tr!("compat-classic-deprecated", command = command));
+    }
+    let events = EventSink::open(options)?;
+    let timings = Timings::new(&events);
+    if options.quick_play.is_some() {
+        config.game.quick_play = options.quick_play.clone();
+    }
+    if options.profile.is_some() {
+        config.server.profile = options.profile.clone();
+    }
+    if options.cwd.is_some() {
+        config.process.working_dir = options.cwd.clone();
+    }
+    if !options.exec_next.is_empty() {
+        config.process.exec_next = options.exec_next.clone();
+    }
+
+    let result = launch_game(options, &args, &config, &events, &timings).await;
+    if let Err(MmcaiError::LaunchAborted) = result {
+        info!("{}", tr!("launch-aborted"));
+        return Ok(());
+    }
+    if let Some(path) = &config.metrics.textfile {
+        metrics::update(path, &result, &timings.stages());
+    }
+    let status = result?;
+    if !status.success() {
+        process::exit(launch::exit_code(status));
+    }
+
+    Ok(())
+}
+
+/// `mmcai_rs --cmd export prism <username> <password> <api url> [accounts.json]`: logs in and
+/// writes the session into Prism's accounts, so Prism's account list shows it.
+async fn export_to_prism(options: &cli::Options, args: &[String]) -> Result<()> {
+    let config = Config::load()?;
+    i18n::init(config.language.as_deref());
+    logging::init(options.verbosity, &config.logging)?;
+    let events = EventSink::open(options)?;
+    let timings = Timings::new(&events);
+    let http = HttpClient::new(&config, None)?;
+
+    let api_url = args[5].as_str();
+    let account = credentials(Account::unstored(
+        args[3].clone(),
+        args[4].clone(),
+        api_url.to_string(),
+    ))?;
+    let client_token = generate_client_token();
+    let login_result = login_with_fallback(
+        &account,
+        &client_token,
+        &[api_url],
+        &http,
+        &config,
+        &timings,
+    )
+    .await?;
+    let path = prism::export_session(
+        args.get(6).map(PathBuf::from),
+        &prism::Session {
+            username: &account.username,
+            api_url: &login_result.api_url,
+            client_token: &client_token,
+            access_token: &login_result.access_token,
+            uuid: &login_result.selected_profile.id,
+            name: &login_result.selected_profile.name,
+        },
+    )?;
+    let name = &login_result.selected_profile.name;
+    let path = format!("{:?}", path);
+    info!(target: logging::SUCCESS_TARGET, "{}", tr!("export-done", name = name, path = path));
+    Ok(())
+}
+
+/// `mmcai_rs --cmd prelaunch <username> <password> <api url>`, as Prism's pre-launch command
+/// when the wrapper slot is taken: logs in and leaves the result for the wrapper, or
+/// for a script through the environment file.
+async fn prelaunch(options: &cli::Options, args: &[String]) -> Result<()> {
+    let mut config = Config::load()?;
+    if options.profile.is_some() {
+        config.server.profile = options.profile.clone();
+    }
+    i18n::init(config.language.as_deref());
+    logging::init(options.verbosity, &config.logging)?;
+    let events = EventSink::open(options)?;
+    let timings = Timings::new(&events);
+    let http = HttpClient::new(&config, None)?;
+    let authlib_injector_path = options
+        .authlib_injector
+        .clone()
+        .or_else(|| find_authlib_injector(None))
+        .ok_or(MmcaiError::AuthlibInjectorNotFound)?;
+
+    let instance = Instance::load();
+    let account = credentials(wrapper_account(
+        &args[2..5],
+        options.account.as_deref(),
+        instance.as_ref(),
+        &config,
+    )?)?;
+    let client_token = account
+        .client_token
+        .clone()
+        .unwrap_or_else(generate_client_token);
+    let api_urls: Vec<&str> = std::iter::once(account.api_url())
+        .chain(config.server.fallback_api_urls.iter().map(String::as_str))
+        .collect();
+    let login_result =
+        login_with_fallback(&account, &client_token, &api_urls, &http, &config, &timings).await?;
+    let path = prelaunch::write(
+        &Handoff {
+            username: account.username.clone(),
+            account_api_url: account.api_url().to_string(),
+            api_url: login_result.api_url,
+            prefetched_data: login_result.prefetched_data,
+            client_token,
+            access_token: login_result.access_token,
+            uuid: login_result.selected_profile.id,
+            name: login_result.selected_profile.name.clone(),
+            written_at: dates::unix_now(),
+        },
+        &authlib_injector_path,
+    )?;
+    let name = &login_result.selected_profile.name;
+    let path = format!("{:?}", path);
+    info!(target: logging::SUCCESS_TARGET, "{}", tr!("prelaunch-done", name = name, path = path));
+    Ok(())
+}
+
+/// `mmcai_rs --cmd account change-password <name> [<2fa code>]`: changes the stored
+/// account's password on the server, then updates the stored password and forgets
+/// the cached session, which was obtained with the old one.
+async fn change_password(options: &cli::Options, args: &[String]) -> Result<()> {
+    let config = Config::load()?;
+    i18n::init(config.language.as_deref());
+    logging::init(options.verbosity, &config.logging)?;
+    let http = HttpClient::new(&config, None)?;
+
+    let selector = format!("{}{}", accounts::ACCOUNT_PREFIX, args[3]);
+    let account = accounts::lookup(&selector, None)?
+        .ok_or_else(|| MmcaiError::UnknownAccount(args[3].clone()))?;
+    let api_url = stored_api_url(&account, &config)?;
+    let ask = |id| {
+        picker::ask_secret(&tr!(id, name = account.name)).ok_or(MmcaiError::CredentialsNotGiven)
+    };
+    let old_password = match &account.password {
+        Some(password) => password.clone(),
+        None => ask("password-old")?,
+    };
+    let new_password = ask("password-new")?;
+    if ask("password-repeat")? != new_password {
+        return Err(MmcaiError::PasswordsDiffer);
+    }
+
+    let api_url = metadata::resolve_api_root(&http, &api_url).await?;
+    let backend = config.backend()?;
+    let endpoint = backend::template(backend, &config, Endpoint::ChangePassword)?;
+    let change_url = backend::resolve(&api_url, Endpoint::ChangePassword, endpoint)?;
+    let request = ChangePasswordRequest {
+        login: &account.username,
+        old_password: &old_password,
+        new_password: &new_password,
+        code: args.get(4).map(String::as_str),
+    };
+    let failed = |source| MmcaiError::YggdrasilAuthFailed {
+        source,
+        response: "<request failed, no response body>".into(),
+    };
+    let response = http
+        .send(|client| {
+            client
+                .post(&change_url)
+                .headers(auth_headers())
+                .json(&request)
+        })
+        .await
+        .map_err(failed)?;
+    retry::check_rate_limit(&response)?;
+    let status = response.status();
+    let body = response.text().await.map_err(failed)?;
+    // the answer has the signin answer's envelope; its data isn't needed
+    let answer = serde_json::from_str::<serde_json::Value>(&body)
+        .ok()
+        .and_then(|mut answer| {
+            answer.as_object_mut()?.remove("data");
+            serde_json::from_value::<AuthResponse>(answer).ok()
+        });
+    match answer {
+        Some(answer) if answer.failed() => return Err(answer.error()),
+        Some(_) if status.is_success() => {}
+        None if status.is_success() => {}
+        _ => {
+            return Err(MmcaiError::LoginRejected {
+                status_code: status.as_u16(),
+                message: response::body_excerpt(&body),
+            })
+        }
+    }
+
+    accounts::update_password(&account.name, &new_password)?;
+    session::remove(&account.cache_key());
+    info!(target: logging::SUCCESS_TARGET, "{}", tr!("password-changed", name = account.name));
+    Ok(())
+}
+
+/// `mmcai_rs --cmd skin ...`: manages the textures of a stored account through the server's
+/// texture API, logging in first for the access token it needs.
+async fn skin_command(options: &cli::Options, args: &[String]) -> Result<()> {
+    let command = skin::Command::parse(args)?;
+    let config = Config::load()?;
+    i18n::init(config.language.as_deref());
+    logging::init(options.verbosity, &config.logging)?;
+    if command == skin::Command::List {
+        print!("{}", skin::list());
+        return Ok(());
+    }
+    let events = EventSink::open(options)?;
+    let timings = Timings::new(&events);
+    let http = HttpClient::new(&config, None)?;
+
+    let selector = options.account.as_deref().or(command.account());
+    let (_, login_result) = login_stored(selector, &http, &config, &timings).await?;
+    let profile = &login_result.selected_profile;
+    skin::run(
+        &command,
+        &http,
+        &skin::Target {
+            api_url: &login_result.api_url,
+            access_token: &login_result.access_token,
+            uuid: &profile.id,
+            name: &profile.name,
+            textures: &login_result.textures,
+        },
+    )
+    .await
+}
+
+/// `mmcai_rs --cmd whoami <username> <password> <api url>`: logs in and shows the profile the
+/// game would get, with its textures, the token's expiry and the previous login.
+async fn whoami(options: &cli::Options, args: &[String]) -> Result<()> {
+    let mut config = Config::load()?;
+    if options.profile.is_some() {
+        config.server.profile = options.profile.clone();
+    }
+    i18n::init(config.language.as_deref());
+    logging::init(options.verbosity, &config.logging)?;
+    let events = EventSink::open(options)?;
+    let timings = Timings::new(&events);
+    let http = HttpClient::new(&config, None)?;
+
+    let instance = Instance::load();
+    let account = credentials(wrapper_account(
+        &args[2..5],
+        options.account.as_deref(),
+        instance.as_ref(),
+        &config,
+    )?)?;
+    // read before this login is recorded
+    let last_login = audit::last_success(&account.username);
+    let client_token = account
+        .client_token
+        .clone()
+        .unwrap_or_else(generate_client_token);
+    let api_urls: Vec<&str> = std::iter::once(account.api_url())
+        .chain(config.server.fallback_api_urls.iter().map(String::as_str))
+        .collect();
+    let login_result =
+        login_with_fallback(&account, &client_token, &api_urls, &http, &config, &timings).await?;
+    print!("{}", format_whoami(&login_result, last_login.as_deref()));
+    Ok(())
+}
+
+fn format_whoami(login_result: &LoginResult, last_login: Option<&str>) -> String {
+    let texture = |url: &Option<String>, guid: &Option<String>| {
+        skin::describe(url.as_deref(), guid.as_deref())
+    };
+    let textures = &login_result.textures;
+    let expires = match login_result.expired_date.as_deref() {
+        Some(date) => session::parse_expiry(Some(date)).map_or_else(
+            || date.to_string(),
+            |expires_at| dates::format_iso8601(UNIX_EPOCH + Duration::from_secs(expires_at)),
+        ),
+        None => tr!("whoami-unknown"),
+    };
+    let profile = &login_result.selected_profile;
+    [
+        format!("{} ({})", profile.name, profile.id),
+        tr!("whoami-server", url = login_result.api_url.as_str()),
+        tr!(
+            "whoami-skin",
+            texture = texture(&textures.skin_url, &textures.skin_guid)
+        ),
+        tr!(
+            "whoami-cloak",
+            texture = texture(&textures.cloak_url, &textures.cloak_guid)
+        ),
+        tr!(
+            "whoami-full-skin",
+            texture = texture(&textures.full_skin_url, &None)
+        ),
+        tr!("whoami-expires", time = expires),
+        match last_login {
+            Some(time) => tr!("whoami-last-login", time = time),
+            None => tr!("whoami-last-login-unknown"),
+        },
+    ]
+    .iter()
+    .map(|line| line.to_string() + "\n")
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{Fake, Faker};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::model::Profile;
+
+    fn get_fake_args(length: usize) -> Vec<String> {
+        let seed = [
+            1, 0, 0, 0, 23, 0, 0, 0, 200, 1, 0, 0, 210, 30, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0,
+        ];
+        let r = &mut StdRng::from_seed(seed);
+        (0..length)
+            .map(|_| Faker.fake_with_rng::<String, _>(r))
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_args() {
+        assert!(matches!(
+            validate_args(&get_fake_args(1)),
+            Err(MmcaiError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            validate_args(&get_fake_args(2)),
+            Err(MmcaiError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            validate_args(&get_fake_args(3)),
+            Err(MmcaiError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            validate_args(&get_fake_args(4)),
+            Err(MmcaiError::CannotRunDirectly)
+        ));
+        assert!(matches!(validate_args(&get_fake_args(5)), Ok(())));
+    }
+
+    #[test]
+    fn test_commands_need_marker() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        // a player named `skin`, launched directly
+        let options = cli::Options::default();
+        let result = runtime.block_on(run(&options, args(&["mmcai_rs", "skin", "pw", "api"])));
+        assert!(matches!(result, Err(MmcaiError::CannotRunDirectly)));
+
+        let options = cli::Options {
+            command: true,
+            ..Default::default()
+        };
+        let result = runtime.block_on(run(&options, args(&["mmcai_rs", "skins"])));
+        assert!(matches!(result, Err(MmcaiError::CommandUsage(_))));
+    }
+
+    #[test]
+    fn test_format_whoami() {
+        let login_result = LoginResult {
+            api_url: "https://auth.example.com/api".into(),
+            prefetched_data: None,
+            access_token: "t".into(),
+            expired_date: Some("2025-04-01T12:30:00Z".into()),
+            selected_profile: Profile {
+                id: "0f8d".into(),
+                name: "Steve".into(),
+            },
+            textures: skin::Textures {
+                skin_url: Some("https://skins.example.com/steve.png".into()),
+                skin_guid: Some("5e1f".into()),
+                ..Default::default()
+            },
+            server_ip: None,
+        };
+        let lines = format_whoami(&login_result, None);
+        let lines: Vec<&str> = lines.lines().collect();
+        assert_eq!(lines[0], "Steve (0f8d)");
+        assert_eq!(lines[2], "Skin: https://skins.example.com/steve.png (5e1f)");
+        assert_eq!(lines[3], "Cloak: none");
+        assert_eq!(lines[5], "Token expires: 2025-04-01T12:30:00Z");
+    }
+}
@@ -0,0 +1,118 @@
+use std::{path::Path, process::ExitStatus};
+
+use tracing::warn;
+
+use crate::i18n::tr;
+
+/// Output lines that tell why the game died, with what to tell the player.
+const SIGNATURES: &[(&str, &str)] = &[
+    (
+        "---- Minecraft Crash Report ----",
+        "Minecraft wrote a crash report (see crash-reports in the instance folder)",
+    ),
+    (
+        "A fatal error has been detected by the Java Runtime Environment",
+        "the JVM itself crashed",
+    ),
+    ("java.lang.OutOfMemoryError", "the game ran out of memory"),
+    (
+        "Could not reserve enough space for object heap",
+        "Java could not reserve the configured memory",
+    ),
+    (
+        "Error occurred during initialization of VM",
+        "Java failed to start, check the JVM arguments",
+    ),
+    (
+        "Error opening zip file or JAR manifest missing",
+        "the -javaagent jar is missing or broken",
+    ),
+    (
+        "Exception in thread \"main\"",
+        "the game threw an exception on startup",
+    ),
+];
+
+/// Returns the description of the crash signature in `line`, if there is one.
+pub fn scan(line: &[u8]) -> Option<&'static str> {
+    let line = String::from_utf8_lossy(line);
+    SIGNATURES
+        .iter()
+        .find(|(needle, _)| line.contains(needle))
+        .map(|(_, description)| *description)
+}
+
+/// Why the game died, collected from its exit status, its output and hs_err files.
+#[derive(Debug, PartialEq)]
+pub struct CrashReport {
+    pub reasons: Vec<String>,
+}
+
+impl CrashReport {
+    /// `dir` is where the JVM writes `hs_err_pid<pid>.log` when it crashes: the working
+    /// directory of the game.
+    pub fn diagnose(status: ExitStatus, pid: u32, signs: &[&str], dir: &Path) -> CrashReport {
+        let mut reasons = Vec::new();
+        if let Some(code) = status.code() {
+            reasons.push(format!("the game exited with code {}", code));
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            if let Some(signal) = status.signal() {
+                reasons.push(format!("the game was killed by signal {}", signal));
+            }
+        }
+        for sign in signs {
+            if !reasons.iter().any(|reason| reason == sign) {
+                reasons.push(sign.to_string());
+            }
+        }
+        let hs_err = dir.join(format!("hs_err_pid{}.log", pid));
+        if hs_err.is_file() {
+            reasons.push(format!("the JVM wrote a crash log to {:?}", hs_err));
+        }
+        CrashReport { reasons }
+    }
+
+    pub fn print(&self) {
+        warn!("{}", tr!("game-crashed"));
+        for reason in &self.reasons {
+            warn!("  - {}", reason);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_fs::{prelude::*, TempDir};
+
+    use super::*;
+
+    #[test]
+    fn test_scan() {
+        assert_eq!(
+            scan(b"Exception in thread \"main\" java.lang.OutOfMemoryError: Java heap space"),
+            Some("the game ran out of memory")
+        );
+        assert_eq!(scan(b"[Render thread/INFO]: Setting user: Player"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_diagnose() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let dir = TempDir::new().unwrap();
+        dir.child("hs_err_pid42.log").touch().unwrap();
+        let report = CrashReport::diagnose(
+            ExitStatus::from_raw(1 << 8),
+            42,
+            &["the JVM itself crashed"],
+            dir.path(),
+        );
+        assert_eq!(report.reasons.len(), 3);
+        assert_eq!(report.reasons[0], "the game exited with code 1");
+        assert!(report.reasons[2].contains("hs_err_pid42.log"));
+    }
+}
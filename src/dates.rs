@@ -0,0 +1,183 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Seconds since the Unix epoch, saturating at zero for clocks set before 1970.
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Converts a UTC calendar date and time to a point in time.
+pub fn from_civil(
+    year: i64,
+    month: u32,
+    day: u32,
+    hours: u32,
+    minutes: u32,
+    seconds: u32,
+) -> Option<SystemTime> {
+    if !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || hours > 23
+        || minutes > 59
+        || seconds > 60
+    {
+        return None;
+    }
+
+    // days since the epoch for a proleptic Gregorian date (Howard Hinnant's algorithm)
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let year_of_era = y - era * 400;
+    let month_index = (month as i64 + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = u64::try_from(era * 146097 + day_of_era - 719468).ok()?;
+
+    let seconds = days * 86400 + hours as u64 * 3600 + minutes as u64 * 60 + seconds as u64;
+    Some(UNIX_EPOCH + Duration::from_secs(seconds))
+}
+
+/// Parses ISO 8601 timestamps like `2025-04-01T12:30:00Z`, `2025-04-01 12:30:00.123`
+/// or `2025-04-01T12:30:00+03:00` (also `+0300` and `+03`). A missing offset is taken as UTC.
+pub fn parse_iso8601(value: &str) -> Option<SystemTime> {
+    let value = value.trim();
+    let (date, time) = value.split_once(['T', ' '])?;
+
+    let mut date = date.splitn(3, '-');
+    let year = date.next()?.parse().ok()?;
+    let month = date.next()?.parse().ok()?;
+    let day = date.next()?.parse().ok()?;
+
+    // split off the offset, which starts at 'Z', '+' or the '-' after the seconds
+    let offset_start = time.find(['Z', 'z', '+', '-']).unwrap_or(time.len());
+    let (clock, offset) = time.split_at(offset_start);
+    let mut clock = clock.splitn(3, ':');
+    let hours = clock.next()?.parse().ok()?;
+    let minutes = clock.next()?.parse().ok()?;
+    let seconds = match clock.next() {
+        Some(seconds) => seconds.split('.').next()?.parse().ok()?,
+        None => 0,
+    };
+
+    let utc = from_civil(year, month, day, hours, minutes, seconds)?;
+    let offset_seconds: i64 = match offset {
+        "" | "Z" | "z" => 0,
+        _ => {
+            let sign = if offset.starts_with('-') { -1 } else { 1 };
+            // `+03:00`, `+0300` or just the hours, `+03`
+            let offset = &offset[1..];
+            let (offset_hours, offset_minutes) = offset
+                .split_once(':')
+                .unwrap_or_else(|| offset.split_at(offset.len().min(2)));
+            let offset_hours: i64 = offset_hours.parse().ok()?;
+            let offset_minutes: i64 = match offset_minutes {
+                "" => 0,
+                offset_minutes => offset_minutes.parse().ok()?,
+            };
+            sign * (offset_hours * 3600 + offset_minutes * 60)
+        }
+    };
+    // local time = UTC + offset, so UTC = local - offset
+    if offset_seconds >= 0 {
+        utc.checked_sub(Duration::from_secs(offset_seconds as u64))
+    } else {
+        utc.checked_add(Duration::from_secs(offset_seconds.unsigned_abs()))
+    }
+}
+
+/// Formats the UTC time of day as `HH:MM:SS`.
+pub fn format_time_of_day(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+        % 86400;
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// Formats a point in time as `2025-04-01T12:30:00Z`.
+pub fn format_iso8601(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    // the inverse of from_civil (Howard Hinnant's civil_from_days)
+    let days = (seconds / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!(
+        "{:04}-{:02}-{:02}T{}Z",
+        year,
+        month,
+        day,
+        format_time_of_day(time)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(seconds: u64) -> Option<SystemTime> {
+        Some(UNIX_EPOCH + Duration::from_secs(seconds))
+    }
+
+    #[test]
+    fn test_format_time_of_day() {
+        let time = from_civil(2025, 4, 1, 9, 5, 7).unwrap();
+        assert_eq!(format_time_of_day(time), "09:05:07");
+    }
+
+    #[test]
+    fn test_format_iso8601() {
+        for (year, month, day) in [(1970, 1, 1), (2000, 2, 29), (2025, 12, 31)] {
+            let time = from_civil(year, month, day, 23, 59, 1).unwrap();
+            assert_eq!(parse_iso8601(&format_iso8601(time)), Some(time));
+        }
+        assert_eq!(
+            format_iso8601(from_civil(2015, 10, 21, 7, 28, 0).unwrap()),
+            "2015-10-21T07:28:00Z"
+        );
+    }
+
+    #[test]
+    fn test_from_civil() {
+        assert_eq!(from_civil(1970, 1, 1, 0, 0, 0), at(0));
+        assert_eq!(from_civil(2015, 10, 21, 7, 28, 0), at(1445412480));
+        assert_eq!(from_civil(2024, 2, 29, 0, 0, 0), at(1709164800));
+        assert_eq!(from_civil(2024, 13, 1, 0, 0, 0), None);
+        assert_eq!(from_civil(1969, 12, 31, 0, 0, 0), None);
+    }
+
+    #[test]
+    fn test_parse_iso8601() {
+        assert_eq!(parse_iso8601("2015-10-21T07:28:00Z"), at(1445412480));
+        assert_eq!(parse_iso8601("2015-10-21 07:28:00.5"), at(1445412480));
+        assert_eq!(parse_iso8601("2015-10-21T10:28:00+03:00"), at(1445412480));
+        assert_eq!(parse_iso8601("2015-10-21T02:28:00-0500"), at(1445412480));
+        assert_eq!(parse_iso8601("2015-10-21T07:28Z"), at(1445412480));
+        assert_eq!(parse_iso8601("2015-10-21T12:28:00+05"), at(1445412480));
+        assert_eq!(parse_iso8601("2015-10-21T04:28:00-03"), at(1445412480));
+        assert_eq!(parse_iso8601("2015-10-21T07:28:00+"), None);
+        assert_eq!(parse_iso8601("next tuesday"), None);
+        assert_eq!(parse_iso8601("2015-10-21"), None);
+    }
+}
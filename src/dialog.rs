@@ -0,0 +1,291 @@
+#[cfg(not(windows))]
+use crate::i18n::tr;
+
+const TITLE: &str = "mmcai_rs";
+
+/// Shows the error in a native message box, for runs where nobody sees stderr, like a
+/// GUI-subsystem build or a launcher started from a desktop shortcut. Best effort: when
+/// no dialog can be shown the error is only printed.
+pub fn show_error(message: &str) {
+    #[cfg(all(unix, not(target_os = "macos")))]
+    if !has_display() {
+        return;
+    }
+    rfd::MessageDialog::new()
+        .set_title(TITLE)
+        .set_description(message)
+        .set_level(rfd::MessageLevel::Error)
+        .set_buttons(rfd::MessageButtons::Ok)
+        .show();
+}
+
+/// Asks for whichever of the username and password isn't known yet. `None` when the
+/// user cancelled or no dialog could be shown.
+pub fn ask_credentials(
+    server: &str,
+    username: Option<&str>,
+    password: Option<&str>,
+) -> Option<(String, String)> {
+    #[cfg(windows)]
+    {
+        let (asked_username, asked_password) = windows::ask_credentials(server, username)?;
+        Some((
+            username.map_or(asked_username, str::to_string),
+            password.map_or(asked_password, str::to_string),
+        ))
+    }
+    #[cfg(not(windows))]
+    {
+        let username = match username {
+            Some(username) => username.to_string(),
+            None => ask(&tr!("dialog-username", server = server), false)?,
+        };
+        let password = match password {
+            Some(password) => password.to_string(),
+            None => ask(
+                &tr!("dialog-password", username = username, server = server),
+                true,
+            )?,
+        };
+        Some((username, password))
+    }
+}
+
+/// Asks for a password. `None` when cancelled or no dialog could be shown; on Windows
+/// it is the credential dialog, whose username field is ignored.
+pub fn ask_secret(prompt: &str) -> Option<String> {
+    #[cfg(windows)]
+    return windows::prompt(prompt, TITLE, None).map(|(_, password)| password);
+    #[cfg(not(windows))]
+    ask(prompt, true)
+}
+
+/// Lets the user pick one of `items`, starting at `selected`. `None` when cancelled or
+/// no dialog could be shown; Windows has no native list dialog to use.
+#[cfg_attr(windows, allow(unused_variables))]
+pub fn choose(prompt: &str, items: &[String], selected: usize) -> Option<usize> {
+    #[cfg(windows)]
+    return None;
+    #[cfg(target_os = "macos")]
+    {
+        let list: Vec<String> = items.iter().map(|item| applescript_string(item)).collect();
+        let script = format!(
+            "choose from list {{{}}} with prompt {} with title {} default items {{{}}}",
+            list.join(", "),
+            applescript_string(prompt),
+            applescript_string(TITLE),
+            list.get(selected)?
+        );
+        let output = std::process::Command::new("osascript")
+            .arg("-e")
+            .arg(script)
+            .output()
+            .ok()?;
+        // "false" when cancelled
+        let chosen = answer(output)?;
+        items.iter().position(|item| *item == chosen)
+    }
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        if !has_display() {
+            return None;
+        }
+        let mut zenity = std::process::Command::new("zenity");
+        zenity.args(["--list", "--radiolist", "--title", TITLE, "--text", prompt]);
+        zenity.args(["--column", "", "--column", "#", "--column", ""]);
+        zenity.args(["--hide-column", "2", "--print-column", "2", "--hide-header"]);
+        let mut kdialog = std::process::Command::new("kdialog");
+        kdialog.args([
+            "--title",
+            TITLE,
+            "--default",
+            items.get(selected)?,
+            "--menu",
+            prompt,
+        ]);
+        for (index, item) in items.iter().enumerate() {
+            let checked = if index == selected { "TRUE" } else { "FALSE" };
+            zenity.args([checked, &index.to_string(), item]);
+            kdialog.arg(index.to_string()).arg(item);
+        }
+        let output = zenity.output().or_else(|_| kdialog.output());
+        answer(output.ok()?)?.parse().ok()
+    }
+}
+
+/// One line of input; `hidden` for passwords.
+#[cfg(target_os = "macos")]
+fn ask(prompt: &str, hidden: bool) -> Option<String> {
+    let script = format!(
+        "text returned of (display dialog {} default answer \"\"{} with title {})",
+        applescript_string(prompt),
+        if hidden { " with hidden answer" } else { "" },
+        applescript_string(TITLE)
+    );
+    let output = std::process::Command::new("osascript")
+        .arg("-e")
+        .arg(script)
+        .output()
+        .ok()?;
+    answer(output)
+}
+
+/// One line of input from zenity or, without it, kdialog; `hidden` for passwords.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn ask(prompt: &str, hidden: bool) -> Option<String> {
+    if !has_display() {
+        return None;
+    }
+    let mut zenity = std::process::Command::new("zenity");
+    zenity.args(["--entry", "--title", TITLE, "--text", prompt]);
+    if hidden {
+        zenity.arg("--hide-text");
+    }
+    let output = zenity.output().or_else(|_| {
+        std::process::Command::new("kdialog")
+            .args(["--title", TITLE])
+            .arg(if hidden { "--password" } else { "--inputbox" })
+            .arg(prompt)
+            .output()
+    });
+    answer(output.ok()?)
+}
+
+/// The dialogs exit unsuccessfully when cancelled.
+#[cfg(unix)]
+fn answer(output: std::process::Output) -> Option<String> {
+    if !output.status.success() {
+        return None;
+    }
+    let answer = String::from_utf8(output.stdout).ok()?;
+    Some(answer.trim_end_matches(['\r', '\n']).to_string())
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn has_display() -> bool {
+    std::env::var_os("DISPLAY").is_some() || std::env::var_os("WAYLAND_DISPLAY").is_some()
+}
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn applescript_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(windows)]
+mod windows {
+    use std::{ffi::c_void, iter, mem, ptr};
+
+    use crate::i18n::tr;
+
+    const CREDUI_FLAGS_DO_NOT_PERSIST: u32 = 0x00002;
+    const CREDUI_FLAGS_EXCLUDE_CERTIFICATES: u32 = 0x00008;
+    const CREDUI_FLAGS_ALWAYS_SHOW_UI: u32 = 0x00080;
+    const CREDUI_FLAGS_GENERIC_CREDENTIALS: u32 = 0x40000;
+    const CREDUI_MAX_USERNAME_LENGTH: usize = 513;
+    const CREDUI_MAX_PASSWORD_LENGTH: usize = 256;
+
+    #[repr(C)]
+    struct CredUiInfo {
+        size: u32,
+        parent: *mut c_void,
+        message: *const u16,
+        caption: *const u16,
+        banner: *mut c_void,
+    }
+
+    #[link(name = "credui")]
+    extern "system" {
+        fn CredUIPromptForCredentialsW(
+            ui_info: *const CredUiInfo,
+            target_name: *const u16,
+            reserved: *mut c_void,
+            auth_error: u32,
+            username: *mut u16,
+            username_size: u32,
+            password: *mut u16,
+            password_size: u32,
+            save: *mut i32,
+            flags: u32,
+        ) -> u32;
+    }
+
+    /// The standard Windows credential dialog; nothing is stored in the Credential
+    /// Manager.
+    pub fn ask_credentials(server: &str, username: Option<&str>) -> Option<(String, String)> {
+        prompt(
+            &tr!("dialog-credentials", server = server),
+            server,
+            username,
+        )
+    }
+
+    /// The credential dialog with `message`, for the credentials of `target`.
+    pub fn prompt(message: &str, target: &str, username: Option<&str>) -> Option<(String, String)> {
+        let caption = wide(super::TITLE);
+        let message = wide(message);
+        let info = CredUiInfo {
+            size: mem::size_of::<CredUiInfo>() as u32,
+            parent: ptr::null_mut(),
+            message: message.as_ptr(),
+            caption: caption.as_ptr(),
+            banner: ptr::null_mut(),
+        };
+        let target = wide(target);
+        let mut username_buffer = [0u16; CREDUI_MAX_USERNAME_LENGTH + 1];
+        for (slot, unit) in username_buffer
+            .iter_mut()
+            .zip(username.unwrap_or_default().encode_utf16())
+            .take(CREDUI_MAX_USERNAME_LENGTH)
+        {
+            *slot = unit;
+        }
+        let mut password_buffer = [0u16; CREDUI_MAX_PASSWORD_LENGTH + 1];
+        let mut save = 0;
+        let result = unsafe {
+            CredUIPromptForCredentialsW(
+                &info,
+                target.as_ptr(),
+                ptr::null_mut(),
+                0,
+                username_buffer.as_mut_ptr(),
+                username_buffer.len() as u32,
+                password_buffer.as_mut_ptr(),
+                password_buffer.len() as u32,
+                &mut save,
+                CREDUI_FLAGS_GENERIC_CREDENTIALS
+                    | CREDUI_FLAGS_ALWAYS_SHOW_UI
+                    | CREDUI_FLAGS_DO_NOT_PERSIST
+                    | CREDUI_FLAGS_EXCLUDE_CERTIFICATES,
+            )
+        };
+        let credentials =
+            (result == 0).then(|| (from_wide(&username_buffer), from_wide(&password_buffer)));
+        password_buffer.fill(0);
+        credentials
+    }
+
+    fn from_wide(buffer: &[u16]) -> String {
+        let length = buffer
+            .iter()
+            .position(|&unit| unit == 0)
+            .unwrap_or(buffer.len());
+        String::from_utf16_lossy(&buffer[..length])
+    }
+
+    fn wide(value: &str) -> Vec<u16> {
+        value.encode_utf16().chain(iter::once(0)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_applescript_string() {
+        assert_eq!(
+            applescript_string(r#"Wrong "password" \o/"#),
+            r#""Wrong \"password\" \\o/""#
+        );
+    }
+}
@@ -0,0 +1,75 @@
+use std::{fs, path::Path};
+
+use tracing::info;
+
+use crate::errors::MmcaiError;
+use crate::i18n::tr;
+use crate::params::LaunchParams;
+use crate::Result;
+
+/// Writes what is about to be handed to the JVM (`--dump-launch`): the command line and
+/// the patched param block. Tokens are redacted unless `unsafe_full` is set.
+pub fn write(
+    path: &Path,
+    java_executable: &str,
+    jvm_args: &[String],
+    minecraft_params: &[String],
+    unsafe_full: bool,
+) -> Result<()> {
+    fs::write(
+        path,
+        format(java_executable, jvm_args, minecraft_params, unsafe_full),
+    )
+    .map_err(|source| MmcaiError::WriteLaunchDumpFailed {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    info!("{}", tr!("dump-written", path = format!("{:?}", path)));
+    Ok(())
+}
+
+fn format(
+    java_executable: &str,
+    jvm_args: &[String],
+    minecraft_params: &[String],
+    unsafe_full: bool,
+) -> String {
+    let mut params = LaunchParams::parse(minecraft_params);
+    if !unsafe_full {
+        params = params.redacted();
+    }
+
+    let mut dump = String::from("# command line\n");
+    dump.push_str(java_executable);
+    dump.push('\n');
+    for arg in jvm_args {
+        dump.push_str(arg);
+        dump.push('\n');
+    }
+    dump.push_str("\n# params\n");
+    for line in params.to_lines() {
+        dump.push_str(&line);
+        dump.push('\n');
+    }
+    dump
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        let jvm_args = vec!["-javaagent:authlib-injector.jar=http://x".to_string()];
+        let params = vec![
+            "param --accessToken".to_string(),
+            "param secret".to_string(),
+            "launch".to_string(),
+        ];
+        assert_eq!(
+            format("java", &jvm_args, &params, false),
+            "# command line\njava\n-javaagent:authlib-injector.jar=http://x\n\n# params\nparam --accessToken\nparam <redacted>\nlaunch\n"
+        );
+        assert!(format("java", &jvm_args, &params, true).contains("param secret"));
+    }
+}
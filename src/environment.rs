@@ -0,0 +1,111 @@
+use std::ffi::OsString;
+
+use crate::config::EnvConfig;
+
+/// Builds the environment of the game from the wrapper's, or returns `None` when the
+/// config doesn't change anything and the environment can simply be inherited.
+pub fn child_environment<I>(config: &EnvConfig, vars: I) -> Option<Vec<(OsString, OsString)>>
+where
+    I: IntoIterator<Item = (OsString, OsString)>,
+{
+    if config.allow.is_empty() && config.deny.is_empty() && config.set.is_empty() {
+        return None;
+    }
+
+    let mut environment: Vec<(OsString, OsString)> = vars
+        .into_iter()
+        .filter(|(name, _)| is_forwarded(config, &name.to_string_lossy()))
+        .collect();
+    for (name, value) in &config.set {
+        environment.retain(|(existing, _)| !existing.to_string_lossy().eq_ignore_ascii_case(name));
+        environment.push((name.into(), value.into()));
+    }
+    Some(environment)
+}
+
+fn is_forwarded(config: &EnvConfig, name: &str) -> bool {
+    // Prism's INST_* variables describe the instance and are always wanted
+    let allowed = config.allow.is_empty()
+        || matches(name, "INST_*")
+        || config.allow.iter().any(|pattern| matches(name, pattern));
+    allowed && !config.deny.iter().any(|pattern| matches(name, pattern))
+}
+
+/// Case-insensitive (Windows variables are) match where `*` stands for any characters.
+fn matches(name: &str, pattern: &str) -> bool {
+    let name = name.to_ascii_uppercase();
+    let pattern = pattern.to_ascii_uppercase();
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // no wildcard at all
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    fn vars(names: &[&str]) -> Vec<(OsString, OsString)> {
+        names
+            .iter()
+            .map(|name| (OsString::from(name), OsString::from("x")))
+            .collect()
+    }
+
+    fn names(environment: Option<Vec<(OsString, OsString)>>) -> Vec<String> {
+        environment
+            .unwrap()
+            .into_iter()
+            .map(|(name, _)| name.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_matches() {
+        assert!(matches("HTTPS_PROXY", "*_proxy"));
+        assert!(matches("INST_JAVA", "INST_*"));
+        assert!(matches("AWS_SECRET_ACCESS_KEY", "AWS_*_KEY"));
+        assert!(matches("PATH", "PATH"));
+        assert!(!matches("PATHEXT", "PATH"));
+        assert!(!matches("MY_PATH", "PATH*"));
+    }
+
+    #[test]
+    fn test_child_environment() {
+        let all = vars(&["PATH", "HTTP_PROXY", "INST_DIR", "GITHUB_TOKEN"]);
+        assert_eq!(child_environment(&EnvConfig::default(), all.clone()), None);
+
+        let config = EnvConfig {
+            deny: vec!["*_PROXY".into(), "*TOKEN*".into()],
+            ..Default::default()
+        };
+        assert_eq!(
+            names(child_environment(&config, all.clone())),
+            ["PATH", "INST_DIR"]
+        );
+
+        let mut set = BTreeMap::new();
+        set.insert("path".to_string(), "/usr/bin".to_string());
+        let config = EnvConfig {
+            allow: vec!["PATH".into()],
+            set,
+            ..Default::default()
+        };
+        assert_eq!(names(child_environment(&config, all)), ["INST_DIR", "path"]);
+    }
+}
@@ -1,46 +1,875 @@
 use reqwest::Error as ReqwestError;
+use serde_json::{json, Value};
+use std::error::Error as _;
 use std::io::Error as IoError;
+use std::path::PathBuf;
 use thiserror::Error;
 
+use crate::i18n::tr;
+
+/// Everything the wrapper and the library can fail with. Each error has a stable
+/// [`code`](MmcaiError::code) and exit code; new variants may be added in any release.
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum MmcaiError {
-    #[error("Usage: {0} <username> <password> <api url>")]
+    #[error("{}", tr!("error-invalid-argument", program = _0))]
     InvalidArgument(String),
 
-    #[error("Looks like you have entered a valid command, but you can't run mmcai_rs directly! Put your command in \"Wrapper command\" in Prism Launcher.")]
+    #[error("{}", tr!("error-unknown-option", option = _0))]
+    UnknownOption(String),
+
+    #[error("{}", tr!("error-missing-option-value", option = _0))]
+    MissingOptionValue(String),
+
+    #[error("{}", tr!("error-invalid-option-value", option = _0, value = format!("{:?}", _1)))]
+    InvalidOptionValue(String, String),
+
+    #[error("{}", tr!("error-cannot-run-directly"))]
     CannotRunDirectly,
 
-    #[error("authlib-injector not found in the same directory as mmcai_rs.")]
+    #[error("{}", tr!("error-authlib-injector-not-found"))]
     AuthlibInjectorNotFound,
 
-    #[error("Cannot reach the authentication server.")]
+    #[error("{}", tr!("error-yggdrasil-hello-failed"))]
     YggdrasilHelloFailed(#[source] ReqwestError),
 
-    #[error("Wrong username or password. Server response: {response}")]
+    #[error("{}", tr!("error-invalid-metadata", api_url = api_url, reason = reason, excerpt = excerpt))]
+    InvalidMetadata {
+        api_url: String,
+        reason: String,
+        excerpt: String,
+    },
+
+    #[error("{}", tr!("error-server-unhealthy", host = host, problem = problem))]
+    ServerUnhealthy { host: String, problem: String },
+
+    #[error("{}", tr!("error-rate-limited", retry_after = retry_after))]
+    RateLimited { retry_after: u64 },
+
+    #[error("{}", tr!("error-yggdrasil-auth-failed", response = response))]
     YggdrasilAuthFailed {
         #[source]
         source: ReqwestError,
         response: String,
     },
 
-    #[error("Cannot build reqwest client. This should not happen. Please report this issue to the developers.")]
+    #[error("{}", tr!("error-invalid-auth-response", message = message, response = response))]
+    InvalidAuthResponse { message: String, response: String },
+
+    #[error("{}", tr!("error-invalid-credentials", message = message))]
+    InvalidCredentials { message: String },
+
+    #[error("{}", tr!("error-credentials-not-given"))]
+    CredentialsNotGiven,
+
+    #[error("{}", tr!("error-account-not-activated", message = message, see = see(url)))]
+    AccountNotActivated {
+        message: String,
+        url: Option<String>,
+    },
+
+    #[error("{}", tr!("error-account-blocked", message = message, see = see(url)))]
+    AccountBlocked {
+        message: String,
+        url: Option<String>,
+    },
+
+    #[error("{}", tr!("error-account-locked", message = message, see = see(url)))]
+    AccountLocked {
+        message: String,
+        url: Option<String>,
+    },
+
+    #[error("{}", tr!("error-login-rejected", status_code = status_code, message = message))]
+    LoginRejected { status_code: u16, message: String },
+
+    #[error("{}", tr!("error-unknown-profile", name = name, available = available))]
+    UnknownProfile { name: String, available: String },
+
+    #[error("{}", tr!("error-reqwest-client-build-failed"))]
     ReqwestClientBuildFailed(#[source] ReqwestError),
 
-    #[error("Cannot read Minecraft params. This should not happen. Please report this issue to the developers.")]
+    #[error("{}", tr!("error-read-config-failed", path = format!("{:?}", path)))]
+    ReadConfigFailed {
+        path: PathBuf,
+        #[source]
+        source: IoError,
+    },
+
+    #[error("{}", tr!("error-invalid-config", path = format!("{:?}", path), message = message))]
+    InvalidConfig { path: PathBuf, message: String },
+
+    #[error("{}", tr!("error-unknown-backend", name = format!("{:?}", name), available = available))]
+    UnknownBackend { name: String, available: String },
+
+    #[error("{}", tr!("error-invalid-endpoint", endpoint = endpoint, message = message))]
+    InvalidEndpoint { endpoint: String, message: String },
+
+    #[error("{}", tr!("error-invalid-proxy", url = format!("{:?}", url)))]
+    InvalidProxy {
+        url: String,
+        #[source]
+        source: ReqwestError,
+    },
+
+    #[error("{}", tr!("error-read-certificate-failed", path = format!("{:?}", path)))]
+    ReadCertificateFailed {
+        path: PathBuf,
+        #[source]
+        source: IoError,
+    },
+
+    #[error("{}", tr!("error-invalid-certificate", path = format!("{:?}", path)))]
+    InvalidCertificate {
+        path: PathBuf,
+        #[source]
+        source: Option<ReqwestError>,
+    },
+
+    #[error("{}", tr!("error-certificate-pin-mismatch", host = host))]
+    CertificatePinMismatch { host: String },
+
+    #[error("{}", tr!("error-certificate-pin-unavailable", host = host))]
+    CertificatePinUnavailable { host: String },
+
+    #[error("{}", tr!("error-open-trace-file-failed", path = format!("{:?}", path)))]
+    OpenTraceFileFailed {
+        path: PathBuf,
+        #[source]
+        source: IoError,
+    },
+
+    #[error("{}", tr!("error-runtime-build-failed"))]
+    RuntimeBuildFailed(#[source] IoError),
+
+    #[error("{}", tr!("error-read-minecraft-params-failed"))]
     ReadMinecraftParamsFailed(#[source] IoError),
 
-    #[error("Cannot write Minecraft params. This should not happen. Please report this issue to the developers.")]
+    #[error("{}", tr!("error-invalid-minecraft-params", param = _0))]
+    InvalidMinecraftParams(String),
+
+    #[error("{}", tr!("error-params-timed-out", seconds = seconds))]
+    ParamsTimedOut { seconds: u64 },
+
+    /// The launcher cancelled the launch; the wrapper exits without an error.
+    #[error("{}", tr!("error-launch-aborted"))]
+    LaunchAborted,
+
+    #[error("{}", tr!("error-write-minecraft-params-failed"))]
     WriteMinecraftParamsFailed(#[source] IoError),
 
-    #[error("Cannot start Minecraft. This should not happen. Please report this issue to the developers.")]
+    #[error("{}", tr!("error-read-params-file-failed", path = format!("{:?}", path)))]
+    ReadParamsFileFailed {
+        path: PathBuf,
+        #[source]
+        source: IoError,
+    },
+
+    #[error("{}", tr!("error-write-params-record-failed", path = format!("{:?}", path)))]
+    WriteParamsRecordFailed {
+        path: PathBuf,
+        #[source]
+        source: IoError,
+    },
+
+    #[error("{}", tr!("error-write-launch-dump-failed", path = format!("{:?}", path)))]
+    WriteLaunchDumpFailed {
+        path: PathBuf,
+        #[source]
+        source: IoError,
+    },
+
+    #[error("{}", tr!("error-open-log-file-failed", path = format!("{:?}", path)))]
+    OpenLogFileFailed {
+        path: PathBuf,
+        #[source]
+        source: IoError,
+    },
+
+    #[error("{}", tr!("error-open-events-failed", target = target))]
+    OpenEventsFailed {
+        target: String,
+        #[source]
+        source: IoError,
+    },
+
+    #[error("{}", tr!("error-connect-journald-failed"))]
+    ConnectJournaldFailed(#[source] IoError),
+
+    #[error("{}", tr!("error-open-event-log-failed"))]
+    OpenEventLogFailed(#[source] IoError),
+
+    #[error("{}", tr!("error-read-accounts-failed", path = format!("{:?}", path)))]
+    ReadAccountsFailed {
+        path: PathBuf,
+        #[source]
+        source: IoError,
+    },
+
+    #[error("{}", tr!("error-invalid-accounts", path = format!("{:?}", path), message = message))]
+    InvalidAccounts { path: PathBuf, message: String },
+
+    #[error("{}", tr!("error-unknown-account", account = _0))]
+    UnknownAccount(String),
+
+    #[error("{}", tr!("error-command-usage", usage = _0))]
+    CommandUsage(String),
+
+    #[error("{}", tr!("error-missing-api-url"))]
+    MissingApiUrl,
+
+    #[error("{}", tr!("error-account-without-server", account = _0))]
+    AccountWithoutServer(String),
+
+    #[error("{}", tr!("error-passwords-differ"))]
+    PasswordsDiffer,
+
+    #[error("{}", tr!("error-read-texture-failed", path = format!("{:?}", path)))]
+    ReadTextureFailed {
+        path: PathBuf,
+        #[source]
+        source: IoError,
+    },
+
+    #[error("{}", tr!("error-invalid-texture", path = format!("{:?}", _0)))]
+    InvalidTexture(PathBuf),
+
+    #[error("{}", tr!("error-texture-request-failed"))]
+    TextureRequestFailed(#[source] ReqwestError),
+
+    #[error("{}", tr!("error-texture-rejected", status_code = status_code, message = message))]
+    TextureRejected { status_code: u16, message: String },
+
+    #[error("{}", tr!("error-no-skin", name = _0))]
+    NoSkin(String),
+
+    #[error("{}", tr!("error-unreadable-skin", reason = _0))]
+    UnreadableSkin(String),
+
+    #[error("{}", tr!("error-invalid-skin-name", name = format!("{:?}", _0)))]
+    InvalidSkinName(String),
+
+    #[error("{}", tr!("error-unknown-skin", name = _0))]
+    UnknownSkin(String),
+
+    #[error("{}", tr!("error-texture-forbidden", reason = _0))]
+    TextureForbidden(String),
+
+    #[error("{}", tr!("error-source-profile-not-found", name = _0))]
+    SourceProfileNotFound(String),
+
+    #[error("{}", tr!("error-write-texture-failed", path = format!("{:?}", path)))]
+    WriteTextureFailed {
+        path: PathBuf,
+        #[source]
+        source: IoError,
+    },
+
+    #[error("{}", tr!("error-write-accounts-failed", path = format!("{:?}", path)))]
+    WriteAccountsFailed {
+        path: PathBuf,
+        #[source]
+        source: IoError,
+    },
+
+    #[error("{}", tr!("error-open-wrapper-log-failed", path = format!("{:?}", path)))]
+    OpenWrapperLogFailed {
+        path: PathBuf,
+        #[source]
+        source: IoError,
+    },
+
+    #[error("{}", tr!("error-write-arg-file-failed", path = format!("{:?}", path)))]
+    WriteArgFileFailed {
+        path: PathBuf,
+        #[source]
+        source: IoError,
+    },
+
+    #[error("{}", tr!("error-write-prelaunch-failed", path = format!("{:?}", path)))]
+    WritePrelaunchFailed {
+        path: PathBuf,
+        #[source]
+        source: IoError,
+    },
+
+    #[error("{}", tr!("error-working-directory-not-found", path = format!("{:?}", _0)))]
+    WorkingDirectoryNotFound(PathBuf),
+
+    #[error("{}", tr!("error-spawn-process-failed"))]
     SpawnProcessFailed(#[source] IoError),
 
-    #[error("Cannot write Minecraft params. Stdin is unavailable. This should not happen. Please report this issue to the developers.")]
+    #[error("{}", tr!("error-wait-process-failed"))]
+    WaitProcessFailed(#[source] IoError),
+
+    #[error("{}", tr!("error-game-unresponsive", seconds = seconds))]
+    GameUnresponsive { seconds: u64 },
+
+    #[error("{}", tr!("error-stdin-unavailable"))]
     StdinUnavailable,
 
-    #[error("Cannot find Java executable. This should not happen. Please report this issue to the developers.")]
+    #[error("{}", tr!("error-java-executable-not-found"))]
     JavaExecutableNotFound,
 
-    #[error("Unknown error. This should not happen. Please report this issue to the developers.")]
+    #[error("{}", tr!("error-other"))]
     Other,
 }
+
+impl MmcaiError {
+    /// The code the wrapper exits with when it fails with this error. Each stage has its
+    /// own block of codes, listed in the README's "Exit codes" table; a new error takes the
+    /// next free code of its stage's block, and codes are never reused or renumbered.
+    /// Once the game has run, the wrapper exits with the game's code instead.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            MmcaiError::InvalidArgument(_)
+            | MmcaiError::CommandUsage(_)
+            | MmcaiError::MissingApiUrl
+            | MmcaiError::PasswordsDiffer => 2,
+            MmcaiError::UnknownOption(_) => 3,
+            MmcaiError::MissingOptionValue(_) => 4,
+            MmcaiError::CannotRunDirectly => 5,
+            MmcaiError::ReadConfigFailed { .. } => 6,
+            MmcaiError::InvalidConfig { .. }
+            | MmcaiError::UnknownBackend { .. }
+            | MmcaiError::InvalidEndpoint { .. } => 7,
+            MmcaiError::WorkingDirectoryNotFound(_) => 8,
+            MmcaiError::InvalidOptionValue(..) => 9,
+            MmcaiError::YggdrasilAuthFailed { .. } => 10,
+            MmcaiError::RateLimited { .. } => 11,
+            MmcaiError::YggdrasilHelloFailed(_) => 12,
+            MmcaiError::ServerUnhealthy { .. } => 13,
+            MmcaiError::InvalidMetadata { .. } => 14,
+            MmcaiError::InvalidAuthResponse { .. } => 15,
+            MmcaiError::InvalidCredentials { .. } | MmcaiError::CredentialsNotGiven => 16,
+            MmcaiError::AccountNotActivated { .. } => 17,
+            MmcaiError::AccountBlocked { .. } => 18,
+            MmcaiError::LoginRejected { .. } => 19,
+            MmcaiError::UnknownProfile { .. } => 20,
+            MmcaiError::AccountLocked { .. } => 21,
+            MmcaiError::AuthlibInjectorNotFound => 30,
+            MmcaiError::WriteArgFileFailed { .. } => 31,
+            MmcaiError::WritePrelaunchFailed { .. } => 32,
+            MmcaiError::JavaExecutableNotFound => 50,
+            MmcaiError::SpawnProcessFailed(_) => 51,
+            MmcaiError::StdinUnavailable => 52,
+            MmcaiError::WriteMinecraftParamsFailed(_) => 53,
+            MmcaiError::WaitProcessFailed(_) => 54,
+            MmcaiError::ReadMinecraftParamsFailed(_) => 55,
+            MmcaiError::InvalidMinecraftParams(_) => 56,
+            MmcaiError::ReadParamsFileFailed { .. } => 57,
+            MmcaiError::GameUnresponsive { .. } => 58,
+            MmcaiError::ParamsTimedOut { .. } => 59,
+            MmcaiError::LaunchAborted => 60,
+            MmcaiError::ReqwestClientBuildFailed(_) => 70,
+            MmcaiError::InvalidProxy { .. } => 71,
+            MmcaiError::ReadCertificateFailed { .. } => 72,
+            MmcaiError::InvalidCertificate { .. } => 73,
+            MmcaiError::CertificatePinMismatch { .. } => 74,
+            MmcaiError::CertificatePinUnavailable { .. } => 75,
+            MmcaiError::OpenTraceFileFailed { .. } => 90,
+            MmcaiError::WriteParamsRecordFailed { .. } => 91,
+            MmcaiError::WriteLaunchDumpFailed { .. } => 92,
+            MmcaiError::OpenLogFileFailed { .. } => 93,
+            MmcaiError::OpenWrapperLogFailed { .. } => 94,
+            MmcaiError::OpenEventsFailed { .. } => 95,
+            MmcaiError::ConnectJournaldFailed(_) => 96,
+            MmcaiError::OpenEventLogFailed(_) => 97,
+            MmcaiError::ReadAccountsFailed { .. }
+            | MmcaiError::InvalidAccounts { .. }
+            | MmcaiError::UnknownAccount(_)
+            | MmcaiError::AccountWithoutServer(_) => 98,
+            MmcaiError::WriteAccountsFailed { .. } => 99,
+            MmcaiError::ReadTextureFailed { .. } => 110,
+            MmcaiError::InvalidTexture(_) => 111,
+            MmcaiError::TextureRequestFailed(_) => 112,
+            MmcaiError::TextureRejected { .. } => 113,
+            MmcaiError::NoSkin(_) => 114,
+            MmcaiError::WriteTextureFailed { .. } => 115,
+            MmcaiError::UnreadableSkin(_) => 116,
+            MmcaiError::InvalidSkinName(_) => 117,
+            MmcaiError::UnknownSkin(_) => 118,
+            MmcaiError::SourceProfileNotFound(_) => 119,
+            MmcaiError::TextureForbidden(_) => 120,
+            MmcaiError::RuntimeBuildFailed(_) | MmcaiError::Other => 1,
+        }
+    }
+
+    /// A stable name for the error, for tools that need to tell errors apart where the
+    /// exit code groups several. Like the exit codes, codes are never reused or renamed.
+    pub fn code(&self) -> &'static str {
+        match self {
+            MmcaiError::InvalidArgument(_) => "E_USAGE_BAD_ARGUMENTS",
+            MmcaiError::UnknownOption(_) => "E_USAGE_UNKNOWN_OPTION",
+            MmcaiError::MissingOptionValue(_) => "E_USAGE_MISSING_OPTION_VALUE",
+            MmcaiError::InvalidOptionValue(..) => "E_USAGE_BAD_OPTION_VALUE",
+            MmcaiError::CannotRunDirectly => "E_USAGE_NOT_IN_LAUNCHER",
+            MmcaiError::CommandUsage(_) => "E_USAGE_BAD_COMMAND",
+            MmcaiError::MissingApiUrl => "E_USAGE_MISSING_API_URL",
+            MmcaiError::PasswordsDiffer => "E_USAGE_PASSWORDS_DIFFER",
+            MmcaiError::ReadConfigFailed { .. } => "E_USAGE_CONFIG_UNREADABLE",
+            MmcaiError::InvalidConfig { .. } => "E_USAGE_BAD_CONFIG",
+            MmcaiError::UnknownBackend { .. } => "E_USAGE_UNKNOWN_BACKEND",
+            MmcaiError::InvalidEndpoint { .. } => "E_USAGE_BAD_ENDPOINT",
+            MmcaiError::WorkingDirectoryNotFound(_) => "E_USAGE_NO_WORKING_DIRECTORY",
+            MmcaiError::YggdrasilHelloFailed(_) => "E_AUTH_SERVER_UNREACHABLE",
+            MmcaiError::InvalidMetadata { .. } => "E_AUTH_BAD_METADATA",
+            MmcaiError::ServerUnhealthy { .. } => "E_AUTH_SERVER_UNHEALTHY",
+            MmcaiError::RateLimited { .. } => "E_AUTH_RATE_LIMITED",
+            MmcaiError::YggdrasilAuthFailed { .. } => "E_AUTH_REQUEST_FAILED",
+            MmcaiError::InvalidAuthResponse { .. } => "E_AUTH_BAD_RESPONSE",
+            MmcaiError::InvalidCredentials { .. } => "E_AUTH_BAD_PASSWORD",
+            MmcaiError::CredentialsNotGiven => "E_AUTH_NO_CREDENTIALS",
+            MmcaiError::AccountNotActivated { .. } => "E_AUTH_ACCOUNT_NOT_ACTIVATED",
+            MmcaiError::AccountBlocked { .. } => "E_AUTH_ACCOUNT_BLOCKED",
+            MmcaiError::AccountLocked { .. } => "E_AUTH_ACCOUNT_LOCKED",
+            MmcaiError::LoginRejected { .. } => "E_AUTH_REJECTED",
+            MmcaiError::UnknownProfile { .. } => "E_AUTH_UNKNOWN_PROFILE",
+            MmcaiError::AuthlibInjectorNotFound => "E_INJECTOR_NOT_FOUND",
+            MmcaiError::WriteArgFileFailed { .. } => "E_INJECTOR_ARG_FILE_UNWRITABLE",
+            MmcaiError::WritePrelaunchFailed { .. } => "E_INJECTOR_PRELAUNCH_UNWRITABLE",
+            MmcaiError::JavaExecutableNotFound => "E_GAME_NO_JAVA",
+            MmcaiError::SpawnProcessFailed(_) => "E_GAME_SPAWN_FAILED",
+            MmcaiError::StdinUnavailable => "E_GAME_NO_STDIN",
+            MmcaiError::WriteMinecraftParamsFailed(_) => "E_GAME_PARAMS_UNWRITABLE",
+            MmcaiError::WaitProcessFailed(_) => "E_GAME_WAIT_FAILED",
+            MmcaiError::ReadMinecraftParamsFailed(_) => "E_GAME_PARAMS_UNREADABLE",
+            MmcaiError::InvalidMinecraftParams(_) => "E_GAME_BAD_PARAMS",
+            MmcaiError::ReadParamsFileFailed { .. } => "E_GAME_PARAMS_FILE_UNREADABLE",
+            MmcaiError::ParamsTimedOut { .. } => "E_GAME_PARAMS_TIMED_OUT",
+            MmcaiError::LaunchAborted => "E_GAME_LAUNCH_ABORTED",
+            MmcaiError::GameUnresponsive { .. } => "E_GAME_UNRESPONSIVE",
+            MmcaiError::ReqwestClientBuildFailed(_) => "E_HTTP_CLIENT_FAILED",
+            MmcaiError::InvalidProxy { .. } => "E_HTTP_BAD_PROXY",
+            MmcaiError::ReadCertificateFailed { .. } => "E_HTTP_CERTIFICATE_UNREADABLE",
+            MmcaiError::InvalidCertificate { .. } => "E_HTTP_BAD_CERTIFICATE",
+            MmcaiError::CertificatePinMismatch { .. } => "E_HTTP_PIN_MISMATCH",
+            MmcaiError::CertificatePinUnavailable { .. } => "E_HTTP_PIN_UNAVAILABLE",
+            MmcaiError::OpenTraceFileFailed { .. } => "E_FILES_TRACE_UNWRITABLE",
+            MmcaiError::WriteParamsRecordFailed { .. } => "E_FILES_PARAMS_RECORD_UNWRITABLE",
+            MmcaiError::WriteLaunchDumpFailed { .. } => "E_FILES_LAUNCH_DUMP_UNWRITABLE",
+            MmcaiError::OpenLogFileFailed { .. } => "E_FILES_LOG_UNWRITABLE",
+            MmcaiError::OpenWrapperLogFailed { .. } => "E_FILES_WRAPPER_LOG_UNWRITABLE",
+            MmcaiError::OpenEventsFailed { .. } => "E_FILES_EVENTS_UNWRITABLE",
+            MmcaiError::ConnectJournaldFailed(_) => "E_FILES_JOURNALD_UNAVAILABLE",
+            MmcaiError::OpenEventLogFailed(_) => "E_FILES_EVENT_LOG_UNAVAILABLE",
+            MmcaiError::ReadAccountsFailed { .. } => "E_FILES_ACCOUNTS_UNREADABLE",
+            MmcaiError::InvalidAccounts { .. } => "E_FILES_BAD_ACCOUNTS",
+            MmcaiError::UnknownAccount(_) => "E_FILES_UNKNOWN_ACCOUNT",
+            MmcaiError::AccountWithoutServer(_) => "E_FILES_ACCOUNT_WITHOUT_SERVER",
+            MmcaiError::WriteAccountsFailed { .. } => "E_FILES_ACCOUNTS_UNWRITABLE",
+            MmcaiError::ReadTextureFailed { .. } => "E_TEXTURES_UNREADABLE",
+            MmcaiError::InvalidTexture(_) => "E_TEXTURES_BAD_IMAGE",
+            MmcaiError::TextureRequestFailed(_) => "E_TEXTURES_REQUEST_FAILED",
+            MmcaiError::TextureRejected { .. } => "E_TEXTURES_REJECTED",
+            MmcaiError::TextureForbidden(_) => "E_TEXTURES_FORBIDDEN",
+            MmcaiError::NoSkin(_) => "E_TEXTURES_NO_SKIN",
+            MmcaiError::WriteTextureFailed { .. } => "E_TEXTURES_UNWRITABLE",
+            MmcaiError::UnreadableSkin(_) => "E_TEXTURES_SKIN_UNAVAILABLE",
+            MmcaiError::InvalidSkinName(_) => "E_TEXTURES_BAD_SKIN_NAME",
+            MmcaiError::UnknownSkin(_) => "E_TEXTURES_UNKNOWN_SKIN",
+            MmcaiError::SourceProfileNotFound(_) => "E_TEXTURES_UNKNOWN_SOURCE_PROFILE",
+            MmcaiError::RuntimeBuildFailed(_) => "E_INTERNAL_RUNTIME_FAILED",
+            MmcaiError::Other => "E_INTERNAL_UNKNOWN",
+        }
+    }
+
+    /// What the user can do about the error, where the message doesn't say already, in
+    /// the selected language.
+    pub fn hint(&self) -> Option<String> {
+        let id = match self {
+            MmcaiError::InvalidCredentials { .. } | MmcaiError::CredentialsNotGiven => {
+                "hint-credentials"
+            }
+            MmcaiError::YggdrasilHelloFailed(_) | MmcaiError::ServerUnhealthy { .. } => {
+                "hint-unreachable"
+            }
+            MmcaiError::InvalidMetadata { .. } => "hint-invalid-metadata",
+            MmcaiError::RateLimited { .. } => "hint-rate-limited",
+            MmcaiError::UnknownBackend { .. } => "hint-unknown-backend",
+            MmcaiError::InvalidEndpoint { .. } => "hint-invalid-endpoint",
+            MmcaiError::MissingApiUrl => "hint-missing-api-url",
+            MmcaiError::CertificatePinMismatch { .. } => "hint-pin-mismatch",
+            MmcaiError::InvalidProxy { .. } => "hint-invalid-proxy",
+            MmcaiError::AuthlibInjectorNotFound => "hint-injector-not-found",
+            _ => return None,
+        };
+        Some(tr!(id))
+    }
+
+    /// The stage that failed, named after the exit code ranges.
+    pub fn category(&self) -> &'static str {
+        match self.exit_code() {
+            2..=9 => "usage",
+            10..=29 => "auth",
+            30..=49 => "injector",
+            50..=69 => "game",
+            70..=89 => "http",
+            90..=109 => "files",
+            110..=125 => "textures",
+            _ => "internal",
+        }
+    }
+
+    /// Whether running the wrapper again later may succeed without changing anything.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            MmcaiError::RateLimited { .. } | MmcaiError::GameUnresponsive { .. } => true,
+            MmcaiError::LoginRejected { status_code, .. } => *status_code >= 500,
+            e => e.is_unreachable(),
+        }
+    }
+
+    /// What the server answered, for errors caused by its answer.
+    pub fn server_response(&self) -> Option<&str> {
+        match self {
+            MmcaiError::YggdrasilAuthFailed { response, .. }
+            | MmcaiError::InvalidAuthResponse { response, .. } => Some(response),
+            MmcaiError::InvalidCredentials { message }
+            | MmcaiError::AccountNotActivated { message, .. }
+            | MmcaiError::AccountBlocked { message, .. }
+            | MmcaiError::AccountLocked { message, .. }
+            | MmcaiError::LoginRejected { message, .. }
+            | MmcaiError::TextureRejected { message, .. }
+            | MmcaiError::TextureForbidden(message) => Some(message),
+            _ => None,
+        }
+    }
+
+    /// The message followed by those of the errors that caused it, each after a colon
+    /// instead of the previous one's full stop.
+    pub fn full_message(&self) -> String {
+        let mut message = self.to_string();
+        let mut source = self.source();
+        while let Some(e) = source {
+            message = format!("{}: {}", message.trim_end_matches('.'), e);
+            source = e.source();
+        }
+        message
+    }
+
+    /// The error as printed with `--error-format json`.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "code": self.exit_code(),
+            "error": self.code(),
+            "stage": self.category(),
+            "message": self.full_message(),
+            "hint": self.hint(),
+            "retryable": self.is_retryable(),
+            "server_response": self.server_response(),
+        })
+    }
+
+    /// Whether the error means the server could not be reached at all, as opposed to
+    /// the server answering with something we didn't like.
+    pub fn is_unreachable(&self) -> bool {
+        match self {
+            // an error page instead of the API usually means a proxy or CDN in the way
+            MmcaiError::YggdrasilHelloFailed(_)
+            | MmcaiError::InvalidMetadata { .. }
+            | MmcaiError::ServerUnhealthy { .. } => true,
+            MmcaiError::YggdrasilAuthFailed { source, .. } => !source.is_decode(),
+            _ => false,
+        }
+    }
+}
+
+/// Points to the page the server gave for sorting out an account problem.
+fn see(url: &Option<String>) -> String {
+    match url {
+        Some(url) => format!(" {}", tr!("error-see", url = url)),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes() {
+        assert_eq!(
+            MmcaiError::InvalidArgument("mmcai_rs".into()).exit_code(),
+            2
+        );
+        assert_eq!(MmcaiError::RateLimited { retry_after: 1 }.exit_code(), 11);
+        assert_eq!(MmcaiError::AuthlibInjectorNotFound.exit_code(), 30);
+        assert_eq!(MmcaiError::GameUnresponsive { seconds: 1 }.exit_code(), 58);
+        assert_eq!(MmcaiError::LaunchAborted.exit_code(), 60);
+        assert_eq!(MmcaiError::LaunchAborted.category(), "game");
+        assert_eq!(MmcaiError::Other.exit_code(), 1);
+        assert_eq!(
+            MmcaiError::RateLimited { retry_after: 1 }.category(),
+            "auth"
+        );
+        assert_eq!(MmcaiError::Other.category(), "internal");
+    }
+
+    #[test]
+    fn test_codes() {
+        let errors = [
+            MmcaiError::InvalidCredentials {
+                message: String::new(),
+            },
+            MmcaiError::CredentialsNotGiven,
+            MmcaiError::RateLimited { retry_after: 1 },
+            MmcaiError::UnknownAccount("steve".into()),
+            MmcaiError::TextureForbidden(String::new()),
+            MmcaiError::Other,
+        ];
+        let codes: Vec<_> = errors.iter().map(MmcaiError::code).collect();
+        assert_eq!(codes[0], "E_AUTH_BAD_PASSWORD");
+        for (error, code) in errors.iter().zip(&codes) {
+            let stage = error.category().to_uppercase();
+            assert!(code.starts_with(&format!("E_{}_", stage)), "{}", code);
+        }
+        // errors sharing an exit code still have codes of their own
+        assert_ne!(codes[0], codes[1]);
+        assert_eq!(MmcaiError::Other.hint(), None);
+    }
+
+    /// One error of every variant.
+    fn every_error() -> Vec<MmcaiError> {
+        let io = || IoError::from(std::io::ErrorKind::Other);
+        let reqwest = || reqwest::Client::new().get("not a url").build().unwrap_err();
+        let path = || PathBuf::from("file");
+        let text = String::new;
+        vec![
+            MmcaiError::InvalidArgument(text()),
+            MmcaiError::UnknownOption(text()),
+            MmcaiError::MissingOptionValue(text()),
+            MmcaiError::InvalidOptionValue(text(), text()),
+            MmcaiError::CannotRunDirectly,
+            MmcaiError::AuthlibInjectorNotFound,
+            MmcaiError::YggdrasilHelloFailed(reqwest()),
+            MmcaiError::InvalidMetadata {
+                api_url: text(),
+                reason: text(),
+                excerpt: text(),
+            },
+            MmcaiError::ServerUnhealthy {
+                host: text(),
+                problem: text(),
+            },
+            MmcaiError::RateLimited { retry_after: 1 },
+            MmcaiError::YggdrasilAuthFailed {
+                source: reqwest(),
+                response: text(),
+            },
+            MmcaiError::InvalidAuthResponse {
+                message: text(),
+                response: text(),
+            },
+            MmcaiError::InvalidCredentials { message: text() },
+            MmcaiError::CredentialsNotGiven,
+            MmcaiError::AccountNotActivated {
+                message: text(),
+                url: None,
+            },
+            MmcaiError::AccountBlocked {
+                message: text(),
+                url: None,
+            },
+            MmcaiError::AccountLocked {
+                message: text(),
+                url: None,
+            },
+            MmcaiError::LoginRejected {
+                status_code: 403,
+                message: text(),
+            },
+            MmcaiError::UnknownProfile {
+                name: text(),
+                available: text(),
+            },
+            MmcaiError::ReqwestClientBuildFailed(reqwest()),
+            MmcaiError::ReadConfigFailed {
+                path: path(),
+                source: io(),
+            },
+            MmcaiError::InvalidConfig {
+                path: path(),
+                message: text(),
+            },
+            MmcaiError::UnknownBackend {
+                name: text(),
+                available: text(),
+            },
+            MmcaiError::InvalidEndpoint {
+                endpoint: text(),
+                message: text(),
+            },
+            MmcaiError::InvalidProxy {
+                url: text(),
+                source: reqwest(),
+            },
+            MmcaiError::ReadCertificateFailed {
+                path: path(),
+                source: io(),
+            },
+            MmcaiError::InvalidCertificate {
+                path: path(),
+                source: None,
+            },
+            MmcaiError::CertificatePinMismatch { host: text() },
+            MmcaiError::CertificatePinUnavailable { host: text() },
+            MmcaiError::OpenTraceFileFailed {
+                path: path(),
+                source: io(),
+            },
+            MmcaiError::RuntimeBuildFailed(io()),
+            MmcaiError::ReadMinecraftParamsFailed(io()),
+            MmcaiError::InvalidMinecraftParams(text()),
+            MmcaiError::ParamsTimedOut { seconds: 1 },
+            MmcaiError::LaunchAborted,
+            MmcaiError::WriteMinecraftParamsFailed(io()),
+            MmcaiError::ReadParamsFileFailed {
+                path: path(),
+                source: io(),
+            },
+            MmcaiError::WriteParamsRecordFailed {
+                path: path(),
+                source: io(),
+            },
+            MmcaiError::WriteLaunchDumpFailed {
+                path: path(),
+                source: io(),
+            },
+            MmcaiError::OpenLogFileFailed {
+                path: path(),
+                source: io(),
+            },
+            MmcaiError::OpenEventsFailed {
+                target: text(),
+                source: io(),
+            },
+            MmcaiError::ConnectJournaldFailed(io()),
+            MmcaiError::OpenEventLogFailed(io()),
+            MmcaiError::ReadAccountsFailed {
+                path: path(),
+                source: io(),
+            },
+            MmcaiError::InvalidAccounts {
+                path: path(),
+                message: text(),
+            },
+            MmcaiError::UnknownAccount(text()),
+            MmcaiError::CommandUsage(text()),
+            MmcaiError::MissingApiUrl,
+            MmcaiError::AccountWithoutServer(text()),
+            MmcaiError::PasswordsDiffer,
+            MmcaiError::ReadTextureFailed {
+                path: path(),
+                source: io(),
+            },
+            MmcaiError::InvalidTexture(path()),
+            MmcaiError::TextureRequestFailed(reqwest()),
+            MmcaiError::TextureRejected {
+                status_code: 400,
+                message: text(),
+            },
+            MmcaiError::NoSkin(text()),
+            MmcaiError::UnreadableSkin(text()),
+            MmcaiError::InvalidSkinName(text()),
+            MmcaiError::UnknownSkin(text()),
+            MmcaiError::TextureForbidden(text()),
+            MmcaiError::SourceProfileNotFound(text()),
+            MmcaiError::WriteTextureFailed {
+                path: path(),
+                source: io(),
+            },
+            MmcaiError::WriteAccountsFailed {
+                path: path(),
+                source: io(),
+            },
+            MmcaiError::OpenWrapperLogFailed {
+                path: path(),
+                source: io(),
+            },
+            MmcaiError::WriteArgFileFailed {
+                path: path(),
+                source: io(),
+            },
+            MmcaiError::WritePrelaunchFailed {
+                path: path(),
+                source: io(),
+            },
+            MmcaiError::WorkingDirectoryNotFound(path()),
+            MmcaiError::SpawnProcessFailed(io()),
+            MmcaiError::WaitProcessFailed(io()),
+            MmcaiError::GameUnresponsive { seconds: 1 },
+            MmcaiError::StdinUnavailable,
+            MmcaiError::JavaExecutableNotFound,
+            MmcaiError::Other,
+        ]
+    }
+
+    #[test]
+    fn test_codes_distinct() {
+        let errors = every_error();
+        let variants: std::collections::HashSet<_> =
+            errors.iter().map(std::mem::discriminant).collect();
+        assert_eq!(variants.len(), errors.len(), "a variant is listed twice");
+        let mut codes = std::collections::HashSet::new();
+        for error in &errors {
+            assert!(codes.insert(error.code()), "{} is reused", error.code());
+        }
+        // only these exit codes deliberately stand for several errors
+        let shared = [1, 2, 7, 16, 98];
+        let mut exit_codes = std::collections::HashSet::new();
+        for error in &errors {
+            let code = error.exit_code();
+            assert!(
+                exit_codes.insert(code) || shared.contains(&code),
+                "exit code {} is reused by {}",
+                code,
+                error.code()
+            );
+        }
+    }
+
+    #[test]
+    fn test_messages_translated() {
+        for error in every_error() {
+            let message = error.to_string();
+            assert!(!message.starts_with("error-"), "no message for {}", message);
+            assert!(
+                !message.contains("{ $"),
+                "unfilled placeable in {}",
+                message
+            );
+        }
+    }
+
+    #[test]
+    fn test_to_json() {
+        let error = MmcaiError::InvalidCredentials {
+            message: "Invalid password".into(),
+        };
+        assert_eq!(
+            error.to_json(),
+            json!({
+                "code": 16,
+                "error": "E_AUTH_BAD_PASSWORD",
+                "stage": "auth",
+                "message": "Wrong username or password. The server says: Invalid password",
+                "hint": "Check the username and password in the launcher's account settings.",
+                "retryable": false,
+                "server_response": "Invalid password",
+            })
+        );
+
+        let error = MmcaiError::ReadConfigFailed {
+            path: "mmcai_rs.toml".into(),
+            source: std::io::ErrorKind::NotFound.into(),
+        };
+        let json = error.to_json();
+        assert_eq!(json["stage"], "usage");
+        assert!(json["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("Cannot read config file \"mmcai_rs.toml\": "));
+        assert_eq!(json["server_response"], Value::Null);
+    }
+}
@@ -0,0 +1,127 @@
+use std::{ffi::c_void, fmt, io, iter, ptr};
+
+use tracing::{
+    field::{Field, Visit},
+    Event, Level, Subscriber,
+};
+use tracing_subscriber::{layer::Context, Layer};
+
+type Handle = *mut c_void;
+
+const SOURCE_NAME: &str = "mmcai_rs";
+const EVENTLOG_ERROR_TYPE: u16 = 0x0001;
+const EVENTLOG_WARNING_TYPE: u16 = 0x0002;
+
+#[link(name = "advapi32")]
+extern "system" {
+    fn RegisterEventSourceW(server_name: *const u16, source_name: *const u16) -> Handle;
+    fn DeregisterEventSource(event_log: Handle) -> i32;
+    fn ReportEventW(
+        event_log: Handle,
+        kind: u16,
+        category: u16,
+        event_id: u32,
+        user_sid: *mut c_void,
+        string_count: u16,
+        data_size: u32,
+        strings: *const *const u16,
+        data: *mut c_void,
+    ) -> i32;
+}
+
+/// Writes warnings and errors to the Application log under the `mmcai_rs` source.
+///
+/// The source is not registered with a message file (that needs administrator rights),
+/// so Event Viewer prefixes the text with a note that the description is missing.
+pub struct EventLog {
+    handle: Handle,
+}
+
+// the handle is only used with ReportEventW, which is thread-safe
+unsafe impl Send for EventLog {}
+unsafe impl Sync for EventLog {}
+
+impl EventLog {
+    pub fn open() -> io::Result<EventLog> {
+        let source_name = wide(SOURCE_NAME);
+        let handle = unsafe { RegisterEventSourceW(ptr::null(), source_name.as_ptr()) };
+        if handle.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(EventLog { handle })
+    }
+
+    /// `event_id` lets admins filter; fatal errors use the wrapper's exit code.
+    pub fn send(&self, level: Level, event_id: u32, message: &str) {
+        let kind = match level {
+            Level::ERROR => EVENTLOG_ERROR_TYPE,
+            Level::WARN => EVENTLOG_WARNING_TYPE,
+            _ => return,
+        };
+        let message = wide(message);
+        let strings = [message.as_ptr()];
+        // logging is best effort, it must never break the launch
+        unsafe {
+            ReportEventW(
+                self.handle,
+                kind,
+                0,
+                event_id,
+                ptr::null_mut(),
+                1,
+                0,
+                strings.as_ptr(),
+                ptr::null_mut(),
+            )
+        };
+    }
+}
+
+impl<S: Subscriber> Layer<S> for EventLog {
+    fn on_event(&self, event: &Event<'_>, _context: Context<'_, S>) {
+        let mut fields = EventFields::default();
+        event.record(&mut fields);
+        let event_id = fields.exit_code.unwrap_or(1);
+        self.send(*event.metadata().level(), event_id, &fields.message);
+    }
+}
+
+/// The message with the other fields after it, and the exit code as the event id.
+#[derive(Default)]
+struct EventFields {
+    message: String,
+    exit_code: Option<u32>,
+}
+
+impl Visit for EventFields {
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        match field.name() {
+            "exit_code" => self.exit_code = u32::try_from(value).ok(),
+            _ => self.record_debug(field, &value),
+        }
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        match field.name() {
+            "exit_code" => self.exit_code = u32::try_from(value).ok(),
+            _ => self.record_debug(field, &value),
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            "message" => self.message.insert_str(0, &format!("{:?}", value)),
+            name => self.message.push_str(&format!(" {}={:?}", name, value)),
+        }
+    }
+}
+
+impl Drop for EventLog {
+    fn drop(&mut self) {
+        unsafe { DeregisterEventSource(self.handle) };
+    }
+}
+
+fn wide(value: &str) -> Vec<u16> {
+    value.encode_utf16().chain(iter::once(0)).collect()
+}
@@ -0,0 +1,215 @@
+use std::{
+    collections::BTreeMap,
+    fs::{File, OpenOptions},
+    io::Write,
+    path::Path,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::cli::Options;
+use crate::errors::MmcaiError;
+use crate::Result;
+
+/// A progress event for launcher frontends, written as one JSON object per line.
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+    /// `account` is the stored account's name, or the username.
+    AuthStarted {
+        account: &'a str,
+        api_url: &'a str,
+    },
+    /// `full_skin_url` is the server's pre-rendered image of the skin, for frontends
+    /// showing an avatar; null when the server has none or the session was reused.
+    AuthOk {
+        name: &'a str,
+        uuid: &'a str,
+        full_skin_url: Option<&'a str>,
+    },
+    InjectorFound {
+        path: &'a Path,
+    },
+    Launching {
+        attempt: u32,
+    },
+    /// `code` is null when the game was killed by a signal.
+    ChildExit {
+        code: Option<i32>,
+    },
+    /// How long each stage of the launch took, see `timing::Stage`.
+    Timings {
+        stages_ms: BTreeMap<&'static str, u64>,
+    },
+}
+
+/// Where events go (`--events-fd` / `--events-file`). Without either option, events are
+/// dropped.
+#[derive(Default)]
+pub struct EventSink {
+    file: Option<Mutex<File>>,
+}
+
+impl EventSink {
+    pub fn open(options: &Options) -> Result<EventSink> {
+        let file = match (&options.events_file, options.events_fd) {
+            (Some(path), _) => Some(open_file(path)?),
+            (None, Some(fd)) => Some(from_fd(fd)?),
+            (None, None) => None,
+        };
+        Ok(EventSink {
+            file: file.map(Mutex::new),
+        })
+    }
+
+    pub fn emit(&self, event: Event) {
+        let Some(file) = &self.file else {
+            return;
+        };
+        let line = format(&event, SystemTime::now());
+        if let Ok(mut file) = file.lock() {
+            // a frontend that went away must not take the game down with it
+            let _ = writeln!(file, "{}", line).and_then(|_| file.flush());
+        }
+    }
+}
+
+fn format(event: &Event, time: SystemTime) -> String {
+    let mut value = serde_json::to_value(event).expect("events always serialize");
+    if let Value::Object(map) = &mut value {
+        let time = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        map.insert("time".into(), (time.as_millis() as u64).into());
+    }
+    value.to_string()
+}
+
+fn open_file(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|source| MmcaiError::OpenEventsFailed {
+            target: format!("{:?}", path),
+            source,
+        })
+}
+
+/// A duplicate of the descriptor: it belongs to the launcher, which may close it or
+/// pass it on itself, so the sink must not close the original.
+#[cfg(unix)]
+fn from_fd(fd: i32) -> Result<File> {
+    use std::os::fd::BorrowedFd;
+
+    // fcntl fails on descriptors the launcher did not actually pass down
+    if unsafe { libc::fcntl(fd, libc::F_GETFD) } == -1 {
+        return Err(MmcaiError::OpenEventsFailed {
+            target: format!("file descriptor {}", fd),
+            source: std::io::Error::last_os_error(),
+        });
+    }
+    unsafe { BorrowedFd::borrow_raw(fd) }
+        .try_clone_to_owned()
+        .map(File::from)
+        .map_err(|source| MmcaiError::OpenEventsFailed {
+            target: format!("file descriptor {}", fd),
+            source,
+        })
+}
+
+/// On Windows the number is an inherited handle value, duplicated like on Unix.
+#[cfg(windows)]
+fn from_fd(fd: i32) -> Result<File> {
+    use std::os::windows::io::{BorrowedHandle, RawHandle};
+
+    unsafe { BorrowedHandle::borrow_raw(fd as isize as RawHandle) }
+        .try_clone_to_owned()
+        .map(File::from)
+        .map_err(|source| MmcaiError::OpenEventsFailed {
+            target: format!("handle {}", fd),
+            source,
+        })
+}
+
+#[cfg(not(any(unix, windows)))]
+fn from_fd(fd: i32) -> Result<File> {
+    Err(MmcaiError::OpenEventsFailed {
+        target: format!("file descriptor {}", fd),
+        source: std::io::ErrorKind::Unsupported.into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        let time = UNIX_EPOCH + Duration::from_millis(1500);
+        assert_eq!(
+            format(
+                &Event::AuthOk {
+                    name: "Steve",
+                    uuid: "abc",
+                    full_skin_url: Some("https://skins.example.com/full/Steve.png"),
+                },
+                time
+            ),
+            r#"{"event":"auth_ok","full_skin_url":"https://skins.example.com/full/Steve.png","name":"Steve","time":1500,"uuid":"abc"}"#
+        );
+        assert_eq!(
+            format(&Event::ChildExit { code: None }, time),
+            r#"{"code":null,"event":"child_exit","time":1500}"#
+        );
+    }
+
+    #[test]
+    fn test_emit_to_file() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let path = dir.path().join("events.ndjson");
+        let options = Options {
+            events_file: Some(path.clone()),
+            ..Default::default()
+        };
+        let events = EventSink::open(&options).unwrap();
+        events.emit(Event::AuthStarted {
+            account: "alt",
+            api_url: "https://x",
+        });
+        events.emit(Event::Launching { attempt: 1 });
+
+        let written = std::fs::read_to_string(path).unwrap();
+        let lines: Vec<Value> = written
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["event"], "auth_started");
+        assert_eq!(lines[1]["attempt"], 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_from_fd_leaves_the_original_open() {
+        use std::io::Read;
+        use std::os::fd::AsRawFd;
+
+        let dir = assert_fs::TempDir::new().unwrap();
+        let path = dir.path().join("events.ndjson");
+        let mut launcher_end = open_file(&path).unwrap();
+        drop(from_fd(launcher_end.as_raw_fd()).unwrap());
+        // a double close would have closed the launcher's descriptor too
+        writeln!(launcher_end, "still open").unwrap();
+        let mut written = String::new();
+        File::open(&path)
+            .unwrap()
+            .read_to_string(&mut written)
+            .unwrap();
+        assert_eq!(written, "still open\n");
+        assert!(from_fd(-1).is_err());
+    }
+}
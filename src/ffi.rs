@@ -0,0 +1,218 @@
+use std::{
+    cell::RefCell,
+    ffi::{c_char, c_int, CStr, CString},
+    panic::{self, AssertUnwindSafe},
+    ptr,
+};
+
+use serde_json::json;
+
+use crate::client::YggdrasilClient;
+use crate::config::Config;
+use crate::errors::MmcaiError;
+use crate::params::{Identity, LaunchSpec};
+use crate::Result;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<(CString, CString)>> = const { RefCell::new(None) };
+}
+
+/// Signs in to the server at `api_url` and stores the session as JSON
+/// (`accessToken`, `clientToken`, `expiredDate`, `profile.id`, `profile.name`) in
+/// `*session_json`, to be freed with `mmcai_string_free`. Returns 0, or the exit code
+/// of the error, which `mmcai_last_error` then describes.
+///
+/// The patcher's config file is not read: retries, the proxy and certificate pins are
+/// the defaults of `Config::default`.
+///
+/// # Safety
+///
+/// The strings must be valid, NUL-terminated and live for the call; `session_json` must
+/// point to writable memory for a pointer.
+#[no_mangle]
+pub unsafe extern "C" fn mmcai_authenticate(
+    api_url: *const c_char,
+    username: *const c_char,
+    password: *const c_char,
+    session_json: *mut *mut c_char,
+) -> c_int {
+    let result = guard(|| {
+        let client = YggdrasilClient::builder()
+            .api_url(read(api_url, "api_url")?)
+            .build()?;
+        let (username, password) = (read(username, "username")?, read(password, "password")?);
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(MmcaiError::RuntimeBuildFailed)?;
+        let session = runtime.block_on(client.authenticate(username, password))?;
+        Ok(json!({
+            "accessToken": session.access_token,
+            "clientToken": session.client_token,
+            "expiredDate": session.expired_date,
+            "profile": {
+                "id": session.selected_profile.id,
+                "name": session.selected_profile.name,
+            },
+        })
+        .to_string())
+    });
+    finish(result, session_json)
+}
+
+/// Patches a param block (the launcher's stdin, lines up to `launch`) for the account,
+/// like the patcher does, with the `clientToken` of its session for `--clientId`, and stores the patched block in `*patched`, to be freed with
+/// `mmcai_string_free`. Returns 0 or an exit code, like `mmcai_authenticate`.
+///
+/// # Safety
+///
+/// As for `mmcai_authenticate`.
+#[no_mangle]
+pub unsafe extern "C" fn mmcai_patch_params(
+    params: *const c_char,
+    username: *const c_char,
+    uuid: *const c_char,
+    access_token: *const c_char,
+    client_token: *const c_char,
+    patched: *mut *mut c_char,
+) -> c_int {
+    let result = guard(|| {
+        let mut spec = LaunchSpec::parse(read(params, "params")?);
+        let identity = Identity {
+            name: read(username, "username")?,
+            uuid: read(uuid, "uuid")?,
+            access_token: read(access_token, "access_token")?,
+        };
+        let client_token = read(client_token, "client_token")?;
+        spec.patch(&identity, client_token, &Config::default())?;
+        Ok(spec.serialize())
+    });
+    finish(result, patched)
+}
+
+/// The message of the last error on this thread, or NULL. The string belongs to the
+/// library and stays valid until the next call on the thread.
+#[no_mangle]
+pub extern "C" fn mmcai_last_error() -> *const c_char {
+    LAST_ERROR.with_borrow(|error| {
+        error
+            .as_ref()
+            .map_or(ptr::null(), |(text, _)| text.as_ptr())
+    })
+}
+
+/// The last error on this thread as JSON, like `--error-format json` prints it, or NULL.
+#[no_mangle]
+pub extern "C" fn mmcai_last_error_json() -> *const c_char {
+    LAST_ERROR.with_borrow(|error| {
+        error
+            .as_ref()
+            .map_or(ptr::null(), |(_, json)| json.as_ptr())
+    })
+}
+
+/// Frees a string the library returned through an out parameter.
+///
+/// # Safety
+///
+/// `string` must be NULL or come from the library, and not be freed twice.
+#[no_mangle]
+pub unsafe extern "C" fn mmcai_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+/// Runs the body of a call, turning a panic into an error: unwinding into C is undefined
+/// behaviour. Only works when the library is built with `panic = "unwind"`.
+fn guard(body: impl FnOnce() -> Result<String>) -> Result<String> {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or(Err(MmcaiError::Other))
+}
+
+unsafe fn read<'a>(string: *const c_char, name: &str) -> Result<&'a str> {
+    if string.is_null() {
+        return Err(MmcaiError::InvalidOptionValue(name.into(), "NULL".into()));
+    }
+    CStr::from_ptr(string)
+        .to_str()
+        .map_err(|_| MmcaiError::InvalidOptionValue(name.into(), "not UTF-8".into()))
+}
+
+unsafe fn finish(result: Result<String>, out: *mut *mut c_char) -> c_int {
+    LAST_ERROR.set(None);
+    match result {
+        Ok(string) => {
+            if !out.is_null() {
+                // made from C strings and JSON, so there is no NUL in it
+                *out = CString::new(string).unwrap_or_default().into_raw();
+            }
+            0
+        }
+        Err(e) => {
+            let code = e.exit_code();
+            let text = CString::new(e.full_message().replace('\0', "")).unwrap_or_default();
+            let json = CString::new(e.to_json().to_string()).unwrap_or_default();
+            LAST_ERROR.set(Some((text, json)));
+            code
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patch_params() {
+        let params =
+            CString::new("param --username\nparam Player\nparam --clientId\nparam old\nlaunch\n")
+                .unwrap();
+        let (name, uuid, token, client_token) = (
+            CString::new("Steve").unwrap(),
+            CString::new("0f8d").unwrap(),
+            CString::new("t").unwrap(),
+            CString::new("c0ffee").unwrap(),
+        );
+        let mut patched = ptr::null_mut();
+        let code = unsafe {
+            mmcai_patch_params(
+                params.as_ptr(),
+                name.as_ptr(),
+                uuid.as_ptr(),
+                token.as_ptr(),
+                client_token.as_ptr(),
+                &mut patched,
+            )
+        };
+        assert_eq!(code, 0);
+        let text = unsafe { CStr::from_ptr(patched) }
+            .to_str()
+            .unwrap()
+            .to_owned();
+        unsafe { mmcai_string_free(patched) };
+        assert_eq!(
+            text,
+            "param --username\nparam Steve\nparam --clientId\nparam c0ffee\nlaunch\n"
+        );
+
+        let code = unsafe {
+            mmcai_patch_params(
+                ptr::null(),
+                name.as_ptr(),
+                uuid.as_ptr(),
+                token.as_ptr(),
+                client_token.as_ptr(),
+                &mut patched,
+            )
+        };
+        assert_eq!(code, 9);
+        assert!(!mmcai_last_error().is_null());
+    }
+
+    #[test]
+    fn test_guard_panic() {
+        let result = guard(|| panic!("bug"));
+        assert!(matches!(result, Err(MmcaiError::Other)));
+        assert_eq!(guard(|| Ok("fine".into())).unwrap(), "fine");
+    }
+}
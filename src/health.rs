@@ -0,0 +1,158 @@
+use std::{error::Error as StdError, fmt, io};
+
+use reqwest::{header::HeaderMap, StatusCode, Url};
+
+use crate::errors::MmcaiError;
+use crate::http::HttpClient;
+use crate::Result;
+
+/// Why the auth server looks unhealthy, in terms a player can act on.
+#[derive(Debug, PartialEq)]
+pub enum Problem {
+    Dns,
+    Tls,
+    ConnectionRefused,
+    Timeout,
+    CloudflareChallenge,
+    /// Cloudflare answered, but the server behind it didn't (HTTP 520-527).
+    OriginDown(u16),
+    ServerError(u16),
+    Other(String),
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::Dns => write!(f, "its address cannot be resolved, check your internet connection and DNS"),
+            Problem::Tls => write!(f, "the secure connection failed, the server's certificate may be invalid or something is intercepting the connection"),
+            Problem::ConnectionRefused => write!(f, "it refuses connections, it is probably down"),
+            Problem::Timeout => write!(f, "it does not respond, it or your connection may be down"),
+            Problem::CloudflareChallenge => write!(f, "Cloudflare is asking for a browser challenge, which the patcher cannot solve"),
+            Problem::OriginDown(code) => write!(f, "it appears to be down (HTTP {} from Cloudflare)", code),
+            Problem::ServerError(code) => write!(f, "it appears to be down (HTTP {})", code),
+            Problem::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+/// Hits the API root once and turns anything wrong into a readable [`MmcaiError::ServerUnhealthy`].
+pub async fn check(http: &HttpClient, api_url: &str) -> Result<()> {
+    let unhealthy = |problem: Problem| MmcaiError::ServerUnhealthy {
+        host: Url::parse(api_url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+            .unwrap_or_else(|| api_url.to_string()),
+        problem: problem.to_string(),
+    };
+
+    let response = http
+        .send(|client| client.get(api_url))
+        .await
+        .map_err(|e| unhealthy(classify_error(&e)))?;
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body = response.text().await.unwrap_or_default();
+
+    match classify_response(status, &headers, &body) {
+        Some(problem) => Err(unhealthy(problem)),
+        None => Ok(()),
+    }
+}
+
+pub fn classify_error(error: &reqwest::Error) -> Problem {
+    if error.is_timeout() {
+        return Problem::Timeout;
+    }
+    let mut source: Option<&dyn StdError> = Some(error);
+    while let Some(e) = source {
+        if let Some(io_error) = e.downcast_ref::<io::Error>() {
+            match io_error.kind() {
+                io::ErrorKind::ConnectionRefused => return Problem::ConnectionRefused,
+                io::ErrorKind::TimedOut => return Problem::Timeout,
+                _ => {}
+            }
+        }
+        let message = e.to_string().to_lowercase();
+        if message.contains("dns error") || message.contains("failed to lookup address") {
+            return Problem::Dns;
+        }
+        if ["certificate", "tls", "ssl", "handshake"]
+            .iter()
+            .any(|needle| message.contains(needle))
+        {
+            return Problem::Tls;
+        }
+        source = e.source();
+    }
+    Problem::Other(error.to_string())
+}
+
+fn classify_response(status: StatusCode, headers: &HeaderMap, body: &str) -> Option<Problem> {
+    let from_cloudflare = headers
+        .get("server")
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|server| server.eq_ignore_ascii_case("cloudflare"));
+    let challenged = headers
+        .get("cf-mitigated")
+        .is_some_and(|value| value == "challenge")
+        || (from_cloudflare && body.contains("challenge-platform"));
+
+    match status.as_u16() {
+        _ if challenged => Some(Problem::CloudflareChallenge),
+        code @ 520..=527 if from_cloudflare => Some(Problem::OriginDown(code)),
+        code @ 500..=599 => Some(Problem::ServerError(code)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    #[test]
+    fn test_classify_response() {
+        assert_eq!(classify_response(StatusCode::OK, &headers(&[]), "{}"), None);
+        assert_eq!(
+            classify_response(StatusCode::NOT_FOUND, &headers(&[]), ""),
+            None
+        );
+        assert_eq!(
+            classify_response(StatusCode::BAD_GATEWAY, &headers(&[]), ""),
+            Some(Problem::ServerError(502))
+        );
+        assert_eq!(
+            classify_response(
+                StatusCode::from_u16(521).unwrap(),
+                &headers(&[("server", "cloudflare")]),
+                ""
+            ),
+            Some(Problem::OriginDown(521))
+        );
+        assert_eq!(
+            classify_response(
+                StatusCode::FORBIDDEN,
+                &headers(&[("server", "cloudflare"), ("cf-mitigated", "challenge")]),
+                "<html>Just a moment...</html>"
+            ),
+            Some(Problem::CloudflareChallenge)
+        );
+    }
+
+    #[test]
+    fn test_problem_display() {
+        assert_eq!(
+            Problem::OriginDown(521).to_string(),
+            "it appears to be down (HTTP 521 from Cloudflare)"
+        );
+    }
+}
@@ -0,0 +1,216 @@
+use std::{fs, path::Path, sync::Arc, time::Duration};
+
+use reqwest::{Certificate, Client, ClientBuilder, Proxy, RequestBuilder, Response};
+
+use tracing::warn;
+
+use crate::config::{Config, HttpConfig};
+use crate::errors::MmcaiError;
+use crate::i18n::tr;
+use crate::pinning;
+use crate::retry::RetryPolicy;
+use crate::trace::HttpTrace;
+use crate::Result;
+
+/// The client, retry policy and optional trace shared by every request the wrapper
+/// makes to the auth server. Clones share the connection pool and the trace.
+#[derive(Clone)]
+pub struct HttpClient {
+    client: Client,
+    /// Like `client`, without the certificate pins, see [`HttpClient::public`].
+    public: Client,
+    retry: RetryPolicy,
+    trace: Option<Arc<HttpTrace>>,
+}
+
+impl HttpClient {
+    pub fn new(config: &Config, trace: Option<HttpTrace>) -> Result<HttpClient> {
+        let (client, public) = build_clients(&config.http, None)?;
+        Ok(HttpClient {
+            client,
+            public,
+            retry: RetryPolicy::from(&config.retry),
+            trace: trace.map(Arc::new),
+        })
+    }
+
+    /// A client sending `user_agent` instead of reqwest's by default.
+    #[cfg(feature = "async")]
+    pub fn with_user_agent(config: &Config, user_agent: &str) -> Result<HttpClient> {
+        let (client, public) = build_clients(&config.http, Some(user_agent))?;
+        Ok(HttpClient {
+            client,
+            public,
+            retry: RetryPolicy::from(&config.retry),
+            trace: None,
+        })
+    }
+
+    /// A client for public downloads that carry no credentials, like textures from the
+    /// server's skin domains or another service, which don't present the auth server's
+    /// pinned certificate.
+    pub fn public(&self) -> HttpClient {
+        HttpClient {
+            client: self.public.clone(),
+            ..self.clone()
+        }
+    }
+
+    /// Sends the request built by `build_request`, retrying according to the retry policy.
+    pub async fn send<F>(&self, build_request: F) -> reqwest::Result<Response>
+    where
+        F: Fn(&Client) -> RequestBuilder,
+    {
+        self.retry
+            .send(|| build_request(&self.client), self.trace.as_deref())
+            .await
+    }
+
+    /// Sends the request once, for probes that must not be slowed down by retries.
+    pub async fn send_once<F>(&self, build_request: F) -> reqwest::Result<Response>
+    where
+        F: Fn(&Client) -> RequestBuilder,
+    {
+        let request = build_request(&self.client);
+        match &self.trace {
+            Some(trace) => trace.send(request).await,
+            None => request.send().await,
+        }
+    }
+}
+
+/// Builds the client for the auth server and the one for public downloads, which are the
+/// same unless certificates are pinned.
+fn build_clients(config: &HttpConfig, user_agent: Option<&str>) -> Result<(Client, Client)> {
+    if !pinning::is_enabled(config) {
+        let client = build_client(config, user_agent)?;
+        return Ok((client.clone(), client));
+    }
+    let pinned = base_builder(config, user_agent)?
+        .use_preconfigured_tls(pinning::tls_config(config)?)
+        .https_only(true)
+        .build()
+        .map_err(MmcaiError::ReqwestClientBuildFailed)?;
+    let public = HttpConfig {
+        pinned_public_keys: Vec::new(),
+        pinned_certificates: Vec::new(),
+        ..config.clone()
+    };
+    Ok((pinned, build_client(&public, user_agent)?))
+}
+
+fn build_client(config: &HttpConfig, user_agent: Option<&str>) -> Result<Client> {
+    let mut builder = base_builder(config, user_agent)?;
+
+    for path in &config.ca_certificates {
+        for certificate in load_certificates(path)? {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    if config.danger_accept_invalid_certs {
+        warn!("{}", tr!("tls-insecure"));
+        warn!("{}", tr!("tls-insecure-warning"));
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+
+    builder
+        .build()
+        .map_err(MmcaiError::ReqwestClientBuildFailed)
+}
+
+/// The settings shared by pinning and ordinary clients.
+fn base_builder(config: &HttpConfig, user_agent: Option<&str>) -> Result<ClientBuilder> {
+    let mut builder = Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .connect_timeout(Duration::from_secs(config.connect_timeout))
+        .timeout(Duration::from_secs(config.request_timeout));
+
+    if let Some(user_agent) = user_agent {
+        builder = builder.user_agent(user_agent);
+    }
+
+    // without an explicit proxy reqwest falls back to the proxy environment variables
+    if let Some(proxy) = build_proxy(config)? {
+        builder = builder.proxy(proxy);
+    }
+    Ok(builder)
+}
+
+fn build_proxy(config: &HttpConfig) -> Result<Option<Proxy>> {
+    let Some(url) = &config.proxy else {
+        return Ok(None);
+    };
+    let mut proxy = Proxy::all(url).map_err(|source| MmcaiError::InvalidProxy {
+        url: url.clone(),
+        source,
+    })?;
+    if let Some(username) = &config.proxy_username {
+        proxy = proxy.basic_auth(username, config.proxy_password.as_deref().unwrap_or(""));
+    }
+    Ok(Some(proxy))
+}
+
+fn load_certificates(path: &Path) -> Result<Vec<Certificate>> {
+    let pem = fs::read(path).map_err(|source| MmcaiError::ReadCertificateFailed {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let certificates =
+        Certificate::from_pem_bundle(&pem).map_err(|source| MmcaiError::InvalidCertificate {
+            path: path.to_path_buf(),
+            source: Some(source),
+        })?;
+    if certificates.is_empty() {
+        return Err(MmcaiError::InvalidCertificate {
+            path: path.to_path_buf(),
+            source: None,
+        });
+    }
+    Ok(certificates)
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_fs::prelude::{FileWriteStr, PathChild};
+
+    use super::*;
+
+    #[test]
+    fn test_build_proxy() {
+        let mut config = HttpConfig::default();
+        assert!(build_proxy(&config).unwrap().is_none());
+
+        config.proxy = Some("socks5://127.0.0.1:1080".to_string());
+        config.proxy_username = Some("student".to_string());
+        config.proxy_password = Some("hunter2".to_string());
+        assert!(build_proxy(&config).unwrap().is_some());
+        assert!(build_client(&config, None).is_ok());
+
+        config.proxy = Some("not a proxy".to_string());
+        assert!(matches!(
+            build_proxy(&config),
+            Err(MmcaiError::InvalidProxy { .. })
+        ));
+    }
+
+    #[test]
+    fn test_load_certificates() {
+        let temp_dir = assert_fs::TempDir::new().unwrap();
+        let missing = temp_dir.child("missing.pem");
+        assert!(matches!(
+            load_certificates(missing.path()),
+            Err(MmcaiError::ReadCertificateFailed { .. })
+        ));
+
+        let not_pem = temp_dir.child("not.pem");
+        not_pem
+            .write_str("<html>definitely not a certificate</html>")
+            .unwrap();
+        assert!(matches!(
+            load_certificates(not_pem.path()),
+            Err(MmcaiError::InvalidCertificate { .. })
+        ));
+        temp_dir.close().unwrap();
+    }
+}
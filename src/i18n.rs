@@ -0,0 +1,212 @@
+use std::{
+    env,
+    fmt::Display,
+    sync::{LazyLock, OnceLock},
+};
+
+use fluent_bundle::{concurrent::FluentBundle, FluentArgs, FluentResource, FluentValue};
+
+/// Translates a message from `locales/*.ftl`: `tr!("auth-success", name = playername)`.
+macro_rules! tr {
+    ($id:expr) => {
+        $crate::i18n::translate($id, &[])
+    };
+    ($id:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::translate(
+            $id,
+            &[$((stringify!($name), &$value as &dyn ::std::fmt::Display)),+],
+        )
+    };
+}
+pub(crate) use tr;
+
+const DEFAULT_LANGUAGE: &str = "en";
+
+/// The locales built into the binary, in Fluent syntax.
+const LOCALES: &[(&str, &str)] = &[
+    ("en", include_str!("../locales/en.ftl")),
+    ("ru", include_str!("../locales/ru.ftl")),
+];
+
+type Bundle = FluentBundle<FluentResource>;
+
+static FALLBACK: LazyLock<Bundle> =
+    LazyLock::new(|| bundle(DEFAULT_LANGUAGE).expect("the default locale is built in"));
+static MESSAGES: OnceLock<Option<Bundle>> = OnceLock::new();
+
+/// Selects the language: `language` from the config, otherwise the one from
+/// `LC_ALL`/`LC_MESSAGES`/`LANG`. Unknown languages fall back to English.
+pub fn init(language: Option<&str>) {
+    let language = language
+        .map(str::to_string)
+        .or_else(|| language_from_env(|name| env::var(name).ok()));
+    let messages = language
+        .as_deref()
+        .map(normalize)
+        .filter(|language| *language != DEFAULT_LANGUAGE)
+        .and_then(bundle);
+    let _ = MESSAGES.set(messages);
+}
+
+/// Looks `id` up in the selected language, then in English.
+pub fn translate(id: &str, args: &[(&str, &dyn Display)]) -> String {
+    MESSAGES
+        .get()
+        .and_then(Option::as_ref)
+        .and_then(|messages| format(messages, id, args))
+        .or_else(|| format(&FALLBACK, id, args))
+        .unwrap_or_else(|| id.to_string())
+}
+
+fn language_from_env(var: impl Fn(&str) -> Option<String>) -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| var(name))
+        .find(|value| !value.is_empty() && value != "C" && value != "POSIX")
+}
+
+/// `ru_RU.UTF-8` and `ru-RU` both mean `ru`.
+fn normalize(language: &str) -> &str {
+    language
+        .split(['_', '-', '.', '@'])
+        .next()
+        .unwrap_or(language)
+}
+
+/// The built-in locale for `language`.
+fn bundle(language: &str) -> Option<Bundle> {
+    let (name, source) = LOCALES
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(language))?;
+    let resource =
+        FluentResource::try_new(source.to_string()).expect("the built-in locales are valid");
+    let mut bundle = FluentBundle::new_concurrent(vec![name.parse().expect("a language id")]);
+    // the isolation marks around placeables end up in terminals and log files
+    bundle.set_use_isolating(false);
+    bundle
+        .add_resource(resource)
+        .expect("the built-in locales define each message once");
+    Some(bundle)
+}
+
+/// The message `id` with its `{ $name }` placeables filled in; `None` when the bundle
+/// doesn't have it.
+fn format(bundle: &Bundle, id: &str, args: &[(&str, &dyn Display)]) -> Option<String> {
+    let pattern = bundle.get_message(id)?.value()?;
+    let mut fluent_args = FluentArgs::new();
+    for (name, value) in args {
+        fluent_args.set(*name, FluentValue::from(value.to_string()));
+    }
+    // a missing argument is left as `{$name}` in the text, which is all that can be done
+    let mut errors = Vec::new();
+    Some(
+        bundle
+            .format_pattern(pattern, Some(&fluent_args), &mut errors)
+            .into_owned(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        let english = bundle("en").unwrap();
+        // no isolation marks around the name
+        assert_eq!(
+            format(&english, "auth-success", &[("name", &"Steve")]).unwrap(),
+            "Successfully authenticated as Steve"
+        );
+        assert_eq!(
+            format(&english, "error-exit-code", &[("code", &42)]).unwrap(),
+            "exit code 42"
+        );
+        assert_eq!(
+            format(&english, "auth-success", &[]).unwrap(),
+            "Successfully authenticated as {$name}"
+        );
+        assert_eq!(format(&english, "no-such-message", &[]), None);
+        assert!(bundle("xx").is_none());
+    }
+
+    #[test]
+    fn test_language_selection() {
+        assert_eq!(normalize("ru_RU.UTF-8"), "ru");
+        assert_eq!(normalize("en-US"), "en");
+        let env = |values: &'static [(&str, &str)]| {
+            move |name: &str| {
+                values
+                    .iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+        assert_eq!(
+            language_from_env(env(&[("LANG", "ru_RU.UTF-8"), ("LC_ALL", "C")])),
+            Some("ru_RU.UTF-8".to_string())
+        );
+        assert_eq!(language_from_env(env(&[])), None);
+    }
+
+    /// The ids of a locale's messages with the variables each one uses.
+    fn messages(source: &str) -> Vec<(String, Vec<String>)> {
+        use fluent_syntax::ast::{Entry, Expression, InlineExpression, PatternElement};
+
+        let resource = fluent_syntax::parser::parse(source).expect("valid Fluent");
+        resource
+            .body
+            .into_iter()
+            .filter_map(|entry| match entry {
+                Entry::Message(message) => Some(message),
+                _ => None,
+            })
+            .map(|message| {
+                let variables = message
+                    .value
+                    .into_iter()
+                    .flat_map(|pattern| pattern.elements)
+                    .filter_map(|element| match element {
+                        PatternElement::Placeable {
+                            expression:
+                                Expression::Inline(InlineExpression::VariableReference { id }),
+                        } => Some(id.name.to_string()),
+                        _ => None,
+                    })
+                    .collect();
+                (message.id.name.to_string(), variables)
+            })
+            .collect()
+    }
+
+    /// Every locale parses, every message has to exist in English, translations may
+    /// not use variables the English message doesn't provide, and every error message
+    /// is translated.
+    #[test]
+    fn test_locales_match() {
+        let english: HashMap<String, Vec<String>> = messages(LOCALES[0].1).into_iter().collect();
+        for (language, source) in LOCALES {
+            assert!(bundle(language).is_some());
+            for (id, variables) in messages(source) {
+                let original = english
+                    .get(&id)
+                    .unwrap_or_else(|| panic!("{} has {} but en does not", language, id));
+                for variable in variables {
+                    assert!(
+                        original.contains(&variable),
+                        "{}: {} uses {}",
+                        language,
+                        id,
+                        variable
+                    );
+                }
+            }
+        }
+        let russian: HashMap<String, Vec<String>> = messages(LOCALES[1].1).into_iter().collect();
+        for id in english.keys().filter(|id| id.starts_with("error-")) {
+            assert!(russian.contains_key(id), "ru has no {}", id);
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+const INSTANCE_FILE_NAME: &str = "instance.cfg";
+/// The `instance.cfg` section with the patcher's per-instance settings.
+const MMCAI_SECTION: &str = "mmcai";
+
+/// The parts of Prism's `instance.cfg` the patcher uses.
+#[derive(Debug, Default, PartialEq)]
+pub struct Instance {
+    pub name: Option<String>,
+    /// The Java Prism would use for the instance, when it is set per instance.
+    pub java_path: Option<PathBuf>,
+    /// The `[mmcai]` section: `username`, `password` and `api_url` replace the ones from
+    /// the wrapper command, so one global wrapper command can log every instance in
+    /// with its own account. `api_url` only counts along with the other two.
+    pub mmcai: BTreeMap<String, String>,
+}
+
+impl Instance {
+    pub fn load() -> Option<Instance> {
+        let text = fs::read_to_string(dir()?.join(INSTANCE_FILE_NAME)).ok()?;
+        Some(Instance::parse(&text))
+    }
+
+    fn parse(text: &str) -> Instance {
+        let ini = parse_ini(text);
+        let general = |key: &str| {
+            ini.get(&("General".to_string(), key.to_string()))
+                .filter(|value| !value.is_empty())
+                .cloned()
+        };
+        let java_overridden = general("OverrideJavaLocation").is_some_and(|value| value == "true")
+            || general("OverrideJava").is_some_and(|value| value == "true");
+        Instance {
+            name: general("name"),
+            java_path: general("JavaPath")
+                .filter(|_| java_overridden)
+                .map(PathBuf::from),
+            mmcai: ini
+                .iter()
+                .filter(|((section, _), _)| section == MMCAI_SECTION)
+                .map(|((_, key), value)| (key.clone(), value.clone()))
+                .collect(),
+        }
+    }
+
+    pub fn setting(&self, key: &str) -> Option<&str> {
+        self.mmcai
+            .get(key)
+            .map(String::as_str)
+            .filter(|value| !value.is_empty())
+    }
+}
+
+/// The instance directory: `INST_DIR`, or the working directory or its parent when one
+/// has an `instance.cfg`. Prism starts the wrapper in the instance's `.minecraft`.
+pub fn dir() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os("INST_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    let cwd = env::current_dir().ok()?;
+    cwd.ancestors()
+        .take(2)
+        .find(|dir| dir.join(INSTANCE_FILE_NAME).is_file())
+        .map(Path::to_path_buf)
+}
+
+/// The instance name: Prism's `INST_NAME`, or the name in `instance.cfg` for launchers
+/// that don't set it.
+pub fn name(instance: Option<&Instance>) -> Option<String> {
+    env::var("INST_NAME")
+        .ok()
+        .or_else(|| instance?.name.clone())
+}
+
+/// The instance ID: Prism's `INST_ID`, which is the name of the instance directory.
+pub fn id() -> Option<String> {
+    env::var("INST_ID")
+        .ok()
+        .or_else(|| Some(dir()?.file_name()?.to_str()?.to_string()))
+}
+
+/// Parses Qt's INI format as Prism writes it. Keys before any section are in `General`,
+/// like Qt does.
+fn parse_ini(text: &str) -> BTreeMap<(String, String), String> {
+    let mut values = BTreeMap::new();
+    let mut section = "General".to_string();
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line
+            .strip_prefix('[')
+            .and_then(|line| line.strip_suffix(']'))
+        {
+            section = name.trim().to_string();
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            values.insert(
+                (section.clone(), key.trim().to_string()),
+                unquote(value.trim()),
+            );
+        }
+    }
+    values
+}
+
+/// Qt quotes values with special characters and escapes `"` and `\` in them.
+fn unquote(value: &str) -> String {
+    let Some(inner) = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+    else {
+        return value.to_string();
+    };
+    let mut unquoted = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unquoted.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unquoted.push('\n'),
+            Some('t') => unquoted.push('\t'),
+            Some(escaped) => unquoted.push(escaped),
+            None => unquoted.push('\\'),
+        }
+    }
+    unquoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INSTANCE_CFG: &str = r#"[General]
+ConfigVersion=1.2
+InstanceType=OneSix
+JavaPath=/usr/lib/jvm/java-17-openjdk/bin/java
+OverrideJavaLocation=true
+name="Marallys \"SMP\""
+notes=
+
+[mmcai]
+username=steve
+password=hunter2
+"#;
+
+    #[test]
+    fn test_parse() {
+        let instance = Instance::parse(INSTANCE_CFG);
+        assert_eq!(instance.name.as_deref(), Some("Marallys \"SMP\""));
+        assert_eq!(
+            instance.java_path,
+            Some(PathBuf::from("/usr/lib/jvm/java-17-openjdk/bin/java"))
+        );
+        assert_eq!(instance.setting("username"), Some("steve"));
+        assert_eq!(instance.setting("api_url"), None);
+
+        let not_overridden = INSTANCE_CFG.replace("OverrideJavaLocation=true", "");
+        let instance = Instance::parse(&not_overridden);
+        assert_eq!(instance.java_path, None);
+    }
+}
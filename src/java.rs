@@ -0,0 +1,194 @@
+use std::{
+    collections::HashSet,
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::paths;
+
+const JAVA_BINARY: &str = if cfg!(windows) { "java.exe" } else { "java" };
+
+/// A Java installation that answered `java -version`.
+#[derive(Debug, PartialEq)]
+pub struct Java {
+    pub path: PathBuf,
+    /// 8 for `1.8.0_392`, 17 for `17.0.10`.
+    pub major_version: u32,
+}
+
+/// For when the wrapper runs under a launcher that doesn't set `INST_JAVA`: the
+/// first working Java of at least the `required` major version from `JAVA_HOME`,
+/// `PATH`, the runtimes Prism downloaded and the usual install locations, in that
+/// order.
+pub fn detect(required: Option<u32>) -> Option<Java> {
+    let mut seen = HashSet::new();
+    candidates()
+        .into_iter()
+        .filter(|path| path.is_file())
+        .filter(|path| seen.insert(fs::canonicalize(path).unwrap_or_else(|_| path.clone())))
+        .filter_map(check)
+        .find(|java| required.is_none_or(|required| java.major_version >= required))
+}
+
+/// The oldest Java that runs Minecraft `version`, as (minor, patch), as in Mojang's
+/// version manifests.
+pub fn required_major_version(version: (u32, u32)) -> u32 {
+    match version {
+        version if version >= (20, 5) => 21,
+        version if version >= (18, 0) => 17,
+        (17, _) => 16,
+        _ => 8,
+    }
+}
+
+/// `path` if it is a Java that runs.
+pub fn check(path: PathBuf) -> Option<Java> {
+    let major_version = version(&path)?;
+    Some(Java {
+        path,
+        major_version,
+    })
+}
+
+fn candidates() -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+    if let Some(java_home) = env::var_os("JAVA_HOME") {
+        candidates.push(PathBuf::from(java_home).join("bin").join(JAVA_BINARY));
+    }
+    if let Some(path) = env::var_os("PATH") {
+        candidates.extend(env::split_paths(&path).map(|dir| dir.join(JAVA_BINARY)));
+    }
+    for prism_dir in paths::prism_dirs() {
+        candidates.extend(installations(&prism_dir.join("java")));
+    }
+    let system_dirs: &[&str] = if cfg!(windows) {
+        &[
+            "C:\\Program Files\\Java",
+            "C:\\Program Files\\Eclipse Adoptium",
+            "C:\\Program Files\\Microsoft",
+            "C:\\Program Files\\Zulu",
+        ]
+    } else if cfg!(target_os = "macos") {
+        &["/Library/Java/JavaVirtualMachines"]
+    } else {
+        &["/usr/lib/jvm", "/usr/java", "/opt/java"]
+    };
+    for dir in system_dirs {
+        candidates.extend(installations(Path::new(dir)));
+    }
+    candidates
+}
+
+/// The Java binaries of the installations in `dir`, newest first by the major version
+/// in their names (`java-17-openjdk`, `jdk1.8.0_392`), then by name.
+fn installations(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut homes: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .collect();
+    homes.sort_by_cached_key(|home| {
+        let name = home.file_name().unwrap_or_default().to_string_lossy();
+        (name_major_version(&name), name.into_owned())
+    });
+    homes.reverse();
+    homes
+        .into_iter()
+        .map(|home| {
+            // macOS bundles keep the runtime in Contents/Home
+            let bundle = home.join("Contents").join("Home");
+            let home = if bundle.is_dir() { bundle } else { home };
+            home.join("bin").join(JAVA_BINARY)
+        })
+        .collect()
+}
+
+fn version(java: &Path) -> Option<u32> {
+    let output = Command::new(java).arg("-version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    // java prints its version to stderr
+    parse_major_version(&String::from_utf8_lossy(&output.stderr))
+}
+
+/// Reads the major version from `openjdk version "17.0.10" 2024-01-16` and the like.
+fn parse_major_version(output: &str) -> Option<u32> {
+    let start = output.find("version \"")? + "version \"".len();
+    let version = output[start..].split('"').next()?;
+    let mut parts = version.split(['.', '_', '-', '+']);
+    match parts.next()?.parse().ok()? {
+        1 => parts.next()?.parse().ok(),
+        major => Some(major),
+    }
+}
+
+/// The major version in an installation's directory name, 0 when it has none.
+fn name_major_version(name: &str) -> u32 {
+    let mut numbers = name
+        .split(|c: char| !c.is_ascii_digit())
+        .filter_map(|number| number.parse().ok());
+    match numbers.next() {
+        Some(1) => numbers.next().unwrap_or(1),
+        Some(major) => major,
+        None => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_major_version() {
+        assert_eq!(
+            parse_major_version("openjdk version \"17.0.10\" 2024-01-16\nOpenJDK Runtime"),
+            Some(17)
+        );
+        assert_eq!(
+            parse_major_version("java version \"1.8.0_392\"\nJava(TM) SE"),
+            Some(8)
+        );
+        assert_eq!(
+            parse_major_version("openjdk version \"21\" 2023-09-19"),
+            Some(21)
+        );
+        assert_eq!(parse_major_version("openjdk version \"22-ea\""), Some(22));
+        assert_eq!(parse_major_version("command not found"), None);
+    }
+
+    #[test]
+    fn test_installations() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        for name in [
+            "java-17-openjdk",
+            "java-21-openjdk",
+            "java-8-openjdk",
+            "jdk1.8.0_392",
+        ] {
+            fs::create_dir_all(dir.path().join(name).join("bin")).unwrap();
+        }
+        assert_eq!(
+            installations(dir.path()),
+            vec![
+                dir.path().join("java-21-openjdk/bin").join(JAVA_BINARY),
+                dir.path().join("java-17-openjdk/bin").join(JAVA_BINARY),
+                dir.path().join("jdk1.8.0_392/bin").join(JAVA_BINARY),
+                dir.path().join("java-8-openjdk/bin").join(JAVA_BINARY),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_required_major_version() {
+        assert_eq!(required_major_version((21, 1)), 21);
+        assert_eq!(required_major_version((20, 5)), 21);
+        assert_eq!(required_major_version((20, 4)), 17);
+        assert_eq!(required_major_version((18, 2)), 17);
+        assert_eq!(required_major_version((17, 1)), 16);
+        assert_eq!(required_major_version((12, 2)), 8);
+    }
+}
@@ -0,0 +1,123 @@
+use tracing::warn;
+
+use crate::config::{parse_memory_size, MemoryConfig, MemorySize};
+use crate::i18n::tr;
+
+/// Applies the `[memory]` settings, warning about launcher values that get replaced.
+pub fn apply_memory(jvm_args: &mut Vec<String>, position: usize, memory: &MemoryConfig) {
+    let memory_settings = [
+        ("-Xmx", "memory.max", &memory.max),
+        ("-Xms", "memory.min", &memory.min),
+    ];
+    for (option, setting, size) in memory_settings {
+        let Some(size) = size else { continue };
+        if let Some(previous) = set_memory(jvm_args, position, option, size) {
+            let message = tr!(
+                "memory-replaced",
+                previous = format!("{}{}", option, previous),
+                configured = format!("{}{}", option, size.as_str()),
+                setting = setting,
+            );
+            warn!("{}", message);
+        }
+    }
+    if let (Some(max), Some(min)) = (&memory.max, &memory.min) {
+        if min.bytes() > max.bytes() {
+            let message = tr!(
+                "memory-min-above-max",
+                min = min.as_str(),
+                max = max.as_str()
+            );
+            warn!("{}", message);
+        }
+    }
+}
+
+/// Sets a memory option like `-Xmx` to `size`, replacing whatever the launcher passed or
+/// inserting it at `position`. Returns the launcher's value if it was a different size.
+fn set_memory(
+    jvm_args: &mut Vec<String>,
+    position: usize,
+    option: &str,
+    size: &MemorySize,
+) -> Option<String> {
+    let wanted = format!("{}{}", option, size.as_str());
+    let mut conflict = None;
+    let mut found = false;
+    for arg in jvm_args.iter_mut() {
+        let Some(current) = arg.strip_prefix(option) else {
+            continue;
+        };
+        if parse_memory_size(current) != Some(size.bytes()) {
+            conflict = Some(current.to_string());
+        }
+        *arg = wanted.clone();
+        found = true;
+    }
+    if !found {
+        jvm_args.insert(position, wanted);
+    }
+    conflict
+}
+
+/// Inserts the `extra` JVM arguments at `position`, skipping the ones already given.
+pub fn insert_extra_args(jvm_args: &mut Vec<String>, position: usize, extra: &[String]) {
+    let mut missing: Vec<String> = Vec::new();
+    for arg in extra {
+        if !jvm_args.contains(arg) && !missing.contains(arg) {
+            missing.push(arg.clone());
+        }
+    }
+    jvm_args.splice(position..position, missing);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_set_memory() {
+        let size = |value: &str| MemorySize::try_from(value.to_string()).unwrap();
+
+        let mut jvm_args = args(&["-javaagent:a.jar", "-Xms512m", "-Xmx2G", "Main"]);
+        assert_eq!(
+            set_memory(&mut jvm_args, 1, "-Xmx", &size("4G")),
+            Some("2G".to_string())
+        );
+        assert_eq!(set_memory(&mut jvm_args, 1, "-Xms", &size("512M")), None);
+        assert_eq!(
+            jvm_args,
+            args(&["-javaagent:a.jar", "-Xms512M", "-Xmx4G", "Main"])
+        );
+
+        let mut jvm_args = args(&["-javaagent:a.jar", "Main"]);
+        assert_eq!(set_memory(&mut jvm_args, 1, "-Xmx", &size("4G")), None);
+        assert_eq!(jvm_args, args(&["-javaagent:a.jar", "-Xmx4G", "Main"]));
+    }
+
+    #[test]
+    fn test_insert_extra_args() {
+        let mut jvm_args = args(&["-javaagent:a.jar", "-Xmx2G", "-cp", "x.jar", "Main"]);
+        insert_extra_args(
+            &mut jvm_args,
+            1,
+            &args(&["-Dfoo=1", "-Xmx2G", "-Dfoo=1", "-Dbar"]),
+        );
+        assert_eq!(
+            jvm_args,
+            args(&[
+                "-javaagent:a.jar",
+                "-Dfoo=1",
+                "-Dbar",
+                "-Xmx2G",
+                "-cp",
+                "x.jar",
+                "Main"
+            ])
+        );
+    }
+}
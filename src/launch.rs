@@ -0,0 +1,1117 @@
+use std::{
+    env,
+    ffi::OsString,
+    io::{self, BufRead, BufReader, Read, Write},
+    path::{Path, PathBuf},
+    pin::pin,
+    process::{Child, Command, ExitStatus, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant, SystemTime},
+};
+
+use futures_util::future::{self, Either};
+use tracing::{debug, info, info_span, warn, Instrument};
+
+use crate::argfile::ArgFile;
+use crate::auth::{
+    credentials, generate_client_token, login_with_fallback, wrapper_account, LoginResult,
+};
+use crate::config::{Config, ProcessConfig};
+use crate::crash::{self, CrashReport};
+use crate::dates;
+use crate::errors::MmcaiError;
+use crate::events::{Event, EventSink};
+use crate::http::HttpClient;
+use crate::i18n::tr;
+use crate::injector::find_authlib_injector;
+use crate::instance::Instance;
+use crate::launcher::Launcher;
+use crate::logfile::RotatingLog;
+use crate::model::Profile;
+use crate::pack::Pack;
+use crate::params::{modify_minecraft_params, read_minecraft_params, LaunchParams};
+use crate::timing::{Stage, Timings};
+use crate::trace::HttpTrace;
+use crate::webhook::{Notification, Webhooks};
+use crate::{
+    argfile, cli, dump, environment, instance, java, jvm, launcher, logging, notice, playtime,
+    prelaunch, signature, skin, Result,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Makes the game go down with the wrapper. Prism's "Kill" button only terminates the
+/// wrapper, which used to leave the JVM running on its own.
+///
+/// On Unix, SIGTERM, SIGINT and SIGHUP are forwarded to the child's process group; the
+/// wrapper then exits with the child's status as usual. On Windows, the child is started
+/// in a Job Object that is killed together with the wrapper, and console close/Ctrl+C
+/// events terminate the child.
+pub fn forward_termination(child: &Child) {
+    imp::forward_termination(child);
+}
+
+/// Whether the wrapper was asked to stop, in which case the game must not be relaunched.
+pub fn termination_requested() -> bool {
+    TERMINATION_REQUESTED.load(Ordering::SeqCst)
+}
+
+static TERMINATION_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Where the game's output goes besides our own stdout/stderr.
+#[derive(Clone, Default)]
+pub struct Relay {
+    pub log: Option<Arc<Mutex<RotatingLog>>>,
+    /// Prefix every line with the time and `[mc]`, to tell it apart from `[mmcai_rs]` lines.
+    pub annotate: bool,
+    /// Crash signatures seen in the output, see [`crash::scan`].
+    pub crash_signs: Arc<Mutex<Vec<&'static str>>>,
+}
+
+impl Relay {
+    /// Whether the output has to go through the wrapper at all.
+    fn is_needed(&self, watchdog: Option<Duration>, detect_crashes: bool) -> bool {
+        watchdog.is_some() || self.log.is_some() || self.annotate || detect_crashes
+    }
+}
+
+/// Everything needed to start the game, possibly more than once.
+pub struct Game<'a> {
+    pub java_executable: &'a str,
+    pub jvm_args: &'a [String],
+    pub minecraft_params: &'a [String],
+    pub relay: Relay,
+    pub watchdog: Option<Duration>,
+    pub detect_crashes: bool,
+    pub process: &'a ProcessConfig,
+    /// Where the game runs. `None` keeps the wrapper's own working directory.
+    pub working_dir: Option<&'a Path>,
+    /// The complete environment of the game. `None` inherits the wrapper's.
+    pub environment: Option<&'a [(OsString, OsString)]>,
+    /// Gets the time it took to start the game, and reports all timings once it has.
+    pub timings: Option<&'a Timings<'a>>,
+}
+
+/// Makes sure the configured working directory exists before anything else happens;
+/// the game only fails much later (extracting natives) otherwise.
+pub fn check_working_dir(dir: &Path) -> Result<()> {
+    if dir.is_dir() {
+        Ok(())
+    } else {
+        Err(MmcaiError::WorkingDirectoryNotFound(dir.to_path_buf()))
+    }
+}
+
+/// How a run of the game ended.
+pub struct Exit {
+    pub status: ExitStatus,
+    pub pid: u32,
+}
+
+impl Game<'_> {
+    /// The directory the game runs in, where it also writes crash logs.
+    pub fn working_dir(&self) -> PathBuf {
+        self.working_dir
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| env::current_dir().unwrap_or_default())
+    }
+
+    /// Starts the game, feeds it the launch params and waits for it to exit.
+    pub async fn run(&self) -> Result<Exit> {
+        let output = if self.relay.is_needed(self.watchdog, self.detect_crashes) {
+            Stdio::piped
+        } else {
+            Stdio::inherit
+        };
+        if let Ok(mut signs) = self.relay.crash_signs.lock() {
+            signs.clear();
+        }
+
+        let started = Instant::now();
+        // a chained wrapper gets the Java command line and starts Java itself
+        let mut command = match self.process.exec_next.split_first() {
+            Some((wrapper, wrapper_args)) => {
+                let mut command = Command::new(wrapper);
+                command.args(wrapper_args).arg(self.java_executable);
+                command
+            }
+            None => Command::new(self.java_executable),
+        };
+        imp::isolate(&mut command);
+        imp::prepare_scheduling(&mut command, self.process);
+        if let Some(dir) = self.working_dir {
+            command.current_dir(dir);
+        }
+        if let Some(environment) = self.environment {
+            command.env_clear().envs(environment.iter().cloned());
+        }
+        let mut child = command
+            .args(self.jvm_args)
+            .stdin(Stdio::piped())
+            .stdout(output())
+            .stderr(output())
+            .spawn()
+            .map_err(MmcaiError::SpawnProcessFailed)?;
+        forward_termination(&child);
+        imp::apply_scheduling(&child, self.process);
+        let activity = Arc::new(AtomicBool::new(false));
+        let relays = relay_output(&mut child, &activity, &self.relay);
+
+        let stdin = child.stdin.as_mut().ok_or(MmcaiError::StdinUnavailable)?;
+        self.minecraft_params.iter().for_each(|line| {
+            let _ = writeln!(stdin, "{}", line).map_err(MmcaiError::WriteMinecraftParamsFailed);
+        });
+        if let Some(timings) = self.timings {
+            timings.set(Stage::Spawn, started.elapsed());
+            timings.report();
+        }
+
+        let pid = child.id();
+        let status = wait(child, self.watchdog, activity).await;
+        relays.into_iter().for_each(|relay| {
+            let _ = relay.join();
+        });
+        Ok(Exit {
+            status: status?,
+            pid,
+        })
+    }
+}
+
+/// Copies the child's piped stdout/stderr to ours (and to the log) on background
+/// threads, raising `activity` as soon as the game prints anything.
+fn relay_output(
+    child: &mut Child,
+    activity: &Arc<AtomicBool>,
+    relay: &Relay,
+) -> Vec<JoinHandle<()>> {
+    let mut relays = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        relays.push(relay_stream(
+            stdout,
+            io::stdout(),
+            activity.clone(),
+            relay.clone(),
+        ));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        relays.push(relay_stream(
+            stderr,
+            io::stderr(),
+            activity.clone(),
+            relay.clone(),
+        ));
+    }
+    relays
+}
+
+fn relay_stream<R, W>(
+    source: R,
+    mut sink: W,
+    activity: Arc<AtomicBool>,
+    Relay {
+        log,
+        annotate,
+        crash_signs,
+    }: Relay,
+) -> JoinHandle<()>
+where
+    R: Read + Send + 'static,
+    W: Write + Send + 'static,
+{
+    thread::spawn(move || {
+        let mut source = BufReader::new(source);
+        let mut line = Vec::new();
+        while let Ok(read) = source.read_until(b'\n', &mut line) {
+            if read == 0 {
+                break;
+            }
+            activity.store(true, Ordering::Relaxed);
+            if let Some(sign) = crash::scan(&line) {
+                if let Ok(mut signs) = crash_signs.lock() {
+                    signs.push(sign);
+                }
+            }
+            if annotate {
+                let prefix = annotation(SystemTime::now());
+                line.splice(0..0, prefix.into_bytes());
+            }
+            let _ = sink.write_all(&line).and_then(|_| sink.flush());
+            if let Some(Ok(mut log)) = log.as_deref().map(Mutex::lock) {
+                // the log is a copy, losing it must not take the game down
+                let _ = log.write_all(&line);
+            }
+            line.clear();
+        }
+    })
+}
+
+fn annotation(time: SystemTime) -> String {
+    format!("[{}] [mc] ", dates::format_time_of_day(time))
+}
+
+/// Waits for the child to exit. With a `watchdog`, a child that hasn't printed anything
+/// by then is considered hung (typically a broken `-javaagent`) and killed; Minecraft
+/// logs well before it opens its window, so output is all the watchdog looks for. What
+/// the game left running is terminated before the child is reaped, while its process
+/// group can't belong to anything else yet.
+async fn wait(
+    mut child: Child,
+    watchdog: Option<Duration>,
+    activity: Arc<AtomicBool>,
+) -> Result<ExitStatus> {
+    // the game sees the end of the params only once stdin is closed
+    drop(child.stdin.take());
+    if let Some(timeout) = watchdog {
+        let deadline = Instant::now() + timeout;
+        while !activity.load(Ordering::Relaxed) {
+            if imp::wait_exited(&mut child, false).map_err(MmcaiError::WaitProcessFailed)? {
+                break;
+            }
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = reap(child).await;
+                return Err(MmcaiError::GameUnresponsive {
+                    seconds: timeout.as_secs(),
+                });
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+    reap(child).await
+}
+
+/// Waits for the child to exit on the blocking pool, so the runtime stays free for
+/// anything else in flight, then terminates its process group and reaps it.
+async fn reap(mut child: Child) -> Result<ExitStatus> {
+    tokio::task::spawn_blocking(move || {
+        imp::wait_exited(&mut child, true)?;
+        imp::reap(&child);
+        child.wait()
+    })
+    .await
+    .map_err(|e| MmcaiError::WaitProcessFailed(io::Error::other(e)))?
+    .map_err(MmcaiError::WaitProcessFailed)
+}
+
+/// The exit code to leave with once the child has exited. A child killed by a signal
+/// exits with 128 + the signal number, like in a shell.
+pub fn exit_code(status: ExitStatus) -> i32 {
+    if let Some(code) = status.code() {
+        return code;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+    1
+}
+
+/// Everything from the login to the game's exit, so failures can be counted in one place.
+pub async fn launch_game(
+    options: &cli::Options,
+    args: &[String],
+    config: &Config,
+    events: &EventSink,
+    timings: &Timings<'_>,
+) -> Result<ExitStatus> {
+    if let Some(dir) = &config.process.working_dir {
+        check_working_dir(dir)?;
+    }
+    let trace = options
+        .trace_http
+        .as_deref()
+        .map(HttpTrace::open)
+        .transpose()?;
+    let http = HttpClient::new(config, trace)?;
+    let instance = Instance::load();
+    let instance_name = instance::name(instance.as_ref());
+    let pack = instance::dir().and_then(|dir| Pack::load(&dir));
+    if let Some(pack) = &pack {
+        debug!(
+            minecraft = ?pack.minecraft_version(),
+            loader = ?pack.loader(),
+            "Read the instance's components"
+        );
+    }
+    let webhooks = Webhooks::new(&http, &config.webhooks, instance_name.as_deref());
+    // offline and test instances keep the wrapper but skip the login
+    let pass_through = options.pass_through
+        || instance
+            .as_ref()
+            .and_then(|instance| instance.setting("pass_through"))
+            == Some("true");
+
+    let launcher = options
+        .launcher
+        .unwrap_or_else(|| Launcher::detect(&args[5..]));
+    let (java_command, argument_params) = launcher.split(&args[5..]);
+    let params_on_command_line = argument_params.is_some();
+
+    // find authlib-injector
+    let authlib_injector_path = if pass_through {
+        info!("{}", tr!("pass-through"));
+        None
+    } else {
+        let authlib_injector_path = options
+            .authlib_injector
+            .clone()
+            .or_else(|| find_authlib_injector(None))
+            .ok_or(MmcaiError::AuthlibInjectorNotFound)?;
+        events.emit(Event::InjectorFound {
+            path: &authlib_injector_path,
+        });
+        let path = format!("{:?}", authlib_injector_path);
+        info!("{}", tr!("injector-found", path = path));
+        Some(authlib_injector_path)
+    };
+
+    // yggdrasil part
+    let account = wrapper_account(
+        &args[1..4],
+        options.account.as_deref(),
+        instance.as_ref(),
+        config,
+    )?;
+    // a pre-launch command may have logged in already
+    let handoff = if pass_through {
+        None
+    } else {
+        prelaunch::take(&account.username, account.api_url())
+    };
+    let account = &if pass_through || handoff.is_some() {
+        account
+    } else {
+        credentials(account)?
+    };
+    let api_url = account.api_url();
+
+    let client_token = match &handoff {
+        Some(handoff) => handoff.client_token.clone(),
+        None => account
+            .client_token
+            .clone()
+            .unwrap_or_else(generate_client_token),
+    };
+
+    let api_urls: Vec<&str> = std::iter::once(api_url)
+        .chain(config.server.fallback_api_urls.iter().map(String::as_str))
+        .collect();
+
+    // the launcher streams minecraft params while we are still talking to the server
+    let login = async {
+        if pass_through {
+            return Ok(None);
+        }
+        if let Some(handoff) = handoff {
+            info!("{}", tr!("prelaunch-used", name = handoff.name));
+            return Ok(Some(LoginResult {
+                api_url: handoff.api_url,
+                prefetched_data: handoff.prefetched_data,
+                access_token: handoff.access_token,
+                expired_date: None,
+                selected_profile: Profile {
+                    id: handoff.uuid,
+                    name: handoff.name,
+                },
+                textures: skin::Textures::default(),
+                server_ip: None,
+            }));
+        }
+        events.emit(Event::AuthStarted {
+            account: account.alias(),
+            api_url,
+        });
+        let announcements = async {
+            if let Some(url) = &config.server.announcements_url {
+                if let Some(notice) = notice::fetch(&http, url).await {
+                    notice.print();
+                }
+            }
+        };
+        let (_, login_result) = future::join(
+            announcements,
+            login_with_fallback(account, &client_token, &api_urls, &http, config, timings),
+        )
+        .await;
+        let login_result = match login_result {
+            Ok(login_result) => login_result,
+            Err(error) => {
+                webhooks.notify(Notification::LoginFailed {
+                    account: account.alias(),
+                    error: &error,
+                });
+                return Err(error);
+            }
+        };
+        events.emit(Event::AuthOk {
+            name: &login_result.selected_profile.name,
+            uuid: &login_result.selected_profile.id,
+            full_skin_url: login_result.textures.full_skin_url.as_deref(),
+        });
+        webhooks.notify(Notification::Login {
+            name: &login_result.selected_profile.name,
+        });
+        let name = &login_result.selected_profile.name;
+        info!(target: logging::SUCCESS_TARGET, "{}", tr!("auth-success", name = name));
+        if config.server.verify_texture_signatures {
+            // without prefetching there is no metadata to take the key from
+            if let Some(prefetched) = &login_result.prefetched_data {
+                signature::check_textures(
+                    &http,
+                    &login_result.api_url,
+                    &login_result.selected_profile.id,
+                    prefetched,
+                )
+                .await;
+            }
+        }
+        Ok(Some(login_result))
+    }
+    .instrument(info_span!("auth", account = account.alias(), api_url));
+
+    let params = timings.measure(Stage::ParamRead, async {
+        match argument_params {
+            Some(params) => Ok(params),
+            None => read_minecraft_params(options, config).await,
+        }
+    });
+    // Prism's `abort` or a read timeout drops the login, even mid-request
+    let (login_result, mut minecraft_params) = match future::select(pin!(login), pin!(params)).await
+    {
+        Either::Left((login_result, params)) => {
+            let login_result = login_result?;
+            (login_result, params.await?)
+        }
+        Either::Right((params, login)) => {
+            let params = params?;
+            (login.await?, params)
+        }
+    };
+
+    let playername = match &login_result {
+        Some(login_result) => {
+            let profile = &login_result.selected_profile;
+            info_span!("patch").in_scope(|| {
+                modify_minecraft_params(
+                    &mut minecraft_params,
+                    &login_result.access_token,
+                    &profile.id,
+                    &profile.name,
+                    &client_token,
+                    pack.as_ref(),
+                    config,
+                )
+            })?;
+            profile.name.clone()
+        }
+        // the params go to the game as the launcher sent them
+        None => LaunchParams::parse(&minecraft_params)
+            .argument("--username")
+            .unwrap_or_default()
+            .to_string(),
+    };
+
+    // ready to launch
+    let chosen_java = match &options.java {
+        Some(path) => Some(
+            path.to_str()
+                .ok_or(MmcaiError::JavaExecutableNotFound)?
+                .to_string(),
+        ),
+        None => env::var("INST_JAVA").ok(),
+    };
+    let (java_executable, java_version) = match chosen_java {
+        Some(java_executable) => (java_executable, None),
+        None => {
+            let instance_java = instance
+                .as_ref()
+                .and_then(|instance| instance.java_path.clone())
+                .and_then(java::check);
+            let mut params = LaunchParams::parse(&minecraft_params);
+            params.pack_version = pack.as_ref().and_then(Pack::minecraft_version);
+            let required = params.minecraft_version().map(java::required_major_version);
+            let java = instance_java
+                .or_else(|| java::detect(required))
+                .ok_or(MmcaiError::JavaExecutableNotFound)?;
+            let path = format!("{:?}", java.path);
+            warn!(
+                "{}",
+                tr!("java-detected", path = path, version = java.major_version)
+            );
+            let java_executable = java
+                .path
+                .into_os_string()
+                .into_string()
+                .map_err(|_| MmcaiError::JavaExecutableNotFound)?;
+            (java_executable, Some(java.major_version))
+        }
+    };
+
+    let mut jvm_args = java_command;
+    let mut arg_file = None;
+    let mut injector_args = 0;
+    if let (Some(authlib_injector_path), Some(login_result)) =
+        (&authlib_injector_path, &login_result)
+    {
+        jvm_args.insert(
+            0,
+            format!(
+                "-javaagent:{}={}",
+                authlib_injector_path.to_str().ok_or(MmcaiError::Other)?,
+                login_result.api_url
+            ),
+        );
+        injector_args = 1;
+        if let Some(prefetched_data) = &login_result.prefetched_data {
+            let prefetched_arg =
+                format!("-Dauthlibinjector.yggdrasil.prefetched={}", prefetched_data);
+            jvm_args.insert(1, prefetched_arg);
+            injector_args = 2;
+
+            // some servers' metadata is too large for the OS command line once base64'd
+            if !argfile::fits_command_line(&java_executable, &jvm_args) {
+                let java_version = java_version.or_else(|| {
+                    java::check(PathBuf::from(&java_executable)).map(|java| java.major_version)
+                });
+                if java_version.is_some_and(argfile::is_supported) {
+                    let file = ArgFile::create(&jvm_args[1..2])?;
+                    let path = format!("{:?}", file.path());
+                    info!("{}", tr!("metadata-argfile", path = path));
+                    jvm_args[1] = file.argument();
+                    arg_file = Some(file);
+                } else {
+                    // authlib-injector then fetches the metadata itself
+                    warn!("{}", tr!("metadata-not-prefetched"));
+                    jvm_args.remove(1);
+                    injector_args = 1;
+                }
+            }
+        }
+    }
+    jvm::insert_extra_args(&mut jvm_args, injector_args, &config.jvm_args_extra);
+    jvm::apply_memory(&mut jvm_args, injector_args, &config.memory);
+    if params_on_command_line {
+        // the game gets its params back on the command line, and nothing on stdin
+        jvm_args.extend(launcher::arguments(&minecraft_params));
+        minecraft_params.clear();
+    }
+
+    debug!(?java_executable, ?jvm_args, "Built the command line");
+    debug!(
+        minecraft_params = ?LaunchParams::parse(&minecraft_params).redacted().to_lines(),
+        "Patched the launch params"
+    );
+
+    if let Some(path) = &options.dump_launch {
+        dump::write(
+            path,
+            &java_executable,
+            &jvm_args,
+            &minecraft_params,
+            options.unsafe_full,
+        )?;
+    }
+
+    let log = if config.log.enabled {
+        let log = RotatingLog::open(
+            &config.log.dir(),
+            config.log.max_size_kb * 1024,
+            config.log.max_files,
+        )?;
+        Some(Arc::new(Mutex::new(log)))
+    } else {
+        None
+    };
+    let child_environment = environment::child_environment(&config.env, env::vars_os());
+    let game = Game {
+        java_executable: &java_executable,
+        jvm_args: &jvm_args,
+        minecraft_params: &minecraft_params,
+        relay: Relay {
+            log,
+            annotate: config.log.annotate,
+            ..Default::default()
+        },
+        watchdog: (config.watchdog.timeout > 0)
+            .then(|| Duration::from_secs(config.watchdog.timeout)),
+        detect_crashes: config.crash.detect,
+        process: &config.process,
+        working_dir: config.process.working_dir.as_deref(),
+        environment: child_environment.as_deref(),
+        timings: Some(timings),
+    };
+
+    let _launch = info_span!("launch").entered();
+    let mut relaunches = 0;
+    let status = loop {
+        events.emit(Event::Launching {
+            attempt: relaunches + 1,
+        });
+        webhooks.notify(Notification::Launch { name: &playername });
+        let started = dates::unix_now();
+        let exit = game.run().await?;
+        if config.playtime.enabled {
+            playtime::record(&playtime::Session {
+                account: playername.clone(),
+                instance: instance_name.clone(),
+                start: started,
+                end: dates::unix_now(),
+            });
+        }
+        events.emit(Event::ChildExit {
+            code: exit.status.code(),
+        });
+        if !exit.status.success() && !termination_requested() {
+            webhooks.notify(Notification::Crash {
+                name: &playername,
+                status: exit.status,
+            });
+        }
+        if exit.status.success() || termination_requested() || !config.crash.detect {
+            break exit.status;
+        }
+        let signs = game
+            .relay
+            .crash_signs
+            .lock()
+            .map(|signs| signs.clone())
+            .unwrap_or_default();
+        CrashReport::diagnose(exit.status, exit.pid, &signs, &game.working_dir()).print();
+        if relaunches >= config.crash.relaunch {
+            break exit.status;
+        }
+        relaunches += 1;
+        let message = tr!(
+            "relaunching",
+            attempt = relaunches,
+            max = config.crash.relaunch
+        );
+        info!("{}", message);
+    };
+    drop(arg_file);
+
+    Ok(status)
+}
+
+#[cfg(unix)]
+mod imp {
+    use crate::config::ProcessConfig;
+    use std::os::unix::process::CommandExt;
+    use std::process::{Child, Command};
+    use std::sync::atomic::{AtomicI32, Ordering};
+    use std::{io, mem};
+
+    static CHILD_PID: AtomicI32 = AtomicI32::new(0);
+
+    /// Starts the child in a process group of its own, so everything the game starts can
+    /// be signalled at once. On Linux the child is also killed if the wrapper dies
+    /// without getting a chance to forward anything (SIGKILL).
+    ///
+    /// The kernel sends that signal when the thread that forked exits, not the process,
+    /// so it is only asked for when spawning from the main thread, as the binary does. A
+    /// game started from a worker thread of a library user's runtime would be killed when
+    /// the runtime retires that thread.
+    pub fn isolate(command: &mut Command) {
+        command.process_group(0);
+        #[cfg(target_os = "linux")]
+        if unsafe { libc::gettid() == libc::getpid() } {
+            unsafe {
+                command.pre_exec(|| {
+                    libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL);
+                    Ok(())
+                });
+            }
+        }
+    }
+
+    /// Priority and affinity are set between fork and exec, so every thread the JVM
+    /// starts inherits them. Failures (e.g. a negative nice value without root) are
+    /// ignored rather than keeping the game from starting.
+    pub fn prepare_scheduling(command: &mut Command, process: &ProcessConfig) {
+        let nice = process.nice();
+        #[cfg(target_os = "linux")]
+        let cpu_set = (!process.cpu_affinity.is_empty()).then(|| {
+            let mut cpu_set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+            // the set is a fixed-size bitmask, CPU_SETSIZE (1024 with glibc) bits long
+            let capacity = 8 * std::mem::size_of::<libc::cpu_set_t>();
+            for &cpu in &process.cpu_affinity {
+                if cpu < capacity {
+                    unsafe { libc::CPU_SET(cpu, &mut cpu_set) };
+                } else {
+                    tracing::warn!(
+                        "{}",
+                        crate::i18n::tr!("cpu-affinity-out-of-range", cpu = cpu)
+                    );
+                }
+            }
+            cpu_set
+        });
+        #[cfg(not(target_os = "linux"))]
+        if !process.cpu_affinity.is_empty() {
+            tracing::warn!("{}", crate::i18n::tr!("cpu-affinity-unsupported"));
+        }
+
+        unsafe {
+            command.pre_exec(move || {
+                if let Some(nice) = nice {
+                    libc::setpriority(libc::PRIO_PROCESS, 0, nice);
+                }
+                #[cfg(target_os = "linux")]
+                if let Some(cpu_set) = &cpu_set {
+                    libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), cpu_set);
+                }
+                Ok(())
+            });
+        }
+    }
+
+    pub fn apply_scheduling(_child: &Child, _process: &ProcessConfig) {}
+
+    /// Whether the child has exited, waiting for it with `block`. The child is left a
+    /// zombie, so its pid, which is also its process group's id, isn't reused until it
+    /// is reaped.
+    pub fn wait_exited(child: &mut Child, block: bool) -> io::Result<bool> {
+        let mut flags = libc::WEXITED | libc::WNOWAIT;
+        if !block {
+            flags |= libc::WNOHANG;
+        }
+        loop {
+            let mut info: libc::siginfo_t = unsafe { mem::zeroed() };
+            let pid = child.id() as libc::id_t;
+            if unsafe { libc::waitid(libc::P_PID, pid, &mut info, flags) } == 0 {
+                // with WNOHANG and a running child, waitid leaves `info` zeroed
+                return Ok(info.si_signo != 0);
+            }
+            let error = io::Error::last_os_error();
+            if error.kind() != io::ErrorKind::Interrupted {
+                return Err(error);
+            }
+        }
+    }
+
+    /// Terminates whatever the game left running in its process group. Called before the
+    /// child is reaped: until then no other group can have its id.
+    pub fn reap(child: &Child) {
+        // signals from now on have nothing to go to, the group's id is about to be freed
+        CHILD_PID.store(0, Ordering::SeqCst);
+        unsafe {
+            libc::kill(-(child.id() as i32), libc::SIGTERM);
+        }
+    }
+
+    extern "C" fn forward(signal: libc::c_int) {
+        super::TERMINATION_REQUESTED.store(true, Ordering::SeqCst);
+        let pid = CHILD_PID.load(Ordering::SeqCst);
+        if pid > 0 {
+            // kill() is async-signal-safe; a negative pid signals the whole group
+            unsafe {
+                libc::kill(-pid, signal);
+            }
+        }
+    }
+
+    pub fn forward_termination(child: &Child) {
+        CHILD_PID.store(child.id() as i32, Ordering::SeqCst);
+        let handler = forward as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        for signal in [libc::SIGTERM, libc::SIGINT, libc::SIGHUP] {
+            unsafe {
+                libc::signal(signal, handler);
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use crate::config::{Priority, ProcessConfig};
+    use std::ffi::c_void;
+    use std::io;
+    use std::os::windows::io::AsRawHandle;
+    use std::process::{Child, Command};
+    use std::sync::atomic::{AtomicPtr, Ordering};
+    use std::sync::Once;
+
+    type Handle = *mut c_void;
+
+    const JOB_OBJECT_EXTENDED_LIMIT_INFORMATION_CLASS: i32 = 9;
+    const JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE: u32 = 0x2000;
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct BasicLimitInformation {
+        per_process_user_time_limit: i64,
+        per_job_user_time_limit: i64,
+        limit_flags: u32,
+        minimum_working_set_size: usize,
+        maximum_working_set_size: usize,
+        active_process_limit: u32,
+        affinity: usize,
+        priority_class: u32,
+        scheduling_class: u32,
+    }
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct ExtendedLimitInformation {
+        basic_limit_information: BasicLimitInformation,
+        io_info: [u64; 6],
+        process_memory_limit: usize,
+        job_memory_limit: usize,
+        peak_process_memory_used: usize,
+        peak_job_memory_used: usize,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn CreateJobObjectW(attributes: *mut c_void, name: *const u16) -> Handle;
+        fn SetInformationJobObject(job: Handle, class: i32, info: *mut c_void, length: u32) -> i32;
+        fn AssignProcessToJobObject(job: Handle, process: Handle) -> i32;
+        fn GetCurrentProcess() -> Handle;
+        fn SetConsoleCtrlHandler(
+            handler: Option<unsafe extern "system" fn(u32) -> i32>,
+            add: i32,
+        ) -> i32;
+        fn TerminateProcess(process: Handle, exit_code: u32) -> i32;
+        fn SetPriorityClass(process: Handle, class: u32) -> i32;
+        fn SetProcessAffinityMask(process: Handle, mask: usize) -> i32;
+    }
+
+    static CHILD_HANDLE: AtomicPtr<c_void> = AtomicPtr::new(std::ptr::null_mut());
+    static JOB: Once = Once::new();
+
+    unsafe extern "system" fn on_console_event(_event: u32) -> i32 {
+        super::TERMINATION_REQUESTED.store(true, Ordering::SeqCst);
+        let handle = CHILD_HANDLE.load(Ordering::SeqCst);
+        if !handle.is_null() {
+            TerminateProcess(handle, 1);
+        }
+        // handled: the wrapper exits with the child's status once it is gone
+        1
+    }
+
+    /// Puts the wrapper in a Job Object that is killed when its last handle closes, which
+    /// Windows does when the wrapper dies, however it dies. The child, and everything it
+    /// starts, is in the job from the moment it is created, so nothing can escape it.
+    pub fn isolate(_command: &mut Command) {
+        JOB.call_once(|| unsafe {
+            // the job handle is deliberately never closed
+            let job = CreateJobObjectW(std::ptr::null_mut(), std::ptr::null());
+            if job.is_null() {
+                return;
+            }
+            let mut limits = ExtendedLimitInformation::default();
+            limits.basic_limit_information.limit_flags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+            SetInformationJobObject(
+                job,
+                JOB_OBJECT_EXTENDED_LIMIT_INFORMATION_CLASS,
+                &mut limits as *mut ExtendedLimitInformation as *mut c_void,
+                std::mem::size_of::<ExtendedLimitInformation>() as u32,
+            );
+            AssignProcessToJobObject(job, GetCurrentProcess());
+        });
+    }
+
+    /// The child's handle keeps its id from being reused, waiting doesn't release it.
+    pub fn wait_exited(child: &mut Child, block: bool) -> io::Result<bool> {
+        if block {
+            child.wait().map(|_| true)
+        } else {
+            child.try_wait().map(|status| status.is_some())
+        }
+    }
+
+    /// Processes the game left behind are killed with the job when the wrapper exits.
+    pub fn reap(_child: &Child) {}
+
+    pub fn prepare_scheduling(_command: &mut Command, _process: &ProcessConfig) {}
+
+    /// Threads the JVM starts inherit the priority class and affinity of the process.
+    pub fn apply_scheduling(child: &Child, process: &ProcessConfig) {
+        let handle = child.as_raw_handle() as Handle;
+        if let Some(priority) = process.priority {
+            let class = match priority {
+                Priority::Low => 0x40,           // IDLE_PRIORITY_CLASS
+                Priority::BelowNormal => 0x4000, // BELOW_NORMAL_PRIORITY_CLASS
+                Priority::Normal => 0x20,        // NORMAL_PRIORITY_CLASS
+                Priority::AboveNormal => 0x8000, // ABOVE_NORMAL_PRIORITY_CLASS
+                Priority::High => 0x80,          // HIGH_PRIORITY_CLASS
+            };
+            unsafe { SetPriorityClass(handle, class) };
+        }
+        if !process.cpu_affinity.is_empty() {
+            let mut mask = 0usize;
+            for &cpu in &process.cpu_affinity {
+                if cpu < usize::BITS as usize {
+                    mask |= 1 << cpu;
+                } else {
+                    tracing::warn!(
+                        "{}",
+                        crate::i18n::tr!("cpu-affinity-out-of-range", cpu = cpu)
+                    );
+                }
+            }
+            unsafe { SetProcessAffinityMask(handle, mask) };
+        }
+    }
+
+    pub fn forward_termination(child: &Child) {
+        CHILD_HANDLE.store(child.as_raw_handle() as Handle, Ordering::SeqCst);
+        unsafe {
+            SetConsoleCtrlHandler(Some(on_console_event), 1);
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod imp {
+    use crate::config::ProcessConfig;
+    use std::io;
+    use std::process::{Child, Command};
+
+    pub fn isolate(_command: &mut Command) {}
+
+    pub fn wait_exited(child: &mut Child, block: bool) -> io::Result<bool> {
+        if block {
+            child.wait().map(|_| true)
+        } else {
+            child.try_wait().map(|status| status.is_some())
+        }
+    }
+
+    pub fn reap(_child: &Child) {}
+
+    pub fn prepare_scheduling(_command: &mut Command, _process: &ProcessConfig) {}
+
+    pub fn apply_scheduling(_child: &Child, _process: &ProcessConfig) {}
+
+    pub fn forward_termination(_child: &Child) {}
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::{Command, Stdio};
+
+    use super::*;
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn test_watchdog() {
+        let activity = Arc::new(AtomicBool::new(false));
+        let mut child = Command::new("sleep")
+            .arg("5")
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let relays = relay_output(&mut child, &activity, &Relay::default());
+        assert!(matches!(
+            block_on(wait(child, Some(Duration::from_millis(200)), activity)),
+            Err(MmcaiError::GameUnresponsive { .. })
+        ));
+        relays.into_iter().for_each(|relay| relay.join().unwrap());
+
+        let activity = Arc::new(AtomicBool::new(false));
+        let mut child = Command::new("sh")
+            .args(["-c", "echo hello; sleep 0.5"])
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let _relays = relay_output(&mut child, &activity, &Relay::default());
+        let status = block_on(wait(child, Some(Duration::from_millis(300)), activity)).unwrap();
+        assert!(status.success());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_cpu_affinity_out_of_range() {
+        let mut command = Command::new("true");
+        let process = ProcessConfig {
+            cpu_affinity: vec![0, 1 << 20],
+            ..Default::default()
+        };
+        imp::prepare_scheduling(&mut command, &process);
+        assert!(command.status().unwrap().success());
+    }
+
+    #[test]
+    fn test_reap_group() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let pid_file = dir.path().join("pid");
+        let mut command = Command::new("sh");
+        command.args([
+            "-c",
+            &format!("sleep 30 & echo $! > '{}'", pid_file.display()),
+        ]);
+        imp::isolate(&mut command);
+        let child = command.spawn().unwrap();
+        let status = block_on(wait(child, None, Arc::default())).unwrap();
+        assert!(status.success());
+
+        // the sleep the shell left behind went down with it
+        let pid: i32 = std::fs::read_to_string(&pid_file)
+            .unwrap()
+            .trim()
+            .parse()
+            .unwrap();
+        // a zombie still takes signals, until whatever adopted it reaps it
+        let running = || {
+            let zombie = std::fs::read_to_string(format!("/proc/{}/stat", pid))
+                .is_ok_and(|stat| stat.rsplit(") ").next().is_some_and(|s| s.starts_with('Z')));
+            !zombie && unsafe { libc::kill(pid, 0) } == 0
+        };
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while running() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(20));
+        }
+        assert!(!running());
+    }
+
+    #[test]
+    fn test_exec_next() {
+        // `env` stands in for a wrapper: it runs the rest of its command line
+        let process = ProcessConfig {
+            exec_next: vec!["env".into(), "CHAINED=1".into()],
+            ..Default::default()
+        };
+        let jvm_args = [
+            "-c".to_string(),
+            r#"read param; test "$param $CHAINED" = "username 1""#.to_string(),
+        ];
+        let game = Game {
+            java_executable: "sh",
+            jvm_args: &jvm_args,
+            minecraft_params: &["username".to_string()],
+            relay: Relay::default(),
+            watchdog: None,
+            detect_crashes: false,
+            process: &process,
+            working_dir: None,
+            environment: None,
+            timings: None,
+        };
+        assert!(block_on(game.run()).unwrap().status.success());
+    }
+
+    #[test]
+    fn test_exit_code() {
+        assert_eq!(exit_code(ExitStatus::from_raw(0)), 0);
+        assert_eq!(exit_code(ExitStatus::from_raw(3 << 8)), 3);
+        // killed by SIGTERM
+        assert_eq!(exit_code(ExitStatus::from_raw(libc::SIGTERM)), 143);
+    }
+}
@@ -0,0 +1,163 @@
+use std::env;
+
+use crate::params::{Entry, LaunchParams};
+
+/// JVM options whose value is the next argument, so it isn't taken for the main class.
+const OPTIONS_WITH_VALUE: &[&str] = &[
+    "-cp",
+    "-classpath",
+    "--class-path",
+    "-p",
+    "--module-path",
+    "--upgrade-module-path",
+    "--add-modules",
+    "--add-opens",
+    "--add-exports",
+    "--add-reads",
+    "--limit-modules",
+];
+
+/// How the launcher that started the wrapper hands over the game's params.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Launcher {
+    /// Prism and MultiMC start their own Java entry point and stream the params to it
+    /// on stdin.
+    Prism,
+    /// ATLauncher (and launchers like it) puts the game's main class and arguments on
+    /// the command line it gives the wrapper.
+    AtLauncher,
+}
+
+impl Launcher {
+    /// The `--launcher` names.
+    pub fn parse(name: &str) -> Option<Launcher> {
+        match name {
+            "prism" | "multimc" => Some(Launcher::Prism),
+            "atlauncher" => Some(Launcher::AtLauncher),
+            _ => None,
+        }
+    }
+
+    /// Prism and MultiMC describe the instance in `INST_*` variables for the wrapper
+    /// command. Without them, a command line with game arguments after the main class
+    /// is ATLauncher's; Prism's ends with its entry point.
+    pub fn detect(java_command: &[String]) -> Launcher {
+        detect_with(java_command, |name| env::var_os(name).is_some())
+    }
+
+    /// Splits the Java command line (after the Java executable) into the JVM part, up to
+    /// and including the main class, and the game's params as Prism would send them.
+    /// The params are `None` when they come on stdin.
+    pub fn split(self, java_command: &[String]) -> (Vec<String>, Option<Vec<String>>) {
+        match (self, main_class(java_command)) {
+            (Launcher::AtLauncher, Some(index)) => {
+                let (jvm_args, game_args) = java_command.split_at(index + 1);
+                let params = LaunchParams {
+                    entries: game_args.iter().cloned().map(Entry::Param).collect(),
+                    ..Default::default()
+                };
+                (jvm_args.to_vec(), Some(params.to_lines()))
+            }
+            _ => (java_command.to_vec(), None),
+        }
+    }
+}
+
+/// The variables Prism and MultiMC always set for wrapper commands.
+const PRISM_VARIABLES: &[&str] = &["INST_ID", "INST_DIR", "INST_MC_DIR"];
+
+fn detect_with(java_command: &[String], is_set: impl Fn(&str) -> bool) -> Launcher {
+    if PRISM_VARIABLES.iter().any(|name| is_set(name)) {
+        return Launcher::Prism;
+    }
+    match main_class(java_command) {
+        Some(index) if index + 1 < java_command.len() => Launcher::AtLauncher,
+        _ => Launcher::Prism,
+    }
+}
+
+/// The game's command line arguments from the (patched) params.
+pub fn arguments(minecraft_params: &[String]) -> Vec<String> {
+    LaunchParams::parse(minecraft_params)
+        .entries
+        .into_iter()
+        .filter_map(|entry| match entry {
+            Entry::Param(value) => Some(value),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The index of the main class (or of the jar after `-jar`): the first argument that
+/// isn't a JVM option or an option's value.
+fn main_class(java_command: &[String]) -> Option<usize> {
+    let mut args = java_command.iter().enumerate();
+    while let Some((index, arg)) = args.next() {
+        if arg == "-jar" {
+            return args.next().map(|(index, _)| index);
+        }
+        if OPTIONS_WITH_VALUE.contains(&arg.as_str()) {
+            args.next();
+        } else if !arg.starts_with('-') {
+            return Some(index);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_detect() {
+        let prism = args(&[
+            "-Xmx2G",
+            "-cp",
+            "NewLaunch.jar",
+            "org.prismlauncher.EntryPoint",
+        ]);
+        assert_eq!(detect_with(&prism, |_| false), Launcher::Prism);
+        assert_eq!(Launcher::Prism.split(&prism), (prism.clone(), None));
+
+        let atlauncher = args(&[
+            "-Xmx2G",
+            "-cp",
+            "client.jar",
+            "net.minecraft.client.main.Main",
+            "--username",
+            "Player",
+            "--accessToken",
+            "0",
+        ]);
+        assert_eq!(detect_with(&atlauncher, |_| false), Launcher::AtLauncher);
+        // Prism's variables win over the command line
+        assert_eq!(
+            detect_with(&atlauncher, |name| name == "INST_DIR"),
+            Launcher::Prism
+        );
+        let (jvm_args, params) = Launcher::AtLauncher.split(&atlauncher);
+        assert_eq!(jvm_args, atlauncher[..4]);
+        let params = params.unwrap();
+        assert_eq!(params[0], "param --username");
+        assert_eq!(arguments(&params), atlauncher[4..]);
+    }
+
+    #[test]
+    fn test_main_class() {
+        assert_eq!(main_class(&args(&["-jar", "game.jar", "--demo"])), Some(1));
+        assert_eq!(
+            main_class(&args(&[
+                "--add-opens",
+                "java.base/java.lang=ALL-UNNAMED",
+                "Main"
+            ])),
+            Some(2)
+        );
+        assert_eq!(main_class(&args(&["-Xmx2G"])), None);
+    }
+}
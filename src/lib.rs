@@ -0,0 +1,70 @@
+//! The wrapper Prism runs instead of Java: it logs in to the Yggdrasil-compatible API,
+//! patches the launch parameters and starts the game with authlib-injector. The binary in
+//! `main.rs` only parses the options and hands over to [`commands::run`].
+
+use crate::errors::MmcaiError;
+
+mod accounts;
+mod argfile;
+mod audit;
+mod auth;
+pub mod backend;
+pub mod cli;
+pub mod client;
+mod color;
+pub mod commands;
+pub mod config;
+mod crash;
+mod dates;
+mod dialog;
+mod dump;
+mod environment;
+pub mod errors;
+#[cfg(windows)]
+mod eventlog;
+mod events;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod health;
+mod http;
+mod i18n;
+pub mod injector;
+mod instance;
+mod java;
+mod jvm;
+mod launch;
+mod launcher;
+mod logfile;
+mod logging;
+mod metadata;
+mod metrics;
+pub mod model;
+mod netdiag;
+mod notice;
+mod pack;
+pub mod params;
+mod paths;
+mod picker;
+mod pinning;
+mod playtime;
+mod png;
+mod prelaunch;
+mod preview;
+mod prism;
+mod response;
+mod retry;
+mod secrets;
+mod session;
+mod signature;
+mod skin;
+mod skin_library;
+mod skin_sync;
+#[cfg(any(test, feature = "test-server"))]
+pub mod testing;
+mod texture_cache;
+mod timing;
+mod tokens;
+mod trace;
+mod webhook;
+
+pub type Result<T> = std::result::Result<T, MmcaiError>;
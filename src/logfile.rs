@@ -0,0 +1,109 @@
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::errors::MmcaiError;
+use crate::Result;
+
+const LOG_FILE_NAME: &str = "latest.log";
+
+/// `latest.log` with size-based rotation: once it grows past `max_size` bytes it becomes
+/// `latest.1.log` (the previous `latest.1.log` becomes `latest.2.log`, and so on), keeping
+/// at most `max_files` old files. Every launch starts a fresh `latest.log`.
+pub struct RotatingLog {
+    dir: PathBuf,
+    max_size: u64,
+    max_files: u32,
+    file: File,
+    written: u64,
+}
+
+impl RotatingLog {
+    pub fn open(dir: &Path, max_size: u64, max_files: u32) -> Result<RotatingLog> {
+        let open_failed = |source| MmcaiError::OpenLogFileFailed {
+            path: dir.join(LOG_FILE_NAME),
+            source,
+        };
+        fs::create_dir_all(dir).map_err(open_failed)?;
+        let path = dir.join(LOG_FILE_NAME);
+        if fs::metadata(&path).is_ok_and(|metadata| metadata.len() > 0) {
+            rotate(dir, max_files);
+        }
+        let file = File::create(&path).map_err(open_failed)?;
+        Ok(RotatingLog {
+            dir: dir.to_path_buf(),
+            max_size,
+            max_files,
+            file,
+            written: 0,
+        })
+    }
+
+    fn reopen(&mut self) -> io::Result<()> {
+        rotate(&self.dir, self.max_files);
+        self.file = File::create(self.dir.join(LOG_FILE_NAME))?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingLog {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_size {
+            self.reopen()?;
+        }
+        let written = self.file.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn numbered(dir: &Path, number: u32) -> PathBuf {
+    dir.join(format!("latest.{}.log", number))
+}
+
+/// Shifts every log file up by one number, dropping the oldest.
+fn rotate(dir: &Path, max_files: u32) {
+    if max_files == 0 {
+        let _ = fs::remove_file(dir.join(LOG_FILE_NAME));
+        return;
+    }
+    let _ = fs::remove_file(numbered(dir, max_files));
+    for number in (1..max_files).rev() {
+        let _ = fs::rename(numbered(dir, number), numbered(dir, number + 1));
+    }
+    let _ = fs::rename(dir.join(LOG_FILE_NAME), numbered(dir, 1));
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_fs::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn test_rotation() {
+        let dir = TempDir::new().unwrap();
+        let mut log = RotatingLog::open(dir.path(), 10, 2).unwrap();
+        for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+            log.write_all(line.as_bytes()).unwrap();
+        }
+        let read = |name: &str| fs::read_to_string(dir.path().join(name)).unwrap();
+        assert_eq!(read("latest.log"), "fourth\n");
+        assert_eq!(read("latest.1.log"), "third\n");
+        assert_eq!(read("latest.2.log"), "second\n");
+        assert!(!dir.path().join("latest.3.log").exists());
+
+        // a new launch starts a new file
+        drop(log);
+        RotatingLog::open(dir.path(), 10, 2).unwrap();
+        assert_eq!(read("latest.log"), "");
+        assert_eq!(read("latest.1.log"), "fourth\n");
+    }
+}
@@ -0,0 +1,329 @@
+use std::{
+    fmt,
+    fs::{File, OpenOptions},
+    io,
+    path::Path,
+    sync::Mutex,
+};
+
+use serde::Deserialize;
+use tracing::{
+    field::{Field, Visit},
+    level_filters::LevelFilter,
+    Event, Level, Subscriber,
+};
+use tracing_subscriber::{
+    filter::{filter_fn, EnvFilter},
+    fmt::{
+        format::Writer, writer::MakeWriterExt, FmtContext, FormatEvent, FormatFields,
+        FormattedFields,
+    },
+    layer::SubscriberExt,
+    registry::LookupSpan,
+    Layer,
+};
+
+use crate::color::{self, Color, Stream};
+use crate::config::LoggingConfig;
+use crate::errors::MmcaiError;
+use crate::Result;
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// Events with this target are the error the wrapper exits with. `main` prints those
+/// itself, so they only go to the file, journald and the event log.
+pub const FATAL_TARGET: &str = "mmcai_rs::fatal";
+/// Events with this target report that a step succeeded; they are shown in green.
+pub const SUCCESS_TARGET: &str = "mmcai_rs::success";
+
+/// Installs the logger: `[mmcai_rs]` lines on the console, at INFO unless raised with
+/// `-v` (DEBUG) or `-vv` (TRACE), plus the optional `logging.file`, journald and event log.
+pub fn init(verbosity: u8, config: &LoggingConfig) -> Result<()> {
+    color::init(config.color);
+    let console_level = match verbosity {
+        0 => LevelFilter::INFO,
+        1 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    };
+    let sink_level = LevelFilter::from(config.level);
+
+    let console = console_layer(console_level > LevelFilter::INFO)
+        .with_filter(EnvFilter::default().add_directive(console_level.into()))
+        .with_filter(filter_fn(|metadata| metadata.target() != FATAL_TARGET));
+
+    let file = match &config.file {
+        Some(path) => {
+            let file = Mutex::new(open(path)?);
+            let layer = tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(file);
+            Some(match config.format {
+                LogFormat::Text => layer.boxed(),
+                LogFormat::Json => layer.json().flatten_event(true).boxed(),
+            })
+        }
+        None => None,
+    };
+
+    #[cfg(target_os = "linux")]
+    let journald = if config.journald {
+        let layer = tracing_journald::layer().map_err(MmcaiError::ConnectJournaldFailed)?;
+        Some(layer.with_syslog_identifier("mmcai_rs".to_string()))
+    } else {
+        None
+    };
+    #[cfg(not(target_os = "linux"))]
+    let journald: Option<tracing_subscriber::layer::Identity> = None;
+
+    #[cfg(windows)]
+    let event_log = if config.event_log {
+        Some(crate::eventlog::EventLog::open().map_err(MmcaiError::OpenEventLogFailed)?)
+    } else {
+        None
+    };
+    #[cfg(not(windows))]
+    let event_log: Option<tracing_subscriber::layer::Identity> = None;
+
+    let subscriber = tracing_subscriber::registry()
+        .with(console)
+        .with(file.with_filter(sink_level))
+        .with(journald.with_filter(sink_level))
+        .with(event_log.with_filter(sink_level));
+    // a logger can only be installed once; tests may try more than that
+    let _ = tracing::subscriber::set_global_default(subscriber);
+    #[cfg(not(target_os = "linux"))]
+    if config.journald {
+        tracing::warn!("{}", crate::i18n::tr!("journald-unsupported"));
+    }
+    #[cfg(not(windows))]
+    if config.event_log {
+        tracing::warn!("{}", crate::i18n::tr!("event-log-unsupported"));
+    }
+    Ok(())
+}
+
+fn open(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|source| MmcaiError::OpenWrapperLogFailed {
+            path: path.to_path_buf(),
+            source,
+        })
+}
+
+/// The level of the log sinks, as written in the config.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<LogLevel> for LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Error => LevelFilter::ERROR,
+            LogLevel::Warn => LevelFilter::WARN,
+            LogLevel::Info => LevelFilter::INFO,
+            LogLevel::Debug => LevelFilter::DEBUG,
+            LogLevel::Trace => LevelFilter::TRACE,
+        }
+    }
+}
+
+/// Errors go to stderr, everything else to stdout, like the launcher expects.
+fn console_layer<S>(verbose: bool) -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    tracing_subscriber::fmt::layer()
+        .event_format(ConsoleFormat { verbose })
+        .with_writer(io::stderr.with_max_level(Level::ERROR).or_else(io::stdout))
+}
+
+/// Console lines look like they always did; with `-v` the level, the spans with their
+/// fields and the event's fields are shown too.
+struct ConsoleFormat {
+    verbose: bool,
+}
+
+impl<S, N> FormatEvent<S, N> for ConsoleFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        context: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let metadata = event.metadata();
+        let mut line = String::from("[mmcai_rs] ");
+        if self.verbose {
+            line.push_str(&format!("{:<5} ", metadata.level()));
+            let mut spans = Vec::new();
+            context.visit_spans(|span| {
+                let extensions = span.extensions();
+                let fields = extensions
+                    .get::<FormattedFields<N>>()
+                    .filter(|fields| !fields.is_empty());
+                spans.push(match fields {
+                    Some(fields) => format!("{}{{{}}}", span.name(), fields),
+                    None => span.name().to_string(),
+                });
+                Ok::<(), fmt::Error>(())
+            })?;
+            if !spans.is_empty() {
+                line.push_str(&spans.join(":"));
+                line.push_str(": ");
+            }
+            context.format_fields(Writer::new(&mut line), event)?;
+        } else {
+            line.push_str(match *metadata.level() {
+                Level::ERROR => "ERROR: ",
+                Level::WARN => "WARNING: ",
+                _ => "",
+            });
+            let mut message = MessageVisitor(&mut line);
+            event.record(&mut message);
+        }
+
+        let stream = match *metadata.level() {
+            Level::ERROR => Stream::Stderr,
+            _ => Stream::Stdout,
+        };
+        match console_color(*metadata.level(), metadata.target()) {
+            Some(color) => writeln!(writer, "{}", color::paint(&line, color, stream)),
+            None => writeln!(writer, "{}", line),
+        }
+    }
+}
+
+/// Appends only the message, for the plain console lines.
+struct MessageVisitor<'a>(&'a mut String);
+
+impl Visit for MessageVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.0.push_str(value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.0.push_str(&format!("{:?}", value));
+        }
+    }
+}
+
+fn console_color(level: Level, target: &str) -> Option<Color> {
+    match level {
+        Level::ERROR => Some(Color::Red),
+        Level::WARN => Some(Color::Yellow),
+        _ if target == SUCCESS_TARGET => Some(Color::Green),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde_json::Value;
+
+    use super::*;
+
+    /// Collects what a layer writes, instead of a file or the console.
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(bytes)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Buffer {
+        fn text(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    fn console(verbose: bool) -> String {
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .event_format(ConsoleFormat { verbose })
+            .with_writer(move || writer.clone())
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("auth", account = "steve");
+            let _entered = span.enter();
+            tracing::info!(uuid = "abc", "Successfully authenticated as Steve");
+            tracing::warn!("careful");
+        });
+        buffer.text()
+    }
+
+    #[test]
+    fn test_format_console() {
+        assert_eq!(
+            console(false),
+            "[mmcai_rs] Successfully authenticated as Steve\n[mmcai_rs] WARNING: careful\n"
+        );
+        assert_eq!(
+            console(true).lines().next().unwrap(),
+            "[mmcai_rs] INFO  auth{account=\"steve\"}: Successfully authenticated as Steve uuid=\"abc\""
+        );
+    }
+
+    #[test]
+    fn test_console_color() {
+        assert_eq!(console_color(Level::ERROR, "mmcai_rs"), Some(Color::Red));
+        assert_eq!(console_color(Level::WARN, "mmcai_rs"), Some(Color::Yellow));
+        assert_eq!(
+            console_color(Level::INFO, SUCCESS_TARGET),
+            Some(Color::Green)
+        );
+        assert_eq!(console_color(Level::INFO, "mmcai_rs"), None);
+    }
+
+    #[test]
+    fn test_format_json_file() {
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .json()
+            .flatten_event(true)
+            .with_writer(move || writer.clone())
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("launch", instance = "HT8");
+            let _entered = span.enter();
+            tracing::info!(attempt = 1, "launching");
+        });
+
+        let json: Value = serde_json::from_str(&buffer.text()).unwrap();
+        assert_eq!(json["level"], "INFO");
+        assert_eq!(json["message"], "launching");
+        assert_eq!(json["attempt"], 1);
+        assert_eq!(json["span"]["name"], "launch");
+        assert_eq!(json["span"]["instance"], "HT8");
+    }
+}
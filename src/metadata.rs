@@ -0,0 +1,498 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use reqwest::{
+    header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, LOCATION},
+    Response, StatusCode, Url,
+};
+use ring::digest::{digest, SHA256};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use tracing::{info, warn};
+
+use crate::config::Config;
+use crate::errors::MmcaiError;
+use crate::http::HttpClient;
+use crate::i18n::tr;
+use crate::{dates, paths, pinning, retry, Result};
+
+const API_LOCATION_HEADER: &str = "x-authlib-injector-api-location";
+const MAX_LOCATION_HOPS: usize = 5;
+/// How long a resolved API root is trusted before it is resolved again, in seconds.
+const LOCATION_MAX_AGE: u64 = 7 * 24 * 3600;
+
+/// Where an API URL was last found to lead.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Location {
+    root: String,
+    resolved_at: u64,
+}
+
+impl Location {
+    fn new(root: String) -> Location {
+        Location {
+            root,
+            resolved_at: dates::unix_now(),
+        }
+    }
+}
+
+/// The API metadata as last fetched from a server, kept to revalidate it cheaply and to
+/// survive the metadata endpoint being briefly unavailable.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct CachedMetadata {
+    api_url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    body: String,
+}
+
+/// Resolves the canonical API root of `api_url` by following the authlib-injector
+/// "API Location Indication" (the `X-Authlib-Injector-API-Location` header) and redirects.
+///
+/// The outcome is remembered in the cache for a week, so only the first launch against a
+/// URL pays for the extra request. Later moves are picked up by [`fetch_metadata`], and a
+/// remembered root that stops working is dropped with [`forget_api_root`].
+pub async fn resolve_api_root(http: &HttpClient, api_url: &str) -> Result<String> {
+    let mut locations = load_locations();
+    if let Some(root) = lookup_location(&locations, api_url, dates::unix_now()) {
+        return Ok(root);
+    }
+
+    let mut current = normalize_url(api_url);
+    for _ in 0..MAX_LOCATION_HOPS {
+        let response = http
+            .send(|client| client.get(&current))
+            .await
+            .map_err(|e| pinning::classify(e, MmcaiError::YggdrasilHelloFailed))?;
+        match next_location(&current, &response) {
+            Some(next) => current = next,
+            None => break,
+        }
+    }
+
+    if current != normalize_url(api_url) {
+        info!("{}", tr!("api-location", url = api_url, location = current));
+    }
+    locations.insert(api_url.to_string(), Location::new(current.clone()));
+    store_locations(&locations);
+    Ok(current)
+}
+
+/// Drops the remembered API root of `api_url`, so the next [`resolve_api_root`] follows
+/// the server's indication again. Returns whether the remembered root led elsewhere,
+/// i.e. whether resolving again can make a difference.
+pub fn forget_api_root(api_url: &str) -> bool {
+    let mut locations = load_locations();
+    match locations.remove(api_url) {
+        Some(location) => {
+            store_locations(&locations);
+            location.root != normalize_url(api_url)
+        }
+        None => false,
+    }
+}
+
+/// Where the server says its API lives instead of `current`, if anywhere else.
+///
+/// Only a move within the same origin or to an `https` URL is followed: the password is
+/// sent to wherever this leads, and a plain `http` server elsewhere is not to be trusted
+/// with it on the word of a header.
+fn next_location(current: &str, response: &Response) -> Option<String> {
+    let header = |name| response.headers().get(name)?.to_str().ok();
+    let target = match header(API_LOCATION_HEADER) {
+        Some(location) => location,
+        None if response.status().is_redirection() => header(LOCATION.as_str())?,
+        None => return None,
+    };
+    let current_url = Url::parse(current).ok()?;
+    let next = current_url.join(target).ok()?;
+    if next.origin() != current_url.origin() && next.scheme() != "https" {
+        warn!("{}", tr!("api-location-insecure", location = next.as_str()));
+        return None;
+    }
+    let next = next.to_string();
+    (next != current).then_some(next)
+}
+
+fn normalize_url(url: &str) -> String {
+    Url::parse(url).map_or_else(|_| url.to_string(), |url| url.to_string())
+}
+
+/// Follows remembered locations, which may form a chain when an API moved more than once.
+/// Locations older than [`LOCATION_MAX_AGE`] are ignored.
+fn lookup_location(
+    locations: &BTreeMap<String, Location>,
+    api_url: &str,
+    now: u64,
+) -> Option<String> {
+    let fresh = |url: &str| {
+        locations
+            .get(url)
+            .filter(|location| now < location.resolved_at.saturating_add(LOCATION_MAX_AGE))
+    };
+    let mut current = &fresh(api_url)?.root;
+    for _ in 0..MAX_LOCATION_HOPS {
+        match fresh(current) {
+            Some(next) if next.root != *current => current = &next.root,
+            _ => break,
+        }
+    }
+    Some(current.clone())
+}
+
+fn locations_path() -> Option<PathBuf> {
+    Some(paths::cache_dir()?.join("api_locations.json"))
+}
+
+fn load_locations() -> BTreeMap<String, Location> {
+    locations_path()
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|json| serde_json::from_slice(&json).ok())
+        .unwrap_or_default()
+}
+
+fn store_locations(locations: &BTreeMap<String, Location>) {
+    if let (Some(path), Ok(json)) = (locations_path(), serde_json::to_vec(locations)) {
+        write_cache_file(&path, json);
+    }
+}
+
+/// Fetches the API metadata for `-Dauthlibinjector.yggdrasil.prefetched`, revalidating
+/// the cached copy with a conditional request and falling back to it when the request fails.
+pub async fn fetch_metadata(http: &HttpClient, api_url: &str, config: &Config) -> Result<String> {
+    let cache_path = cache_path(api_url);
+    let cached = cache_path.as_ref().and_then(|path| load(path, api_url));
+
+    let result = http
+        .send(|client| {
+            let mut request = client.get(api_url);
+            if let Some(cached) = &cached {
+                if let Some(etag) = &cached.etag {
+                    request = request.header(IF_NONE_MATCH, etag);
+                }
+                if let Some(last_modified) = &cached.last_modified {
+                    request = request.header(IF_MODIFIED_SINCE, last_modified);
+                }
+            }
+            request
+        })
+        .await;
+
+    let response = match (result, cached) {
+        (Ok(response), cached) => match (response.status(), cached) {
+            (StatusCode::NOT_MODIFIED, Some(cached)) => return Ok(cached.body),
+            (status, Some(cached))
+                if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS =>
+            {
+                return Ok(use_stale(cached, &status.to_string()));
+            }
+            _ => response,
+        },
+        (Err(e), cached) => match (
+            pinning::classify(e, MmcaiError::YggdrasilHelloFailed),
+            cached,
+        ) {
+            (MmcaiError::YggdrasilHelloFailed(e), Some(cached)) if !e.is_decode() => {
+                // without a connection there was no certificate to check the pins on,
+                // and the cached copy mustn't vouch for the server
+                if pinning::is_enabled(&config.http) {
+                    pinning::check_server(http, api_url).await?;
+                }
+                return Ok(use_stale(cached, &e.to_string()));
+            }
+            (e, _) => return Err(e),
+        },
+    };
+
+    retry::check_rate_limit(&response)?;
+
+    if let Some(next) = next_location(api_url, &response) {
+        info!("{}", tr!("api-moved", location = next));
+        let mut locations = load_locations();
+        locations.insert(api_url.to_string(), Location::new(next));
+        store_locations(&locations);
+    }
+
+    let status = response.status();
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+    let (etag, last_modified) = (header(ETAG), header(LAST_MODIFIED));
+    let body = response
+        .text()
+        .await
+        .map_err(MmcaiError::YggdrasilHelloFailed)?;
+
+    validate_metadata(&body).map_err(|reason| MmcaiError::InvalidMetadata {
+        api_url: api_url.to_string(),
+        reason,
+        excerpt: excerpt(&body),
+    })?;
+
+    if status == StatusCode::OK {
+        if let Some(path) = cache_path {
+            store(
+                &path,
+                &CachedMetadata {
+                    api_url: api_url.to_string(),
+                    etag,
+                    last_modified,
+                    body: body.clone(),
+                },
+            );
+        }
+    }
+
+    Ok(body)
+}
+
+/// Makes sure the server sent authlib-injector API metadata and not, say, a Cloudflare
+/// error page or a captive portal, which would otherwise only break inside the game.
+fn validate_metadata(body: &str) -> std::result::Result<(), String> {
+    let json: Value =
+        serde_json::from_str(body).map_err(|_| "the response is not JSON".to_string())?;
+    let object = json
+        .as_object()
+        .ok_or_else(|| "the response is not a JSON object".to_string())?;
+    if !object
+        .get("signaturePublickey")
+        .is_some_and(Value::is_string)
+    {
+        return Err("signaturePublickey is missing".to_string());
+    }
+    if !object.get("skinDomains").is_some_and(Value::is_array) {
+        return Err("skinDomains is missing".to_string());
+    }
+    Ok(())
+}
+
+fn excerpt(body: &str) -> String {
+    const MAX_LENGTH: usize = 200;
+    let body = body.trim();
+    match body.char_indices().nth(MAX_LENGTH) {
+        Some((cut, _)) => format!("{}...", &body[..cut]),
+        None => body.to_string(),
+    }
+}
+
+/// The last metadata successfully fetched from `api_url`, if any.
+pub fn cached_metadata(api_url: &str) -> Option<String> {
+    let path = cache_path(api_url)?;
+    load(&path, api_url).map(|cached| cached.body)
+}
+
+fn use_stale(cached: CachedMetadata, reason: &str) -> String {
+    warn!("{}", tr!("metadata-cached", reason = reason));
+    cached.body
+}
+
+fn cache_path(api_url: &str) -> Option<PathBuf> {
+    let hash: String = digest(&SHA256, api_url.as_bytes())
+        .as_ref()
+        .iter()
+        .take(16)
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    Some(
+        paths::cache_dir()?
+            .join("metadata")
+            .join(format!("{}.json", hash)),
+    )
+}
+
+fn load(path: &Path, api_url: &str) -> Option<CachedMetadata> {
+    let cached: CachedMetadata = serde_json::from_slice(&fs::read(path).ok()?).ok()?;
+    // guards against hash collisions and hand-copied cache files
+    (cached.api_url == api_url).then_some(cached)
+}
+
+fn store(path: &Path, metadata: &CachedMetadata) {
+    if let Ok(json) = serde_json::to_vec(metadata) {
+        write_cache_file(path, json);
+    }
+}
+
+/// Caching is an optimization, failing to write the cache is not worth failing the launch.
+fn write_cache_file(path: &Path, contents: Vec<u8>) {
+    if let Some(parent) = path.parent() {
+        let _ = paths::create_private_dir(parent).and_then(|_| fs::write(path, contents));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_fs::prelude::PathChild;
+
+    use super::*;
+
+    #[test]
+    fn test_validate_metadata() {
+        let valid = r#"{
+            "meta": {"serverName": "Marallys"},
+            "skinDomains": ["marallys.com"],
+            "signaturePublickey": "-----BEGIN PUBLIC KEY-----\n...\n-----END PUBLIC KEY-----\n"
+        }"#;
+        assert!(validate_metadata(valid).is_ok());
+        assert!(validate_metadata("<html><title>Just a moment...</title></html>").is_err());
+        assert!(validate_metadata("[]").is_err());
+        assert!(validate_metadata(r#"{"skinDomains": []}"#).is_err());
+        assert!(validate_metadata(r#"{"signaturePublickey": "key"}"#).is_err());
+    }
+
+    #[test]
+    fn test_excerpt() {
+        assert_eq!(excerpt("  short  "), "short");
+        let long = "x".repeat(500);
+        assert_eq!(excerpt(&long).len(), 203);
+    }
+
+    #[test]
+    fn test_next_location() {
+        let response = |status: u16, headers: &[(&str, &str)]| {
+            let mut builder = http::Response::builder().status(status);
+            for (name, value) in headers {
+                builder = builder.header(*name, *value);
+            }
+            Response::from(builder.body("").unwrap())
+        };
+        let current = "https://example.com/";
+
+        assert_eq!(next_location(current, &response(200, &[])), None);
+        assert_eq!(
+            next_location(
+                current,
+                &response(
+                    200,
+                    &[("X-Authlib-Injector-API-Location", "/api/yggdrasil/")]
+                )
+            ),
+            Some("https://example.com/api/yggdrasil/".to_string())
+        );
+        assert_eq!(
+            next_location(
+                current,
+                &response(301, &[("Location", "https://auth.example.com/")])
+            ),
+            Some("https://auth.example.com/".to_string())
+        );
+        // a server pointing at itself is not a move
+        assert_eq!(
+            next_location(
+                current,
+                &response(200, &[("X-Authlib-Injector-API-Location", "/")])
+            ),
+            None
+        );
+        // Location only matters on redirects
+        assert_eq!(
+            next_location(current, &response(201, &[("Location", "/elsewhere")])),
+            None
+        );
+        // no following a header to plain http on another host
+        assert_eq!(
+            next_location(
+                current,
+                &response(302, &[("Location", "http://evil.example/api/")])
+            ),
+            None
+        );
+        assert_eq!(
+            next_location(
+                "http://localhost:8080/",
+                &response(302, &[("Location", "/api/yggdrasil/")])
+            ),
+            Some("http://localhost:8080/api/yggdrasil/".to_string())
+        );
+    }
+
+    #[test]
+    fn test_lookup_location() {
+        let location = |root: &str, resolved_at| Location {
+            root: root.to_string(),
+            resolved_at,
+        };
+        let locations = BTreeMap::from([
+            ("http://a/".to_string(), location("http://b/", 1000)),
+            ("http://b/".to_string(), location("http://c/", 1000)),
+            ("http://loop/".to_string(), location("http://loop/", 1000)),
+            ("http://old/".to_string(), location("http://a/", 0)),
+        ]);
+        let now = 2000;
+        assert_eq!(
+            lookup_location(&locations, "http://a/", now),
+            Some("http://c/".to_string())
+        );
+        assert_eq!(
+            lookup_location(&locations, "http://loop/", now),
+            Some("http://loop/".to_string())
+        );
+        assert_eq!(lookup_location(&locations, "http://unknown/", now), None);
+        assert_eq!(
+            lookup_location(&locations, "http://old/", LOCATION_MAX_AGE),
+            None
+        );
+    }
+
+    #[test]
+    fn test_stale_cache_with_pins() {
+        // a port that was just free is very likely still closed
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let api_url = format!("http://{}/api/yggdrasil", listener.local_addr().unwrap());
+        drop(listener);
+
+        let body = r#"{"skinDomains": [], "signaturePublickey": "key"}"#;
+        store(
+            &cache_path(&api_url).unwrap(),
+            &CachedMetadata {
+                api_url: api_url.clone(),
+                etag: None,
+                last_modified: None,
+                body: body.to_string(),
+            },
+        );
+
+        let mut config = Config::default();
+        config.retry.attempts = 1;
+        let http = HttpClient::new(&config, None).unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let fetch = |config: &Config| runtime.block_on(fetch_metadata(&http, &api_url, config));
+
+        assert_eq!(fetch(&config).unwrap(), body);
+        config.http.pinned_public_keys =
+            vec!["sha256//AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=".into()];
+        assert!(matches!(
+            fetch(&config),
+            Err(MmcaiError::YggdrasilHelloFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_cache_round_trip() {
+        let temp_dir = assert_fs::TempDir::new().unwrap();
+        let path = temp_dir.child("metadata").child("entry.json").to_path_buf();
+        let metadata = CachedMetadata {
+            api_url: "https://example.com/api/yggdrasil".to_string(),
+            etag: Some("\"abc\"".to_string()),
+            last_modified: None,
+            body: "{\"meta\":{}}".to_string(),
+        };
+
+        assert_eq!(load(&path, &metadata.api_url), None);
+        store(&path, &metadata);
+        assert_eq!(load(&path, &metadata.api_url), Some(metadata));
+        assert_eq!(load(&path, "https://other.example.com/api"), None);
+        temp_dir.close().unwrap();
+    }
+}
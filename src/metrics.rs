@@ -0,0 +1,142 @@
+use std::{
+    collections::BTreeMap, fmt::Write as _, fs, path::Path, process::ExitStatus, time::Duration,
+};
+
+use tracing::warn;
+
+use crate::dates;
+use crate::i18n::tr;
+use crate::timing::Stage;
+use crate::Result;
+
+/// Counters carried over from the previous run, read back from the metrics file.
+#[derive(Debug, Default, PartialEq)]
+struct Counters {
+    launches: u64,
+    /// Failures by category: the error categories, plus `game_exit` for a game that
+    /// exited abnormally.
+    failures: BTreeMap<String, u64>,
+}
+
+/// Updates the node_exporter textfile-collector file (`metrics.textfile`) after a run.
+/// Failing to do so is reported but doesn't change the wrapper's exit code.
+pub fn update(path: &Path, result: &Result<ExitStatus>, stages: &BTreeMap<Stage, Duration>) {
+    let mut counters = fs::read_to_string(path)
+        .map(|previous| parse(&previous))
+        .unwrap_or_default();
+    counters.launches += 1;
+    let failure = match result {
+        Ok(status) if status.success() => None,
+        Ok(_) => Some("game_exit"),
+        Err(e) => Some(e.category()),
+    };
+    if let Some(category) = failure {
+        *counters.failures.entry(category.to_string()).or_default() += 1;
+    }
+
+    let contents = format(&counters, stages, dates::unix_now());
+    // the collector may read at any moment, so the file is replaced in one step
+    let temporary = path.with_extension("prom.tmp");
+    let written = fs::write(&temporary, contents).and_then(|_| fs::rename(&temporary, path));
+    if let Err(e) = written {
+        let path = format!("{:?}", path);
+        warn!("{}", tr!("metrics-write-failed", path = path, error = e));
+    }
+}
+
+fn parse(previous: &str) -> Counters {
+    let mut counters = Counters::default();
+    for line in previous.lines().filter(|line| !line.starts_with('#')) {
+        let Some((name, value)) = line.rsplit_once(' ') else {
+            continue;
+        };
+        let Ok(value) = value.parse::<u64>() else {
+            continue;
+        };
+        if name == "mmcai_launches_total" {
+            counters.launches = value;
+        } else if let Some(category) = name
+            .strip_prefix("mmcai_launch_failures_total{category=\"")
+            .and_then(|rest| rest.strip_suffix("\"}"))
+        {
+            counters.failures.insert(category.to_string(), value);
+        }
+    }
+    counters
+}
+
+fn format(counters: &Counters, stages: &BTreeMap<Stage, Duration>, now: u64) -> String {
+    let mut metrics = String::new();
+    let mut put = |line: String| {
+        writeln!(metrics, "{}", line).expect("writing to a String cannot fail");
+    };
+    put("# HELP mmcai_launches_total Runs of the wrapper.".into());
+    put("# TYPE mmcai_launches_total counter".into());
+    put(format!("mmcai_launches_total {}", counters.launches));
+    put("# HELP mmcai_launch_failures_total Failed runs by category.".into());
+    put("# TYPE mmcai_launch_failures_total counter".into());
+    for (category, count) in &counters.failures {
+        put(format!(
+            "mmcai_launch_failures_total{{category=\"{}\"}} {}",
+            category, count
+        ));
+    }
+    put("# HELP mmcai_stage_seconds Time spent in each stage of the last run.".into());
+    put("# TYPE mmcai_stage_seconds gauge".into());
+    for (stage, duration) in stages {
+        put(format!(
+            "mmcai_stage_seconds{{stage=\"{}\"}} {:.3}",
+            stage.name(),
+            duration.as_secs_f64()
+        ));
+    }
+    put("# HELP mmcai_last_run_timestamp_seconds When the last run ended.".into());
+    put("# TYPE mmcai_last_run_timestamp_seconds gauge".into());
+    put(format!("mmcai_last_run_timestamp_seconds {}", now));
+    metrics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_and_parse() {
+        let counters = Counters {
+            launches: 7,
+            failures: BTreeMap::from([("auth".into(), 2), ("game_exit".into(), 1)]),
+        };
+        let stages = BTreeMap::from([
+            (Stage::Signin, Duration::from_millis(412)),
+            (Stage::Spawn, Duration::from_millis(9)),
+        ]);
+        let metrics = format(&counters, &stages, 1700000000);
+        assert!(metrics.contains("\nmmcai_launches_total 7\n"));
+        assert!(metrics.contains("\nmmcai_launch_failures_total{category=\"auth\"} 2\n"));
+        assert!(metrics.contains("\nmmcai_stage_seconds{stage=\"signin\"} 0.412\n"));
+        assert!(metrics.ends_with("mmcai_last_run_timestamp_seconds 1700000000\n"));
+
+        // counters survive the round trip, gauges are replaced on every run
+        assert_eq!(parse(&metrics), counters);
+    }
+
+    #[test]
+    fn test_update() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let path = dir.path().join("mmcai.prom");
+        update(
+            &path,
+            &Err(crate::errors::MmcaiError::Other),
+            &BTreeMap::new(),
+        );
+        update(
+            &path,
+            &Err(crate::errors::MmcaiError::Other),
+            &BTreeMap::new(),
+        );
+
+        let counters = parse(&fs::read_to_string(&path).unwrap());
+        assert_eq!(counters.launches, 2);
+        assert_eq!(counters.failures["internal"], 2);
+    }
+}
@@ -0,0 +1,212 @@
+use std::{net::SocketAddr, time::Duration};
+
+use reqwest::Url;
+use tokio::{net::TcpStream, time::timeout};
+use tracing::warn;
+
+use crate::health::{self, Problem};
+use crate::http::HttpClient;
+use crate::i18n::tr;
+
+/// Each probe gets this long, so the whole diagnosis stays quick.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Layer {
+    Dns,
+    Tcp,
+    Tls,
+    Http,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Probe {
+    pub layer: Layer,
+    /// What was found, or why the layer failed.
+    pub result: Result<String, String>,
+}
+
+/// Which network layers work on the way to the auth server, to tell a problem with the
+/// player's connection from one with the server.
+#[derive(Debug, PartialEq)]
+pub struct Diagnosis {
+    pub host: String,
+    pub port: u16,
+    pub probes: Vec<Probe>,
+}
+
+impl Diagnosis {
+    /// The first layer that failed; the ones above it weren't probed.
+    pub fn failed_layer(&self) -> Option<Layer> {
+        self.probes
+            .iter()
+            .find(|probe| probe.result.is_err())
+            .map(|probe| probe.layer)
+    }
+
+    pub fn conclusion(&self) -> String {
+        match self.failed_layer() {
+            Some(Layer::Dns) => tr!("netdiag-dns-failed"),
+            Some(Layer::Tcp) => tr!("netdiag-tcp-failed"),
+            Some(Layer::Tls) => tr!("netdiag-tls-failed"),
+            Some(Layer::Http) => tr!("netdiag-http-failed"),
+            None => tr!("netdiag-all-ok"),
+        }
+    }
+
+    pub fn print(&self) {
+        warn!("{}", tr!("netdiag-header", host = self.host));
+        for probe in &self.probes {
+            let name = match probe.layer {
+                Layer::Dns => tr!("netdiag-dns"),
+                Layer::Tcp => tr!("netdiag-tcp", port = self.port),
+                Layer::Tls => tr!("netdiag-tls"),
+                Layer::Http => tr!("netdiag-http"),
+            };
+            let result = match &probe.result {
+                Ok(found) if found.is_empty() => tr!("netdiag-ok"),
+                Ok(found) => format!("{} ({})", tr!("netdiag-ok"), found),
+                Err(error) => format!("{} ({})", tr!("netdiag-failed"), error),
+            };
+            warn!("  {}: {}", name, result);
+        }
+        warn!("{}", self.conclusion());
+    }
+}
+
+/// Probes the layers one by one, stopping at the first that fails. Runs directly, so
+/// the DNS and TCP probes don't go through a configured proxy.
+pub async fn diagnose(http: &HttpClient, api_url: &str) -> Option<Diagnosis> {
+    let url = Url::parse(api_url).ok()?;
+    let host = url.host_str()?.to_string();
+    let port = url.port_or_known_default()?;
+    let mut diagnosis = Diagnosis {
+        host: host.clone(),
+        port,
+        probes: Vec::new(),
+    };
+    let mut probe = |layer, result: Result<String, String>| {
+        let ok = result.is_ok();
+        diagnosis.probes.push(Probe { layer, result });
+        ok
+    };
+
+    let addresses = resolve(&host, port).await;
+    let resolved = addresses.as_ref().map(|addresses| {
+        addresses
+            .iter()
+            .map(|address| address.ip().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    });
+    if !probe(Layer::Dns, resolved.map_err(Clone::clone)) {
+        return Some(diagnosis);
+    }
+    let connected = connect(addresses.as_deref().unwrap_or_default()).await;
+    if !probe(Layer::Tcp, connected.map(|address| address.to_string())) {
+        return Some(diagnosis);
+    }
+
+    let root = format!("{}://{}:{}/", url.scheme(), host, port);
+    let answer = http
+        .send_once(|client| client.get(&root).timeout(PROBE_TIMEOUT))
+        .await;
+    let problem = answer.as_ref().err().map(health::classify_error);
+    if url.scheme() == "https" {
+        let tls = match &problem {
+            Some(Problem::Tls) => Err(answer.as_ref().unwrap_err().to_string()),
+            _ => Ok(String::new()),
+        };
+        if !probe(Layer::Tls, tls) {
+            return Some(diagnosis);
+        }
+    }
+    probe(
+        Layer::Http,
+        match answer {
+            Ok(response) => Ok(response.status().to_string()),
+            Err(e) => Err(problem.map_or_else(|| e.to_string(), |problem| problem.to_string())),
+        },
+    );
+    Some(diagnosis)
+}
+
+async fn resolve(host: &str, port: u16) -> Result<Vec<SocketAddr>, String> {
+    match timeout(PROBE_TIMEOUT, tokio::net::lookup_host((host, port))).await {
+        Ok(Ok(addresses)) => {
+            let addresses: Vec<SocketAddr> = addresses.collect();
+            if addresses.is_empty() {
+                Err(tr!("netdiag-no-addresses"))
+            } else {
+                Ok(addresses)
+            }
+        }
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err(tr!("netdiag-timeout")),
+    }
+}
+
+/// Connects to the addresses in turn and returns the first that accepts.
+async fn connect(addresses: &[SocketAddr]) -> Result<SocketAddr, String> {
+    let mut error = tr!("netdiag-no-addresses");
+    for address in addresses {
+        match timeout(PROBE_TIMEOUT, TcpStream::connect(address)).await {
+            Ok(Ok(_)) => return Ok(*address),
+            Ok(Err(e)) => error = format!("{}: {}", address, e),
+            Err(_) => error = format!("{}: {}", address, tr!("netdiag-timeout")),
+        }
+    }
+    Err(error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connection_refused() {
+        // a port that was just free is very likely still closed
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let config = crate::config::Config::default();
+        let http = HttpClient::new(&config, None).unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let api_url = format!("http://127.0.0.1:{}/api", port);
+        let diagnosis = runtime.block_on(diagnose(&http, &api_url)).unwrap();
+        assert_eq!(diagnosis.failed_layer(), Some(Layer::Tcp));
+        assert_eq!(
+            diagnosis.probes[0].result,
+            Ok("127.0.0.1".to_string()),
+            "an IP address resolves to itself"
+        );
+    }
+
+    #[test]
+    fn test_conclusion() {
+        let diagnosis = Diagnosis {
+            host: "auth.example.com".into(),
+            port: 443,
+            probes: vec![
+                Probe {
+                    layer: Layer::Dns,
+                    result: Ok("192.0.2.1".into()),
+                },
+                Probe {
+                    layer: Layer::Tcp,
+                    result: Ok("192.0.2.1:443".into()),
+                },
+                Probe {
+                    layer: Layer::Tls,
+                    result: Err("certificate has expired".into()),
+                },
+            ],
+        };
+        assert_eq!(diagnosis.failed_layer(), Some(Layer::Tls));
+        assert_eq!(diagnosis.conclusion(), tr!("netdiag-tls-failed"));
+    }
+}
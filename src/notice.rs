@@ -0,0 +1,101 @@
+use serde_json::Value;
+
+use tracing::{info, warn};
+
+use crate::http::HttpClient;
+use crate::i18n::tr;
+
+/// A message the server wants players to see, e.g. announced downtime.
+#[derive(Debug, Default, PartialEq)]
+pub struct Notice {
+    pub message: Option<String>,
+    pub maintenance: bool,
+}
+
+impl Notice {
+    pub fn print(&self) {
+        if self.maintenance {
+            warn!("{}", tr!("server-maintenance"));
+        }
+        if let Some(message) = &self.message {
+            for line in message.lines() {
+                info!("{}", tr!("server-notice", line = line));
+            }
+        }
+    }
+}
+
+/// Reads a notice from the `meta` section of the API metadata. authlib-injector doesn't
+/// define one, so the common `notice`/`motd` and `maintenance` spellings are accepted.
+pub fn from_metadata(metadata: &str) -> Option<Notice> {
+    let json: Value = serde_json::from_str(metadata).ok()?;
+    from_json(json.get("meta")?)
+}
+
+/// Fetches the announcements endpoint (`server.announcements_url`). It may answer with
+/// JSON like `{"message": "...", "maintenance": true}` or with plain text.
+///
+/// Announcements are informational only, so any failure is ignored.
+pub async fn fetch(http: &HttpClient, url: &str) -> Option<Notice> {
+    let response = http.send(|client| client.get(url)).await.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    let body = response.text().await.ok()?;
+    match serde_json::from_str::<Value>(&body) {
+        Ok(json) => from_json(&json),
+        Err(_) => {
+            let message = body.trim();
+            (!message.is_empty()).then(|| Notice {
+                message: Some(message.to_string()),
+                maintenance: false,
+            })
+        }
+    }
+}
+
+fn from_json(json: &Value) -> Option<Notice> {
+    let message = ["notice", "motd", "message"]
+        .iter()
+        .find_map(|key| json.get(key)?.as_str())
+        .map(str::trim)
+        .filter(|message| !message.is_empty())
+        .map(str::to_string);
+    let maintenance = match json.get("maintenance") {
+        Some(Value::Bool(maintenance)) => *maintenance,
+        // some servers put the reason in place of the flag
+        Some(Value::String(reason)) => !reason.is_empty(),
+        _ => false,
+    };
+    let notice = Notice {
+        message,
+        maintenance,
+    };
+    (notice != Notice::default()).then_some(notice)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_metadata() {
+        assert_eq!(
+            from_metadata(r#"{"meta":{"serverName":"Marallys","motd":" Welcome back! "}}"#),
+            Some(Notice {
+                message: Some("Welcome back!".to_string()),
+                maintenance: false,
+            })
+        );
+        assert_eq!(
+            from_metadata(r#"{"meta":{"notice":"Down at 18:00 UTC","maintenance":true}}"#),
+            Some(Notice {
+                message: Some("Down at 18:00 UTC".to_string()),
+                maintenance: true,
+            })
+        );
+        assert_eq!(from_metadata(r#"{"meta":{"serverName":"Marallys"}}"#), None);
+        assert_eq!(from_metadata(r#"{"skinDomains":[]}"#), None);
+        assert_eq!(from_metadata("<html>"), None);
+    }
+}
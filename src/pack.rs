@@ -0,0 +1,84 @@
+use std::{fs, path::Path};
+
+use serde::Deserialize;
+
+use crate::params;
+
+const PACK_FILE_NAME: &str = "mmc-pack.json";
+const MINECRAFT_UID: &str = "net.minecraft";
+/// Component uids of the mod loaders, and the names they are shown with.
+const LOADERS: &[(&str, &str)] = &[
+    ("net.minecraftforge", "Forge"),
+    ("net.neoforged", "NeoForge"),
+    ("net.fabricmc.fabric-loader", "Fabric"),
+    ("org.quiltmc.quilt-loader", "Quilt"),
+    ("com.mumfrey.liteloader", "LiteLoader"),
+];
+
+/// The instance's components from Prism's `mmc-pack.json`: the game version and the
+/// mod loader.
+#[derive(Deserialize, Debug, Default, PartialEq)]
+pub struct Pack {
+    #[serde(default)]
+    pub components: Vec<Component>,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct Component {
+    pub uid: String,
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+impl Pack {
+    /// `None` when the instance has no readable `mmc-pack.json`, e.g. outside Prism.
+    pub fn load(instance_dir: &Path) -> Option<Pack> {
+        let text = fs::read_to_string(instance_dir.join(PACK_FILE_NAME)).ok()?;
+        serde_json::from_str(&text).ok()
+    }
+
+    fn version(&self, uid: &str) -> Option<&str> {
+        self.components
+            .iter()
+            .find(|component| component.uid == uid)?
+            .version
+            .as_deref()
+    }
+
+    /// The Minecraft version, as (minor, patch) like the param block's.
+    pub fn minecraft_version(&self) -> Option<(u32, u32)> {
+        params::parse_version(self.version(MINECRAFT_UID)?)
+    }
+
+    /// The mod loader and its version, e.g. `Fabric 0.16.10`.
+    pub fn loader(&self) -> Option<String> {
+        LOADERS.iter().find_map(|(uid, name)| {
+            let version = self.version(uid)?;
+            Some(format!("{} {}", name, version))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack() {
+        let pack: Pack = serde_json::from_str(
+            r#"{
+                "components": [
+                    {"cachedName": "LWJGL 3", "uid": "org.lwjgl3", "version": "3.3.1"},
+                    {"important": true, "uid": "net.minecraft", "version": "1.20.1"},
+                    {"uid": "net.fabricmc.intermediary", "version": "1.20.1"},
+                    {"uid": "net.fabricmc.fabric-loader", "version": "0.16.10"}
+                ],
+                "formatVersion": 1
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(pack.minecraft_version(), Some((20, 1)));
+        assert_eq!(pack.loader().as_deref(), Some("Fabric 0.16.10"));
+        assert_eq!(Pack::default().minecraft_version(), None);
+    }
+}
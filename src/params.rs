@@ -0,0 +1,961 @@
+use std::{collections::BTreeMap, fmt, fs, time::Duration};
+
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, BufReader};
+use tracing::info;
+
+use crate::cli;
+use crate::config::{Config, WindowConfig};
+use crate::errors::MmcaiError;
+use crate::i18n::tr;
+use crate::pack::Pack;
+use crate::Result;
+
+/// Reads (minor, patch) from a version id like `1.20.1`, `1.8` or
+/// `fabric-loader-0.16.10-1.20.1`.
+pub fn parse_version(id: &str) -> Option<(u32, u32)> {
+    id.split('-').find_map(|part| {
+        let mut numbers = part.split('.');
+        if numbers.next()? != "1" {
+            return None;
+        }
+        let minor = numbers.next()?.parse().ok()?;
+        let patch = numbers.next().map_or(Some(0), |patch| patch.parse().ok())?;
+        Some((minor, patch))
+    })
+}
+
+/// One line of the MultiMC/Prism launcher protocol, as sent to the launcher's Java
+/// entry point on stdin.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Entry {
+    /// `param <value>`: one argument of the game's command line. Options and their
+    /// values are sent as separate params.
+    Param(String),
+    /// `<key> <value>`, e.g. `userName Steve` or `mainClass net.minecraft.client.main.Main`.
+    Property { key: String, value: String },
+    /// A line without a value, e.g. `launch`.
+    Directive(String),
+}
+
+impl Entry {
+    fn parse(line: &str) -> Entry {
+        match line.split_once(' ') {
+            Some(("param", value)) => Entry::Param(value.to_string()),
+            Some((key, value)) => Entry::Property {
+                key: key.to_string(),
+                value: value.to_string(),
+            },
+            None => Entry::Directive(line.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Entry::Param(value) => write!(f, "param {}", value),
+            Entry::Property { key, value } => write!(f, "{} {}", key, value),
+            Entry::Directive(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// Which launcher the param block is meant for, from its `launcher` line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Variant {
+    /// `launcher onesix`/`launcher standard`: the game's arguments are sent as `param`s.
+    Standard,
+    /// `launcher legacy`, used by 1.5/1.6-era instances: the game only gets
+    /// `userName` and a `sessionId` that also carries the uuid.
+    Legacy,
+}
+
+/// The launch parameters the launcher streams to us, patched by key and passed on to
+/// the game unchanged otherwise.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LaunchParams {
+    pub entries: Vec<Entry>,
+    /// The version from the instance's `mmc-pack.json`, for param blocks that don't say.
+    pub pack_version: Option<(u32, u32)>,
+}
+
+impl LaunchParams {
+    pub fn parse<S: AsRef<str>>(lines: &[S]) -> LaunchParams {
+        LaunchParams {
+            entries: lines
+                .iter()
+                .map(|line| Entry::parse(line.as_ref()))
+                .collect(),
+            pack_version: None,
+        }
+    }
+
+    pub fn to_lines(&self) -> Vec<String> {
+        self.entries.iter().map(Entry::to_string).collect()
+    }
+
+    pub fn variant(&self) -> Variant {
+        let launcher = self.entries.iter().find_map(|entry| match entry {
+            Entry::Property { key, value } if key == "launcher" => Some(value.as_str()),
+            _ => None,
+        });
+        match launcher {
+            Some("legacy") => Variant::Legacy,
+            Some(_) => Variant::Standard,
+            // without a launcher line, only the version tells: 1.6 has its own arguments
+            None if self
+                .minecraft_version()
+                .is_some_and(|version| version < (6, 0)) =>
+            {
+                Variant::Legacy
+            }
+            None => Variant::Standard,
+        }
+    }
+
+    /// Replaces the value following every `param <option>`.
+    pub fn set_argument(&mut self, option: &str, value: &str) -> Result<()> {
+        let mut index = 0;
+        while index < self.entries.len() {
+            if matches!(&self.entries[index], Entry::Param(param) if param == option) {
+                match self.entries.get_mut(index + 1) {
+                    Some(Entry::Param(current)) => *current = value.to_string(),
+                    _ => return Err(MmcaiError::InvalidMinecraftParams(option.to_string())),
+                }
+                index += 1;
+            }
+            index += 1;
+        }
+        Ok(())
+    }
+
+    /// Returns the value following the first `param <option>`.
+    pub fn argument(&self, option: &str) -> Option<&str> {
+        let index = self
+            .entries
+            .iter()
+            .position(|entry| matches!(entry, Entry::Param(param) if param == option))?;
+        match self.entries.get(index + 1)? {
+            Entry::Param(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Sets `option` to `value`, adding it after the last game argument if the launcher
+    /// didn't pass it.
+    pub fn put_argument(&mut self, option: &str, value: &str) -> Result<()> {
+        if self.argument(option).is_some() {
+            return self.set_argument(option, value);
+        }
+        let position = self.end_of_arguments();
+        self.entries.splice(
+            position..position,
+            [
+                Entry::Param(option.to_string()),
+                Entry::Param(value.to_string()),
+            ],
+        );
+        Ok(())
+    }
+
+    /// Where new game arguments go: right after the last one.
+    fn end_of_arguments(&self) -> usize {
+        self.entries
+            .iter()
+            .rposition(|entry| matches!(entry, Entry::Param(_)))
+            .map_or(0, |index| index + 1)
+    }
+
+    /// The Minecraft version from `--version`, as (minor, patch), e.g. (20, 1) for 1.20.1.
+    /// Mod loader version ids like `fabric-loader-0.16.10-1.20.1` are understood too.
+    pub fn minecraft_version(&self) -> Option<(u32, u32)> {
+        self.argument("--version")
+            .and_then(parse_version)
+            .or(self.pack_version)
+    }
+
+    /// Makes the game join `address` (`host` or `host:port`) right after starting.
+    pub fn add_quick_play(&mut self, address: &str) -> Result<()> {
+        // quick play arrived in 1.20; unknown versions are assumed to be recent
+        if self
+            .minecraft_version()
+            .is_none_or(|version| version >= (20, 0))
+        {
+            return self.put_argument("--quickPlayMultiplayer", address);
+        }
+        let (host, port) = match address.rsplit_once(':') {
+            Some((host, port)) if port.parse::<u16>().is_ok() => (host, port),
+            _ => (address, "25565"),
+        };
+        self.put_argument("--server", host)?;
+        self.put_argument("--port", port)
+    }
+
+    /// Adds or removes a flag like `param --fullscreen`.
+    pub fn set_flag(&mut self, option: &str, enabled: bool) {
+        let present = self
+            .entries
+            .iter()
+            .any(|entry| matches!(entry, Entry::Param(param) if param == option));
+        if !enabled {
+            self.entries
+                .retain(|entry| !matches!(entry, Entry::Param(param) if param == option));
+        } else if !present {
+            let position = self.end_of_arguments();
+            self.entries
+                .insert(position, Entry::Param(option.to_string()));
+        }
+    }
+
+    /// Applies the `[window]` settings. Prism sizes the window from the `windowParams`
+    /// property (`<width>x<height>` or `max`), other launchers pass `--width`/`--height`.
+    pub fn set_window(&mut self, window: &WindowConfig) -> Result<()> {
+        if window.width.is_some() || window.height.is_some() {
+            let current = self.entries.iter().find_map(|entry| match entry {
+                Entry::Property { key, value } if key == "windowParams" => value
+                    .split_once('x')
+                    .and_then(|(w, h)| Some((w.parse::<u32>().ok()?, h.parse::<u32>().ok()?))),
+                _ => None,
+            });
+            let (current_width, current_height) = current.unwrap_or((854, 480));
+            let width = window.width.unwrap_or(current_width);
+            let height = window.height.unwrap_or(current_height);
+            self.set_property("windowParams", &format!("{}x{}", width, height));
+            if self.argument("--width").is_some() || self.argument("--height").is_some() {
+                self.set_argument("--width", &width.to_string())?;
+                self.set_argument("--height", &height.to_string())?;
+            }
+        }
+        if let Some(fullscreen) = window.fullscreen {
+            self.set_flag("--fullscreen", fullscreen);
+        }
+        Ok(())
+    }
+
+    /// Replaces the value of every `<key> <value>` line with that exact key.
+    pub fn set_property(&mut self, key: &str, value: &str) {
+        for entry in &mut self.entries {
+            if let Entry::Property { key: k, value: v } = entry {
+                if k == key {
+                    *v = value.to_string();
+                }
+            }
+        }
+    }
+
+    /// Applies the user's `[param_rules]`.
+    pub fn apply_rules(
+        &mut self,
+        rules: &BTreeMap<String, String>,
+        identity: &Identity,
+    ) -> Result<()> {
+        for (key, template) in rules {
+            let value = expand(template, identity);
+            if key.starts_with("--") {
+                self.set_argument(key, &value)?;
+            } else {
+                self.set_property(key, &value);
+            }
+        }
+        Ok(())
+    }
+}
+
+const REDACTED: &str = "<redacted>";
+
+impl LaunchParams {
+    /// A copy safe to share: access tokens and session ids are replaced.
+    pub fn redacted(&self) -> LaunchParams {
+        let mut params = self.clone();
+        let mut redact_next = false;
+        for entry in &mut params.entries {
+            match entry {
+                Entry::Param(value) if redact_next => {
+                    *value = REDACTED.to_string();
+                    redact_next = false;
+                }
+                Entry::Param(option) => match option.split_once('=') {
+                    Some(("--accessToken", _)) => {
+                        *option = format!("--accessToken={}", REDACTED);
+                        redact_next = false;
+                    }
+                    _ => redact_next = option == "--accessToken",
+                },
+                Entry::Property { key, value } if key == "sessionId" => {
+                    *value = REDACTED.to_string();
+                }
+                _ => redact_next = false,
+            }
+        }
+        params
+    }
+}
+
+/// The values launch params are patched with.
+pub struct Identity<'a> {
+    pub name: &'a str,
+    pub uuid: &'a str,
+    pub access_token: &'a str,
+}
+
+fn expand(template: &str, identity: &Identity) -> String {
+    template
+        .replace("{uuid}", identity.uuid)
+        .replace("{name}", identity.name)
+        .replace("{token}", identity.access_token)
+}
+
+impl LaunchParams {
+    /// Logs the game in as `identity`, then applies the `[game]`, `[window]` and
+    /// `[param_rules]` settings: everything the wrapper changes in the param block.
+    pub fn patch(
+        &mut self,
+        identity: &Identity,
+        client_token: &str,
+        config: &Config,
+    ) -> Result<()> {
+        let Identity {
+            name,
+            uuid,
+            access_token,
+        } = *identity;
+        self.set_property("userName", name);
+        match self.variant() {
+            Variant::Standard => {
+                self.set_argument("--username", name)?;
+                self.set_argument("--uuid", uuid)?;
+                self.set_argument("--accessToken", access_token)?;
+                // newer versions also get the Xbox user id and launcher client id of the
+                // launcher's own account; there is no Xbox account behind a Yggdrasil login
+                self.set_argument("--xuid", "0")?;
+                self.set_argument("--clientId", client_token)?;
+                self.set_property("sessionId", &format!("token:{}", access_token));
+            }
+            Variant::Legacy => {
+                self.set_property("sessionId", &format!("token:{}:{}", access_token, uuid));
+            }
+        }
+        if let Some(address) = &config.game.quick_play {
+            self.add_quick_play(address)?;
+        }
+        self.set_window(&config.window)?;
+        self.apply_rules(&config.param_rules, identity)
+    }
+
+    /// The value of the first `<key> <value>` line with that exact key.
+    pub fn property(&self, key: &str) -> Option<&str> {
+        self.entries.iter().find_map(|entry| match entry {
+            Entry::Property { key: k, value } if k == key => Some(value.as_str()),
+            _ => None,
+        })
+    }
+}
+
+pub(crate) fn modify_minecraft_params(
+    minecraft_params: &mut Vec<String>,
+    access_token: &str,
+    uuid: &str,
+    playername: &str,
+    client_token: &str,
+    pack: Option<&Pack>,
+    config: &Config,
+) -> Result<()> {
+    let mut params = LaunchParams::parse(minecraft_params);
+    params.pack_version = pack.and_then(Pack::minecraft_version);
+    let identity = Identity {
+        name: playername,
+        uuid,
+        access_token,
+    };
+    params.patch(&identity, client_token, config)?;
+    *minecraft_params = params.to_lines();
+    Ok(())
+}
+
+/// A launch as a launcher hands it over: the Java command line and the param block sent
+/// on stdin. Tools that start the game themselves can patch it exactly like the wrapper:
+///
+/// ```
+/// use marallys_auth_patcher::config::Config;
+/// use marallys_auth_patcher::params::{Identity, LaunchSpec};
+///
+/// let mut spec = LaunchSpec::parse("param --username\nparam Player\nlaunch\n");
+/// let identity = Identity { name: "Steve", uuid: "0f8d", access_token: "t" };
+/// spec.patch(&identity, "client", &Config::default()).unwrap();
+/// assert_eq!(spec.username(), Some("Steve"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LaunchSpec {
+    /// The Java executable and its arguments, up to and including the main class; empty
+    /// when only the param block is known.
+    pub java_command: Vec<String>,
+    pub params: LaunchParams,
+}
+
+impl LaunchSpec {
+    /// Reads a param block, one entry per line, up to and including `launch`.
+    pub fn parse(text: &str) -> LaunchSpec {
+        let mut lines = Vec::new();
+        for line in text.lines().map(str::trim) {
+            lines.push(line);
+            if line == "launch" {
+                break;
+            }
+        }
+        LaunchSpec {
+            java_command: Vec::new(),
+            params: LaunchParams::parse(&lines),
+        }
+    }
+
+    pub fn with_java_command(mut self, java_command: Vec<String>) -> LaunchSpec {
+        self.java_command = java_command;
+        self
+    }
+
+    pub fn patch(
+        &mut self,
+        identity: &Identity,
+        client_token: &str,
+        config: &Config,
+    ) -> Result<()> {
+        self.params.patch(identity, client_token, config)
+    }
+
+    /// The param block as the game reads it on stdin.
+    pub fn serialize(&self) -> String {
+        let mut text = self.params.to_lines().join("\n");
+        text.push('\n');
+        text
+    }
+
+    pub fn username(&self) -> Option<&str> {
+        self.params
+            .argument("--username")
+            .or_else(|| self.params.property("userName"))
+    }
+
+    pub fn uuid(&self) -> Option<&str> {
+        self.params
+            .argument("--uuid")
+            .or_else(|| self.session_id_parts().nth(1))
+    }
+
+    pub fn access_token(&self) -> Option<&str> {
+        self.params
+            .argument("--accessToken")
+            .or_else(|| self.session_id_parts().next())
+    }
+
+    /// The arguments after the Java executable.
+    pub fn jvm_args(&self) -> &[String] {
+        match self.java_command.split_first() {
+            Some((_, args)) => args,
+            None => &[],
+        }
+    }
+
+    /// `sessionId token:<access token>[:<uuid>]`, split.
+    fn session_id_parts(&self) -> impl Iterator<Item = &str> {
+        self.params
+            .property("sessionId")
+            .and_then(|session_id| session_id.strip_prefix("token:"))
+            .into_iter()
+            .flat_map(|token| token.split(':'))
+    }
+}
+
+/// Reads the param block from `--params-file` if given, otherwise from the launcher on stdin.
+pub async fn read_minecraft_params(options: &cli::Options, config: &Config) -> Result<Vec<String>> {
+    let minecraft_params = match &options.params_file {
+        Some(path) => {
+            let contents = fs::read(path).map_err(|source| MmcaiError::ReadParamsFileFailed {
+                path: path.clone(),
+                source,
+            })?;
+            read_params_from(&contents[..]).await?
+        }
+        None => {
+            let read = read_params_from(BufReader::new(tokio::io::stdin()));
+            // a launcher killed mid-start may leave the pipe open without ever finishing
+            match config.watchdog.params_timeout {
+                0 => read.await?,
+                seconds => tokio::time::timeout(Duration::from_secs(seconds), read)
+                    .await
+                    .map_err(|_| MmcaiError::ParamsTimedOut { seconds })??,
+            }
+        }
+    };
+
+    if let Some(path) = &options.record_params {
+        let mut recording = LaunchParams::parse(&minecraft_params).redacted().to_lines();
+        recording.push(String::new());
+        fs::write(path, recording.join("\n")).map_err(|source| {
+            MmcaiError::WriteParamsRecordFailed {
+                path: path.clone(),
+                source,
+            }
+        })?;
+        info!("{}", tr!("params-recorded", path = format!("{:?}", path)));
+    }
+    Ok(minecraft_params)
+}
+
+pub async fn read_params_from<R: AsyncBufRead + Unpin>(reader: R) -> Result<Vec<String>> {
+    let mut minecraft_params: Vec<String> = Vec::new();
+
+    let mut lines = reader.lines();
+    while let Some(line) = lines
+        .next_line()
+        .await
+        .map_err(MmcaiError::ReadMinecraftParamsFailed)?
+    {
+        let line = line.trim().to_string();
+        if line == "abort" {
+            return Err(MmcaiError::LaunchAborted);
+        }
+        minecraft_params.push(line.clone());
+        if line == "launch" {
+            break;
+        }
+    }
+
+    Ok(minecraft_params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let lines = [
+            "mod legacy",
+            "param --gameDir",
+            "param /home/steve/.local/share/PrismLauncher/instances/1.20 userName test",
+            "mainClass net.minecraft.client.main.Main",
+            "traits  two spaces",
+            "launch",
+        ];
+        let params = LaunchParams::parse(&lines);
+        assert_eq!(params.entries[5], Entry::Directive("launch".to_string()));
+        assert_eq!(params.to_lines(), lines);
+    }
+
+    #[test]
+    fn test_variant() {
+        let onesix = [
+            "mod legacy",
+            "mainClass net.minecraft.client.main.Main",
+            "param --username",
+            "param Player",
+            "param --version",
+            "param 1.12.2",
+            "param --uuid",
+            "param 00000000000000000000000000000000",
+            "param --accessToken",
+            "param 0",
+            "windowTitle Prism Launcher: 1.12.2",
+            "windowParams 854x480",
+            "userName Player",
+            "sessionId token:0",
+            "launcher onesix",
+            "launch",
+        ];
+        assert_eq!(LaunchParams::parse(&onesix).variant(), Variant::Standard);
+
+        let legacy = [
+            "userName Player",
+            "sessionId token:0:00000000000000000000000000000000",
+            "windowTitle MultiMC: 1.5.2",
+            "windowParams 854x480",
+            "lwjgl /home/player/MultiMC/libraries/lwjgl/2.9.0",
+            "natives /home/player/MultiMC/instances/1.5.2/natives",
+            "cp /home/player/MultiMC/instances/1.5.2/minecraft.jar",
+            "launcher legacy",
+            "launch",
+        ];
+        assert_eq!(LaunchParams::parse(&legacy).variant(), Variant::Legacy);
+
+        // an old param block without a launcher line, from the instance's version
+        let mut params = LaunchParams::parse(&legacy[..legacy.len() - 2]);
+        assert_eq!(params.variant(), Variant::Standard);
+        params.pack_version = Some((5, 2));
+        assert_eq!(params.variant(), Variant::Legacy);
+    }
+
+    #[test]
+    fn test_add_quick_play() {
+        let mut params = LaunchParams::parse(&[
+            "param --version",
+            "param fabric-loader-0.16.10-1.20.1",
+            "launch",
+        ]);
+        assert_eq!(params.minecraft_version(), Some((20, 1)));
+        params.add_quick_play("play.marallys.com").unwrap();
+        assert_eq!(
+            params.argument("--quickPlayMultiplayer"),
+            Some("play.marallys.com")
+        );
+
+        let mut params = LaunchParams::parse(&["param --version", "param 1.12.2", "launch"]);
+        params.add_quick_play("play.marallys.com:25570").unwrap();
+        assert_eq!(params.argument("--quickPlayMultiplayer"), None);
+        assert_eq!(params.argument("--server"), Some("play.marallys.com"));
+        assert_eq!(params.argument("--port"), Some("25570"));
+        assert_eq!(
+            params.entries.last(),
+            Some(&Entry::Directive("launch".into()))
+        );
+    }
+
+    #[test]
+    fn test_set_window() {
+        let mut params = LaunchParams::parse(&[
+            "param --username",
+            "param Player",
+            "param --fullscreen",
+            "windowParams 854x480",
+            "launch",
+        ]);
+        params
+            .set_window(&WindowConfig {
+                width: Some(1920),
+                height: None,
+                fullscreen: Some(false),
+            })
+            .unwrap();
+        assert_eq!(
+            params.to_lines(),
+            [
+                "param --username",
+                "param Player",
+                "windowParams 1920x480",
+                "launch"
+            ]
+        );
+
+        let mut params =
+            LaunchParams::parse(&["param --width", "param 854", "param --height", "param 480"]);
+        params
+            .set_window(&WindowConfig {
+                width: Some(1280),
+                height: Some(720),
+                fullscreen: Some(true),
+            })
+            .unwrap();
+        assert_eq!(params.argument("--width"), Some("1280"));
+        assert_eq!(params.argument("--height"), Some("720"));
+        assert_eq!(
+            params.entries.last(),
+            Some(&Entry::Param("--fullscreen".into()))
+        );
+    }
+
+    #[test]
+    fn test_redacted() {
+        let params = LaunchParams::parse(&[
+            "param --accessToken",
+            "param secret",
+            "param --username",
+            "param Player",
+            "param --accessToken=secret",
+            "sessionId token:secret",
+            "launch",
+        ]);
+        assert_eq!(
+            params.redacted().to_lines(),
+            [
+                "param --accessToken",
+                "param <redacted>",
+                "param --username",
+                "param Player",
+                "param --accessToken=<redacted>",
+                "sessionId <redacted>",
+                "launch",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_patch_by_key() {
+        let mut params = LaunchParams::parse(&[
+            "param --username",
+            "param Old",
+            "param --gameDir",
+            "param /mods/userName sessionId",
+            "userName Old",
+            "launch",
+        ]);
+        params.set_argument("--username", "New").unwrap();
+        params.set_property("userName", "New");
+        assert_eq!(
+            params.to_lines(),
+            [
+                "param --username",
+                "param New",
+                "param --gameDir",
+                "param /mods/userName sessionId",
+                "userName New",
+                "launch",
+            ]
+        );
+
+        let mut rules = BTreeMap::new();
+        rules.insert("--gameDir".to_string(), "/games/{name}".to_string());
+        rules.insert("sessionId".to_string(), "{uuid}:{token}".to_string());
+        let identity = Identity {
+            name: "Steve",
+            uuid: "abc",
+            access_token: "t0k",
+        };
+        let mut params = LaunchParams::parse(&["param --gameDir", "param x", "sessionId y"]);
+        params.apply_rules(&rules, &identity).unwrap();
+        assert_eq!(
+            params.to_lines(),
+            ["param --gameDir", "param /games/Steve", "sessionId abc:t0k"]
+        );
+
+        let mut params = LaunchParams::parse(&["param --uuid", "launch"]);
+
+        assert!(matches!(
+            params.set_argument("--uuid", "x"),
+            Err(MmcaiError::InvalidMinecraftParams(_))
+        ));
+
+        let mut params = LaunchParams::parse(&["param --uuid", "param x", "launch"]);
+        params.put_argument("--demo", "true").unwrap();
+        assert_eq!(
+            params.to_lines(),
+            [
+                "param --uuid",
+                "param x",
+                "param --demo",
+                "param true",
+                "launch"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_modify_minecraft_params() {
+        let mut minecraft_params = vec![
+            "---START---".to_string(),
+            "param --username".to_string(),
+            "param AnyHow".to_string(),
+            "param --uuid".to_string(),
+            "param AnyHow".to_string(),
+            "param --accessToken".to_string(),
+            "param AnyHow".to_string(),
+            "param --clientId".to_string(),
+            "param AnyHow".to_string(),
+            "param --xuid".to_string(),
+            "param AnyHow".to_string(),
+            "userName AnyHow".to_string(),
+            "sessionId AnyHow".to_string(),
+            "launch".to_string(),
+            "---END---".to_string(),
+        ];
+        let access_token = "TEST_ACCESS_TOKEN";
+        let uuid = "TEST_UUID";
+        let playername = "TEST_PLAYERNAME";
+        let client_token = "TEST_CLIENT_TOKEN";
+        modify_minecraft_params(
+            &mut minecraft_params,
+            access_token,
+            uuid,
+            playername,
+            client_token,
+            None,
+            &Config::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            minecraft_params,
+            vec![
+                "---START---".to_string(),
+                "param --username".to_string(),
+                "param TEST_PLAYERNAME".to_string(),
+                "param --uuid".to_string(),
+                "param TEST_UUID".to_string(),
+                "param --accessToken".to_string(),
+                "param TEST_ACCESS_TOKEN".to_string(),
+                "param --clientId".to_string(),
+                "param TEST_CLIENT_TOKEN".to_string(),
+                "param --xuid".to_string(),
+                "param 0".to_string(),
+                "userName TEST_PLAYERNAME".to_string(),
+                "sessionId token:TEST_ACCESS_TOKEN".to_string(),
+                "launch".to_string(),
+                "---END---".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_modify_legacy_minecraft_params() {
+        let mut minecraft_params: Vec<String> = [
+            "userName Player",
+            "sessionId token:0:00000000000000000000000000000000",
+            "windowTitle MultiMC: 1.5.2",
+            "windowParams 854x480",
+            "cp /home/player/MultiMC/instances/1.5.2/minecraft.jar",
+            "launcher legacy",
+            "launch",
+        ]
+        .iter()
+        .map(|line| line.to_string())
+        .collect();
+        modify_minecraft_params(
+            &mut minecraft_params,
+            "TEST_ACCESS_TOKEN",
+            "TEST_UUID",
+            "TEST_PLAYERNAME",
+            "TEST_CLIENT_TOKEN",
+            None,
+            &Config::default(),
+        )
+        .unwrap();
+        assert_eq!(minecraft_params[0], "userName TEST_PLAYERNAME");
+        assert_eq!(
+            minecraft_params[1],
+            "sessionId token:TEST_ACCESS_TOKEN:TEST_UUID"
+        );
+        assert_eq!(minecraft_params[2], "windowTitle MultiMC: 1.5.2");
+    }
+
+    #[test]
+    fn test_read_params_abort() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let params = runtime
+            .block_on(read_params_from(
+                &b"param --username\nparam Steve\nlaunch\nignored\n"[..],
+            ))
+            .unwrap();
+        assert_eq!(params, ["param --username", "param Steve", "launch"]);
+        assert!(matches!(
+            runtime.block_on(read_params_from(&b"param --username\nabort\n"[..])),
+            Err(MmcaiError::LaunchAborted)
+        ));
+    }
+
+    #[test]
+    fn test_launch_spec() {
+        let text =
+            "param --username\nparam Player\nparam --accessToken\nparam old\nlaunch\nignored\n";
+        let mut spec = LaunchSpec::parse(text).with_java_command(vec![
+            "java".into(),
+            "-Xmx2G".into(),
+            "Main".into(),
+        ]);
+        assert_eq!(spec.jvm_args(), ["-Xmx2G", "Main"]);
+        assert_eq!(spec.access_token(), Some("old"));
+        let identity = Identity {
+            name: "Steve",
+            uuid: "0f8d",
+            access_token: "t",
+        };
+        spec.patch(&identity, "c", &Config::default()).unwrap();
+        assert_eq!(spec.username(), Some("Steve"));
+        assert_eq!(
+            spec.serialize(),
+            "param --username\nparam Steve\nparam --accessToken\nparam t\nlaunch\n"
+        );
+
+        let legacy = LaunchSpec::parse("userName Steve\nsessionId token:t:0f8d\nlauncher legacy\n");
+        assert_eq!(legacy.uuid(), Some("0f8d"));
+        assert_eq!(legacy.access_token(), Some("t"));
+    }
+
+    /// Param blocks in the layout Prism and MultiMC send, tokens redacted like
+    /// `--record-params` does.
+    const PRISM_1_20: &str = include_str!("../tests/fixtures/prism-1.20.1.txt");
+    const MULTIMC_1_12: &str = include_str!("../tests/fixtures/multimc-1.12.2.txt");
+    const MULTIMC_1_5: &str = include_str!("../tests/fixtures/multimc-1.5.2.txt");
+
+    fn patched(text: &str) -> LaunchSpec {
+        let mut spec = LaunchSpec::parse(text);
+        let identity = Identity {
+            name: "Steve",
+            uuid: "0f8d",
+            access_token: "t",
+        };
+        spec.patch(&identity, "client", &Config::default()).unwrap();
+        spec
+    }
+
+    #[test]
+    fn test_fixtures_round_trip() {
+        for text in [PRISM_1_20, MULTIMC_1_12, MULTIMC_1_5] {
+            let spec = LaunchSpec::parse(text);
+            assert_eq!(spec.serialize(), text);
+            assert_eq!(spec.params.redacted(), spec.params);
+        }
+    }
+
+    #[test]
+    fn test_patch_prism_fixture() {
+        let spec = patched(PRISM_1_20);
+        assert_eq!(spec.params.variant(), Variant::Standard);
+        assert_eq!(spec.username(), Some("Steve"));
+        assert_eq!(spec.uuid(), Some("0f8d"));
+        assert_eq!(spec.access_token(), Some("t"));
+        assert_eq!(spec.params.argument("--xuid"), Some("0"));
+        assert_eq!(spec.params.argument("--clientId"), Some("client"));
+        assert_eq!(spec.params.argument("--userType"), Some("msa"));
+        assert_eq!(spec.params.property("userName"), Some("Steve"));
+        assert_eq!(spec.params.property("sessionId"), Some("token:t"));
+
+        // only the account's lines change
+        let before = PRISM_1_20.lines();
+        let after = spec.serialize();
+        let changed: Vec<_> = before
+            .zip(after.lines())
+            .filter(|(before, after)| before != after)
+            .map(|(_, after)| after)
+            .collect();
+        assert_eq!(
+            changed,
+            [
+                "param Steve",
+                "param 0f8d",
+                "param t",
+                "param client",
+                "param 0",
+                "userName Steve",
+                "sessionId token:t",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_patch_multimc_fixtures() {
+        let spec = patched(MULTIMC_1_12);
+        assert_eq!(spec.params.variant(), Variant::Standard);
+        assert_eq!(spec.username(), Some("Steve"));
+        assert_eq!(spec.access_token(), Some("t"));
+        // 1.12 takes neither, so none are added
+        assert_eq!(spec.params.argument("--xuid"), None);
+        assert_eq!(spec.params.argument("--clientId"), None);
+        assert_eq!(
+            spec.serialize().lines().count(),
+            MULTIMC_1_12.lines().count()
+        );
+
+        let spec = patched(MULTIMC_1_5);
+        assert_eq!(spec.params.variant(), Variant::Legacy);
+        assert_eq!(spec.username(), Some("Steve"));
+        assert_eq!(spec.params.property("sessionId"), Some("token:t:0f8d"));
+        assert_eq!(spec.uuid(), Some("0f8d"));
+        assert_eq!(
+            spec.params.property("mainClass"),
+            Some("net.minecraft.launchwrapper.Launch")
+        );
+        assert_eq!(
+            spec.serialize().lines().count(),
+            MULTIMC_1_5.lines().count()
+        );
+    }
+}
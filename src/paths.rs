@@ -0,0 +1,182 @@
+use std::{
+    env,
+    fs::{self, File, OpenOptions},
+    io,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+const APP_DIR_NAME: &str = "mmcai_rs";
+const PRISM_DIR_NAME: &str = "PrismLauncher";
+/// Next to the executable, holds everything the patcher keeps in portable mode.
+const PORTABLE_DIR_NAME: &str = "mmcai_rs-data";
+
+static PORTABLE_DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+/// Chooses where state is kept, before anything is read or written. In portable mode
+/// (`--portable`, or when `mmcai_rs-data` exists next to the executable) it lives in
+/// that directory, for Prism installs carried around on a USB stick; otherwise in the
+/// current OS user's directories.
+pub fn init(portable: bool) {
+    let _ = PORTABLE_DIR.set(portable_dir(portable));
+}
+
+fn portable_dir(forced: bool) -> Option<PathBuf> {
+    let dir = env::current_exe().ok()?.parent()?.join(PORTABLE_DIR_NAME);
+    (forced || dir.is_dir()).then_some(dir)
+}
+
+fn portable() -> Option<&'static Path> {
+    // tests never touch the user's own cache and data
+    #[cfg(test)]
+    return Some(test_dir());
+    #[cfg(not(test))]
+    PORTABLE_DIR.get_or_init(|| portable_dir(false)).as_deref()
+}
+
+/// A directory of the test run's own, standing in for the portable one.
+#[cfg(test)]
+fn test_dir() -> &'static Path {
+    static TEST_DIR: OnceLock<PathBuf> = OnceLock::new();
+    TEST_DIR.get_or_init(|| {
+        env::temp_dir().join(format!("{}-test-{}", APP_DIR_NAME, std::process::id()))
+    })
+}
+
+/// Per-user cache directory: `%LOCALAPPDATA%\mmcai_rs` on Windows, `~/Library/Caches/mmcai_rs`
+/// on macOS and `$XDG_CACHE_HOME/mmcai_rs` (or `~/.cache/mmcai_rs`) elsewhere.
+pub fn cache_dir() -> Option<PathBuf> {
+    if let Some(dir) = portable() {
+        return Some(dir.join("cache"));
+    }
+    let base = if cfg!(windows) {
+        env::var_os("LOCALAPPDATA").map(PathBuf::from)?
+    } else if cfg!(target_os = "macos") {
+        home_dir()?.join("Library").join("Caches")
+    } else {
+        env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .or_else(|| home_dir().map(|home| home.join(".cache")))?
+    };
+    Some(base.join(APP_DIR_NAME))
+}
+
+/// Per-user data directory: `%APPDATA%\mmcai_rs` on Windows, `~/Library/Application Support/mmcai_rs`
+/// on macOS and `$XDG_DATA_HOME/mmcai_rs` (or `~/.local/share/mmcai_rs`) elsewhere.
+pub fn data_dir() -> Option<PathBuf> {
+    if let Some(dir) = portable() {
+        return Some(dir.join("data"));
+    }
+    Some(data_base_dir()?.join(APP_DIR_NAME))
+}
+
+/// Prism Launcher's data directories, where it keeps the Java runtimes it downloads.
+/// On Linux the Flatpak's one is included.
+pub fn prism_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = data_base_dir()
+        .into_iter()
+        .map(|base| base.join(PRISM_DIR_NAME))
+        .collect();
+    if cfg!(all(unix, not(target_os = "macos"))) {
+        if let Some(home) = home_dir() {
+            dirs.push(
+                home.join(".var/app/org.prismlauncher.PrismLauncher/data")
+                    .join(PRISM_DIR_NAME),
+            );
+        }
+    }
+    dirs
+}
+
+fn data_base_dir() -> Option<PathBuf> {
+    if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        Some(home_dir()?.join("Library").join("Application Support"))
+    } else {
+        env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .or_else(|| home_dir().map(|home| home.join(".local").join("share")))
+    }
+}
+
+/// Creates `dir` and its missing parents, accessible by the current user only, since
+/// they hold tokens and login history. Windows' per-user directories already are.
+/// A `dir` that already exists is made private too.
+pub fn create_private_dir(dir: &Path) -> io::Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder.create(dir)?;
+    restrict(dir, 0o700)
+}
+
+/// Sets the mode even when the file or directory existed before with a wider one, which
+/// the mode given when creating it doesn't.
+#[cfg_attr(not(unix), allow(unused_variables))]
+pub fn restrict(path: &Path, mode: u32) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+    }
+    Ok(())
+}
+
+/// Opens the file for appending, creating it (and its directory) readable by the
+/// current user only.
+pub fn append_private(path: &Path) -> io::Result<File> {
+    if let Some(parent) = path.parent() {
+        create_private_dir(parent)?;
+    }
+    let mut options = OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let file = options.open(path)?;
+    restrict(path, 0o600)?;
+    Ok(file)
+}
+
+fn home_dir() -> Option<PathBuf> {
+    env::var_os(if cfg!(windows) { "USERPROFILE" } else { "HOME" })
+        .map(PathBuf::from)
+        .filter(|path| !path.as_os_str().is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_private_files() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = assert_fs::TempDir::new().unwrap();
+        let path = dir.path().join("nested").join("audit.log");
+        append_private(&path).unwrap();
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(path.parent().unwrap()), 0o700);
+        assert_eq!(mode(&path), 0o600);
+
+        // left readable by others, e.g. by an older version or a copy
+        let path = dir.path().join("shared").join("audit.log");
+        fs::create_dir(path.parent().unwrap()).unwrap();
+        fs::write(&path, "").unwrap();
+        fs::set_permissions(path.parent().unwrap(), fs::Permissions::from_mode(0o755)).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        append_private(&path).unwrap();
+        assert_eq!(mode(path.parent().unwrap()), 0o700);
+        assert_eq!(mode(&path), 0o600);
+    }
+}
@@ -0,0 +1,179 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write},
+};
+
+use crossterm::{
+    cursor::MoveUp,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    queue,
+    style::Print,
+    terminal::{self, Clear, ClearType},
+};
+
+use crate::dialog;
+
+/// Lets the user pick one of `items`, starting at `selected`: with the arrow keys in
+/// the terminal when there is one, in a native list dialog otherwise (Prism gives the
+/// wrapper no terminal). `None` when cancelled or nothing could be shown.
+pub fn pick(prompt: &str, items: &[String], selected: usize) -> Option<usize> {
+    if let Some(picked) = pick_in_terminal(prompt, items, selected) {
+        return picked;
+    }
+    dialog::choose(prompt, items, selected)
+}
+
+/// Asks for a password: on the terminal without echoing it when there is one, in a
+/// native dialog otherwise. `None` when cancelled or nothing could be shown.
+pub fn ask_secret(prompt: &str) -> Option<String> {
+    if let Some(secret) = read_hidden(prompt) {
+        return secret;
+    }
+    dialog::ask_secret(prompt)
+}
+
+/// Where the cursor goes for a key, or what was picked.
+#[derive(Debug, PartialEq)]
+enum Key {
+    Up,
+    Down,
+    Enter,
+    Cancel,
+    Other,
+}
+
+fn parse_key(key: KeyEvent) -> Key {
+    let control = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Up | KeyCode::Char('k') => Key::Up,
+        KeyCode::Down | KeyCode::Char('j') => Key::Down,
+        KeyCode::Enter => Key::Enter,
+        KeyCode::Char('c') if control => Key::Cancel,
+        KeyCode::Esc | KeyCode::Char('q') => Key::Cancel,
+        _ => Key::Other,
+    }
+}
+
+/// `None` when there is no terminal, `Some(None)` when the user cancelled.
+fn pick_in_terminal(prompt: &str, items: &[String], selected: usize) -> Option<Option<usize>> {
+    let mut terminal = Terminal::open()?;
+    let mut selected = selected.min(items.len().saturating_sub(1));
+    let _ = queue!(terminal.output, Print(format!("{}\r\n", prompt)));
+    terminal.draw(items, selected);
+    let picked = loop {
+        let Some(key) = next_key() else {
+            break None;
+        };
+        match parse_key(key) {
+            Key::Up => selected = selected.saturating_sub(1),
+            Key::Down => selected = (selected + 1).min(items.len() - 1),
+            Key::Enter => break Some(selected),
+            Key::Cancel => break None,
+            Key::Other => continue,
+        }
+        // back to the first item and draw the list again
+        let _ = queue!(terminal.output, MoveUp(items.len() as u16));
+        terminal.draw(items, selected);
+    };
+    Some(picked)
+}
+
+/// `None` when there is no terminal, `Some(None)` when the user cancelled.
+fn read_hidden(prompt: &str) -> Option<Option<String>> {
+    let mut terminal = Terminal::open()?;
+    let _ = write!(terminal.output, "{} ", prompt);
+    let _ = terminal.output.flush();
+    let mut secret = String::new();
+    let read = loop {
+        let Some(key) = next_key() else {
+            break None;
+        };
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Enter => break Some(secret),
+            KeyCode::Char('c' | 'd') if control => break None,
+            KeyCode::Esc => break None,
+            KeyCode::Backspace => {
+                secret.pop();
+            }
+            KeyCode::Char(c) => secret.push(c),
+            _ => {}
+        }
+    };
+    // nothing was echoed, not even the newline
+    let _ = write!(terminal.output, "\r\n");
+    Some(read)
+}
+
+/// The next key pressed, `None` when the terminal can't be read.
+fn next_key() -> Option<KeyEvent> {
+    loop {
+        match event::read().ok()? {
+            // Windows reports releases too
+            Event::Key(key) if key.kind != KeyEventKind::Release => return Some(key),
+            _ => {}
+        }
+    }
+}
+
+/// The terminal in raw mode, so keys arrive one by one and aren't shown, until dropped.
+/// Keys are read from the terminal itself rather than stdin, which has the launcher's
+/// params.
+struct Terminal {
+    output: File,
+}
+
+impl Terminal {
+    fn open() -> Option<Terminal> {
+        let output = open_output().ok()?;
+        terminal::enable_raw_mode().ok()?;
+        Some(Terminal { output })
+    }
+
+    fn draw(&mut self, items: &[String], selected: usize) {
+        for (index, item) in items.iter().enumerate() {
+            let marker = if index == selected { ">" } else { " " };
+            let _ = queue!(
+                self.output,
+                Clear(ClearType::CurrentLine),
+                Print(format!("{} {}\r\n", marker, item))
+            );
+        }
+        let _ = self.output.flush();
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+#[cfg(unix)]
+fn open_output() -> io::Result<File> {
+    OpenOptions::new().write(true).open("/dev/tty")
+}
+
+#[cfg(windows)]
+fn open_output() -> io::Result<File> {
+    OpenOptions::new().write(true).open("CONOUT$")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_key() {
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        assert_eq!(parse_key(key(KeyCode::Up)), Key::Up);
+        assert_eq!(parse_key(key(KeyCode::Char('j'))), Key::Down);
+        assert_eq!(parse_key(key(KeyCode::Enter)), Key::Enter);
+        assert_eq!(parse_key(key(KeyCode::Esc)), Key::Cancel);
+        assert_eq!(
+            parse_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+            Key::Cancel
+        );
+        assert_eq!(parse_key(key(KeyCode::Char('x'))), Key::Other);
+    }
+}
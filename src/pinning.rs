@@ -0,0 +1,407 @@
+use std::{error::Error, fmt, fs, io, path::Path, sync::Arc};
+
+use base64::prelude::*;
+use ring::digest::{digest, SHA256};
+use rustls::{
+    client::{
+        danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+        WebPkiServerVerifier,
+    },
+    crypto::{self, ring::default_provider, CryptoProvider},
+    pki_types::{pem::PemObject, CertificateDer, ServerName, UnixTime},
+    CertificateError, ClientConfig, DigitallySignedStruct, OtherError, RootCertStore,
+    SignatureScheme,
+};
+
+use crate::config::HttpConfig;
+use crate::errors::MmcaiError;
+use crate::http::HttpClient;
+use crate::Result;
+
+pub fn is_enabled(config: &HttpConfig) -> bool {
+    !config.pinned_public_keys.is_empty() || !config.pinned_certificates.is_empty()
+}
+
+/// The TLS setup of a client whose every connection must present a pinned certificate.
+///
+/// The pins are checked during the handshake, so nothing is sent on a connection to a
+/// server that doesn't match them, whichever host it goes to and whether or not it is a
+/// new one. The chain is still validated against the bundled roots and
+/// `ca_certificates`, unless `danger_accept_invalid_certs` is set: then the pin is the
+/// only check, as for a self-signed certificate.
+pub fn tls_config(config: &HttpConfig) -> Result<ClientConfig> {
+    let provider = Arc::new(default_provider());
+    let chain = if config.danger_accept_invalid_certs {
+        None
+    } else {
+        let mut roots = RootCertStore {
+            roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+        };
+        for path in &config.ca_certificates {
+            for certificate in load_certificates(path)? {
+                roots
+                    .add(certificate)
+                    .map_err(|_| invalid_certificate(path))?;
+            }
+        }
+        let verifier =
+            WebPkiServerVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+                .build()
+                .map_err(|_| MmcaiError::Other)?;
+        Some(verifier)
+    };
+    let verifier = PinningVerifier {
+        chain,
+        provider: provider.clone(),
+        pins: Pins::from(config),
+    };
+
+    let mut tls = ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|_| MmcaiError::Other)?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(verifier))
+        .with_no_client_auth();
+    // reqwest only negotiates HTTP/2 on its own TLS configurations
+    tls.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(tls)
+}
+
+fn load_certificates(path: &Path) -> Result<Vec<CertificateDer<'static>>> {
+    let pem = fs::read(path).map_err(|source| MmcaiError::ReadCertificateFailed {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let certificates = CertificateDer::pem_slice_iter(&pem)
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|_| invalid_certificate(path))?;
+    if certificates.is_empty() {
+        return Err(invalid_certificate(path));
+    }
+    Ok(certificates)
+}
+
+fn invalid_certificate(path: &Path) -> MmcaiError {
+    MmcaiError::InvalidCertificate {
+        path: path.to_path_buf(),
+        source: None,
+    }
+}
+
+/// The pin check failing during a handshake, carried through rustls and reqwest.
+#[derive(Debug)]
+struct PinMismatch {
+    host: String,
+}
+
+impl fmt::Display for PinMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the certificate of {} is not pinned", self.host)
+    }
+}
+
+impl Error for PinMismatch {}
+
+#[derive(Debug)]
+struct PinningVerifier {
+    chain: Option<Arc<WebPkiServerVerifier>>,
+    provider: Arc<CryptoProvider>,
+    pins: Pins,
+}
+
+impl ServerCertVerifier for PinningVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> std::result::Result<ServerCertVerified, rustls::Error> {
+        if let Some(chain) = &self.chain {
+            chain.verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now)?;
+        }
+        if self.pins.matches(end_entity) {
+            Ok(ServerCertVerified::assertion())
+        } else {
+            let mismatch = PinMismatch {
+                host: server_name.to_str().into_owned(),
+            };
+            Err(rustls::Error::InvalidCertificate(CertificateError::Other(
+                OtherError(Arc::new(mismatch)),
+            )))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        certificate: &CertificateDer<'_>,
+        signature: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        crypto::verify_tls12_signature(
+            message,
+            certificate,
+            signature,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        certificate: &CertificateDer<'_>,
+        signature: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        crypto::verify_tls13_signature(
+            message,
+            certificate,
+            signature,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+/// Turns a failed request into the pinning error it really is, or else `otherwise`.
+///
+/// A connection the pins rejected fails like any other unreachable server, which must not
+/// be mistaken for one: that would fall back to cached data or a retry elsewhere. Plain
+/// `http://` URLs are refused outright by a pinning client, as they have no certificate.
+pub fn classify(
+    error: reqwest::Error,
+    otherwise: impl FnOnce(reqwest::Error) -> MmcaiError,
+) -> MmcaiError {
+    if let Some(mismatch) = find_mismatch(&error) {
+        return MmcaiError::CertificatePinMismatch {
+            host: mismatch.host.clone(),
+        };
+    }
+    match error.url() {
+        Some(url) if error.is_builder() && url.scheme() == "http" => {
+            MmcaiError::CertificatePinUnavailable {
+                host: url.host_str().unwrap_or_default().to_string(),
+            }
+        }
+        _ => otherwise(error),
+    }
+}
+
+fn find_mismatch(error: &reqwest::Error) -> Option<&PinMismatch> {
+    let mut current: Option<&(dyn Error + 'static)> = Some(error);
+    while let Some(error) = current {
+        if let Some(rustls::Error::InvalidCertificate(CertificateError::Other(other))) =
+            error.downcast_ref::<rustls::Error>()
+        {
+            return other.0.downcast_ref::<PinMismatch>();
+        }
+        // io::Error hides the error it wraps from source(), so it is unwrapped by hand
+        current = match error.downcast_ref::<io::Error>() {
+            Some(error) => error.get_ref().map(|inner| inner as &(dyn Error + 'static)),
+            None => error.source(),
+        };
+    }
+    None
+}
+
+/// Makes a request just to connect to the server, so that its certificate is checked
+/// against the pins before anything depends on the server being the right one.
+pub async fn check_server(http: &HttpClient, url: &str) -> Result<()> {
+    http.send(|client| client.head(url))
+        .await
+        .map_err(|e| classify(e, MmcaiError::YggdrasilHelloFailed))?;
+    Ok(())
+}
+
+/// The configured pins, parsed once for all handshakes.
+#[derive(Debug)]
+struct Pins {
+    /// Base64 SHA-256 hashes of the subjectPublicKeyInfo.
+    public_keys: Vec<String>,
+    /// SHA-256 hashes of the whole certificate.
+    certificates: Vec<Vec<u8>>,
+}
+
+impl From<&HttpConfig> for Pins {
+    fn from(config: &HttpConfig) -> Pins {
+        Pins {
+            public_keys: config
+                .pinned_public_keys
+                .iter()
+                .map(|pin| normalize_public_key_pin(pin).to_string())
+                .collect(),
+            certificates: config
+                .pinned_certificates
+                .iter()
+                .filter_map(|pin| parse_fingerprint(pin))
+                .collect(),
+        }
+    }
+}
+
+impl Pins {
+    fn matches(&self, certificate: &[u8]) -> bool {
+        let certificate_fingerprint = digest(&SHA256, certificate);
+        let certificate_pinned = self
+            .certificates
+            .iter()
+            .any(|pin| pin == certificate_fingerprint.as_ref());
+
+        let public_key_pinned = subject_public_key_info(certificate).is_some_and(|spki| {
+            let spki_hash = BASE64_STANDARD.encode(digest(&SHA256, spki));
+            self.public_keys.contains(&spki_hash)
+        });
+
+        certificate_pinned || public_key_pinned
+    }
+}
+
+/// Accepts `AB:CD:...` as printed by `openssl x509 -fingerprint -sha256`, with or without colons.
+fn parse_fingerprint(pin: &str) -> Option<Vec<u8>> {
+    let hex: String = pin.chars().filter(|c| *c != ':' && *c != ' ').collect();
+    if hex.len() != 64 || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// Accepts curl's `sha256//<base64>` as well as the HPKP-style `sha256/<base64>`.
+fn normalize_public_key_pin(pin: &str) -> &str {
+    pin.trim()
+        .trim_start_matches("sha256/")
+        .trim_start_matches('/')
+}
+
+/// Returns the DER element of the `subjectPublicKeyInfo` field of an X.509 certificate.
+fn subject_public_key_info(certificate: &[u8]) -> Option<&[u8]> {
+    let (_, certificate, _) = der_element(certificate)?;
+    let (_, tbs_certificate, _) = der_element(certificate)?;
+
+    let mut rest = tbs_certificate;
+    // the version field is an explicitly tagged, optional [0]
+    if rest.first() == Some(&0xa0) {
+        rest = der_element(rest)?.2;
+    }
+    // serialNumber, signature, issuer, validity, subject
+    for _ in 0..5 {
+        rest = der_element(rest)?.2;
+    }
+    let (whole, _, _) = der_element(rest)?;
+    Some(whole)
+}
+
+/// Splits the first DER element off `input`, returning (whole element, contents, rest).
+pub fn der_element(input: &[u8]) -> Option<(&[u8], &[u8], &[u8])> {
+    let first_length_byte = *input.get(1)?;
+    let (header_length, content_length) = if first_length_byte < 0x80 {
+        (2, first_length_byte as usize)
+    } else {
+        let length_bytes = (first_length_byte & 0x7f) as usize;
+        if length_bytes == 0 || length_bytes > 4 {
+            return None;
+        }
+        let length = input
+            .get(2..2 + length_bytes)?
+            .iter()
+            .fold(0usize, |acc, b| (acc << 8) | *b as usize);
+        (2 + length_bytes, length)
+    };
+    let end = header_length.checked_add(content_length)?;
+    if end > input.len() {
+        return None;
+    }
+    Some((&input[..end], &input[header_length..end], &input[end..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+
+    use super::*;
+
+    /// A tiny hand-assembled certificate skeleton: only the structure matters here.
+    fn fake_certificate() -> Vec<u8> {
+        let version = [0xa0, 0x03, 0x02, 0x01, 0x02];
+        let serial = [0x02, 0x01, 0x01];
+        let signature = [0x30, 0x00];
+        let issuer = [0x30, 0x00];
+        let validity = [0x30, 0x00];
+        let subject = [0x30, 0x00];
+        let spki = [0x30, 0x03, 0x03, 0x01, 0x00];
+        let tbs_contents = [
+            &version[..],
+            &serial,
+            &signature,
+            &issuer,
+            &validity,
+            &subject,
+            &spki,
+        ]
+        .concat();
+        let tbs = [&[0x30, tbs_contents.len() as u8][..], &tbs_contents].concat();
+        let certificate_contents = [&tbs[..], &signature, &[0x03, 0x01, 0x00]].concat();
+        [
+            &[0x30, 0x81, certificate_contents.len() as u8][..],
+            &certificate_contents,
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn test_subject_public_key_info() {
+        assert_eq!(
+            subject_public_key_info(&fake_certificate()),
+            Some(&[0x30, 0x03, 0x03, 0x01, 0x00][..])
+        );
+        assert_eq!(subject_public_key_info(&[0x30, 0x05, 0x30]), None);
+        assert_eq!(subject_public_key_info(&[]), None);
+    }
+
+    #[test]
+    fn test_plain_http_refused() {
+        let mut config = Config::default();
+        config.http.pinned_public_keys =
+            vec!["sha256//AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=".into()];
+        let http = HttpClient::new(&config, None).unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        assert!(matches!(
+            runtime.block_on(check_server(&http, "http://127.0.0.1:1/api")),
+            Err(MmcaiError::CertificatePinUnavailable { host }) if host == "127.0.0.1"
+        ));
+    }
+
+    #[test]
+    fn test_pins_match() {
+        let certificate = fake_certificate();
+        let spki_pin = BASE64_STANDARD.encode(digest(&SHA256, &[0x30, 0x03, 0x03, 0x01, 0x00]));
+        let fingerprint: Vec<String> = digest(&SHA256, &certificate)
+            .as_ref()
+            .iter()
+            .map(|b| format!("{:02X}", b))
+            .collect();
+
+        let mut config = HttpConfig::default();
+        assert!(!Pins::from(&config).matches(&certificate));
+
+        config.pinned_public_keys = vec![format!("sha256//{}", spki_pin)];
+        assert!(Pins::from(&config).matches(&certificate));
+
+        config.pinned_public_keys =
+            vec!["sha256//AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=".into()];
+        assert!(!Pins::from(&config).matches(&certificate));
+
+        config.pinned_certificates = vec![fingerprint.join(":")];
+        assert!(Pins::from(&config).matches(&certificate));
+    }
+}
@@ -0,0 +1,172 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{Duration, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::i18n::tr;
+use crate::{dates, paths};
+
+const PLAYTIME_FILE_NAME: &str = "playtime.jsonl";
+
+/// One run of the game, in seconds since the Unix epoch.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Session {
+    pub account: String,
+    /// Prism's instance name, when the wrapper ran under Prism.
+    pub instance: Option<String>,
+    pub start: u64,
+    pub end: u64,
+}
+
+impl Session {
+    fn seconds(&self) -> u64 {
+        self.end.saturating_sub(self.start)
+    }
+}
+
+/// `playtime.jsonl` in the per-user data directory, one session per line.
+pub fn path() -> Option<PathBuf> {
+    Some(paths::data_dir()?.join(PLAYTIME_FILE_NAME))
+}
+
+/// Appends a finished session. Failing to do so is reported but never fails the launch.
+pub fn record(session: &Session) {
+    let Some(path) = path() else {
+        return;
+    };
+    if let Err(e) = append(&path, session) {
+        let path = format!("{:?}", path);
+        warn!("{}", tr!("playtime-write-failed", path = path, error = e));
+    }
+}
+
+fn append(path: &Path, session: &Session) -> io::Result<()> {
+    let mut file = paths::append_private(path)?;
+    writeln!(file, "{}", serde_json::to_string(session)?)
+}
+
+/// Reads every recorded session; lines that don't parse are skipped.
+pub fn load(path: &Path) -> Vec<Session> {
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// `mmcai_rs --cmd stats`: total playtime per account and instance, and per week.
+pub fn print_stats() {
+    let sessions = path().map(|path| load(&path)).unwrap_or_default();
+    print!("{}", format_stats(&sessions));
+}
+
+fn format_stats(sessions: &[Session]) -> String {
+    if sessions.is_empty() {
+        return format!("{}\n", tr!("stats-empty"));
+    }
+
+    let total: u64 = sessions.iter().map(Session::seconds).sum();
+    let mut per_game: BTreeMap<(&str, &str), u64> = BTreeMap::new();
+    let mut per_week: BTreeMap<u64, u64> = BTreeMap::new();
+    for session in sessions {
+        let instance = session.instance.as_deref().unwrap_or("-");
+        *per_game.entry((&session.account, instance)).or_default() += session.seconds();
+        *per_week.entry(week_start(session.start)).or_default() += session.seconds();
+    }
+
+    let mut stats = format!(
+        "{}\n",
+        tr!("stats-total", duration = format_duration(total))
+    );
+    for ((account, instance), seconds) in per_game {
+        stats += &format!(
+            "  {} / {}: {}\n",
+            account,
+            instance,
+            format_duration(seconds)
+        );
+    }
+    stats += &format!("{}\n", tr!("stats-per-week"));
+    for (week, seconds) in per_week {
+        let monday = dates::format_iso8601(UNIX_EPOCH + Duration::from_secs(week));
+        stats += &format!("  {}: {}\n", &monday[..10], format_duration(seconds));
+    }
+    stats
+}
+
+/// The Monday 00:00 UTC of the week `time` falls in. The epoch was a Thursday.
+fn week_start(time: u64) -> u64 {
+    let days = time / 86400;
+    (days - (days + 3) % 7) * 86400
+}
+
+fn format_duration(seconds: u64) -> String {
+    format!("{}h {:02}m", seconds / 3600, seconds / 60 % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(year: i64, month: u32, day: u32, hours: u32) -> u64 {
+        dates::from_civil(year, month, day, hours, 0, 0)
+            .unwrap()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    #[test]
+    fn test_week_start() {
+        // 2025-04-03 is a Thursday, 2025-03-31 the Monday before
+        assert_eq!(week_start(at(2025, 4, 3, 15)), at(2025, 3, 31, 0));
+        assert_eq!(week_start(at(2025, 3, 31, 0)), at(2025, 3, 31, 0));
+        assert_eq!(week_start(at(2025, 4, 6, 23)), at(2025, 3, 31, 0));
+    }
+
+    #[test]
+    fn test_format_stats() {
+        let session = |instance: &str, start: u64, hours: u64| Session {
+            account: "Steve".into(),
+            instance: Some(instance.into()),
+            start,
+            end: start + hours * 3600 + 300,
+        };
+        let sessions = [
+            session("Marallys", at(2025, 4, 1, 18), 2),
+            session("Marallys", at(2025, 4, 8, 18), 1),
+            session("Vanilla", at(2025, 4, 9, 18), 0),
+        ];
+        assert_eq!(
+            format_stats(&sessions),
+            "Total playtime: 3h 15m\n  \
+             Steve / Marallys: 3h 10m\n  \
+             Steve / Vanilla: 0h 05m\n\
+             Per week:\n  \
+             2025-03-31: 2h 05m\n  \
+             2025-04-07: 1h 10m\n"
+        );
+        assert_eq!(format_stats(&[]), "No playtime recorded yet.\n");
+    }
+
+    #[test]
+    fn test_load_skips_bad_lines() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let path = dir.path().join(PLAYTIME_FILE_NAME);
+        let session = Session {
+            account: "Steve".into(),
+            instance: None,
+            start: 10,
+            end: 70,
+        };
+        append(&path, &session).unwrap();
+        fs::write(&path, fs::read_to_string(&path).unwrap() + "{not json\n").unwrap();
+        assert_eq!(load(&path), vec![session]);
+    }
+}
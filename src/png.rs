@@ -0,0 +1,147 @@
+use ::png::{BitDepth, ColorType, Decoder, Encoder, Transformations};
+
+use crate::skin::PNG_SIGNATURE;
+
+/// HD skins go up to 1024 pixels wide, anything past this is not a skin.
+const MAX_SIZE: u32 = 4096;
+
+/// An 8-bit RGBA image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    /// Row by row, 4 bytes per pixel.
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    pub fn new(width: u32, height: u32) -> Image {
+        Image {
+            width,
+            height,
+            pixels: vec![0; width as usize * height as usize * 4],
+        }
+    }
+
+    /// The pixel's RGBA, transparent outside the image.
+    pub fn get(&self, x: u32, y: u32) -> [u8; 4] {
+        if x >= self.width || y >= self.height {
+            return [0; 4];
+        }
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        [
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        ]
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, rgba: [u8; 4]) {
+        if x < self.width && y < self.height {
+            let i = (y as usize * self.width as usize + x as usize) * 4;
+            self.pixels[i..i + 4].copy_from_slice(&rgba);
+        }
+    }
+}
+
+/// The width and height from the IHDR chunk, which has to come first; `None` when
+/// `png` is not a PNG image.
+pub fn dimensions(png: &[u8]) -> Option<(u32, u32)> {
+    let header = png.strip_prefix(PNG_SIGNATURE)?;
+    if header.get(..8)? != b"\0\0\0\x0dIHDR" {
+        return None;
+    }
+    let width = u32::from_be_bytes(header.get(8..12)?.try_into().ok()?);
+    let height = u32::from_be_bytes(header.get(12..16)?.try_into().ok()?);
+    Some((width, height))
+}
+
+/// Decodes a PNG of any color type and bit depth into 8-bit RGBA. The error says why
+/// it couldn't be read.
+pub fn decode(png: &[u8]) -> Result<Image, String> {
+    let mut decoder = Decoder::new(png);
+    // palettes and transparency chunks are expanded, 16-bit channels cut to 8 bits
+    decoder.set_transformations(Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+    let (width, height) = reader.info().size();
+    if width > MAX_SIZE || height > MAX_SIZE {
+        return Err(format!("unexpected size {}x{}", width, height));
+    }
+    let mut data = vec![0; reader.output_buffer_size()];
+    let frame = reader.next_frame(&mut data).map_err(|e| e.to_string())?;
+
+    let mut image = Image::new(width, height);
+    let channels = frame.color_type.samples();
+    let pixels = data[..frame.buffer_size()].chunks_exact(channels);
+    for (rgba, pixel) in image.pixels.chunks_exact_mut(4).zip(pixels) {
+        rgba.copy_from_slice(&match frame.color_type {
+            ColorType::Grayscale => [pixel[0], pixel[0], pixel[0], 255],
+            ColorType::GrayscaleAlpha => [pixel[0], pixel[0], pixel[0], pixel[1]],
+            ColorType::Rgb => [pixel[0], pixel[1], pixel[2], 255],
+            ColorType::Rgba => [pixel[0], pixel[1], pixel[2], pixel[3]],
+            // expanded to RGB(A) by the transformations
+            ColorType::Indexed => [0; 4],
+        });
+    }
+    Ok(image)
+}
+
+/// Encodes the image as an RGBA PNG.
+pub fn encode(image: &Image) -> Vec<u8> {
+    let mut png = Vec::new();
+    let mut encoder = Encoder::new(&mut png, image.width, image.height);
+    encoder.set_color(ColorType::Rgba);
+    encoder.set_depth(BitDepth::Eight);
+    // writing to a Vec doesn't fail, and the pixels always match the size
+    if let Ok(mut writer) = encoder.write_header() {
+        let _ = writer.write_image_data(&image.pixels);
+        let _ = writer.finish();
+    }
+    png
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut image = Image::new(3, 2);
+        image.set(0, 0, [255, 0, 0, 255]);
+        image.set(2, 1, [10, 20, 30, 128]);
+        let decoded = decode(&encode(&image)).unwrap();
+        assert_eq!(decoded, image);
+        assert_eq!(decoded.get(5, 5), [0; 4]);
+    }
+
+    #[test]
+    fn test_decode_palette() {
+        let mut png = Vec::new();
+        let mut encoder = Encoder::new(&mut png, 2, 1);
+        encoder.set_color(ColorType::Indexed);
+        encoder.set_depth(BitDepth::Eight);
+        encoder.set_palette(vec![255, 0, 0, 0, 0, 255]);
+        encoder.set_trns(vec![255, 0]);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&[0, 1]).unwrap();
+        writer.finish().unwrap();
+
+        let image = decode(&png).unwrap();
+        assert_eq!(image.get(0, 0), [255, 0, 0, 255]);
+        assert_eq!(image.get(1, 0), [0, 0, 255, 0]);
+    }
+
+    #[test]
+    fn test_dimensions() {
+        assert_eq!(dimensions(&encode(&Image::new(64, 32))), Some((64, 32)));
+        assert_eq!(dimensions(PNG_SIGNATURE), None);
+        assert_eq!(dimensions(b"GIF89a"), None);
+    }
+
+    #[test]
+    fn test_decode_rejects() {
+        assert!(decode(b"GIF89a").is_err());
+        assert!(decode(PNG_SIGNATURE).is_err());
+    }
+}
@@ -0,0 +1,180 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use ring::digest::{digest, SHA256};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::MmcaiError;
+use crate::{dates, instance, paths, session, Result};
+
+const PRELAUNCH_DIR_NAME: &str = "prelaunch";
+/// A hand-off older than this is from an earlier launch and is not used.
+const HANDOFF_MAX_AGE: u64 = 5 * 60;
+
+/// The login done by `mmcai_rs --cmd prelaunch`, for the wrapper of the same launch.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Handoff {
+    /// The login name the account signed in with.
+    pub username: String,
+    /// The API URL of the account, as configured.
+    pub account_api_url: String,
+    /// The API URL authlib-injector is given.
+    pub api_url: String,
+    pub prefetched_data: Option<String>,
+    pub client_token: String,
+    pub access_token: String,
+    pub uuid: String,
+    pub name: String,
+    pub written_at: u64,
+}
+
+/// The hand-off files of this launch, without extension: in the private data directory,
+/// named after the instance so instances launched at the same time don't take each
+/// other's login.
+fn base_path() -> Option<PathBuf> {
+    let instance = instance::dir().unwrap_or_default();
+    let hash: String = digest(&SHA256, instance.as_os_str().as_encoded_bytes())
+        .as_ref()
+        .iter()
+        .take(8)
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    Some(paths::data_dir()?.join(PRELAUNCH_DIR_NAME).join(hash))
+}
+
+/// Writes the hand-off for the wrapper, and the same login as `KEY="value"` lines for
+/// scripts that start the game themselves. Returns the environment file.
+pub fn write(handoff: &Handoff, authlib_injector: &Path) -> Result<PathBuf> {
+    let base_path = base_path().ok_or_else(|| MmcaiError::WritePrelaunchFailed {
+        path: PathBuf::from(PRELAUNCH_DIR_NAME),
+        source: io::Error::new(io::ErrorKind::NotFound, "no data directory"),
+    })?;
+    remove_stale(base_path.parent().unwrap_or(&base_path));
+    let write = |extension: &str, contents: io::Result<Vec<u8>>| {
+        let path = base_path.with_extension(extension);
+        contents
+            .and_then(|contents| session::write_private(&path, &contents))
+            .map_err(|source| MmcaiError::WritePrelaunchFailed {
+                path: path.clone(),
+                source,
+            })
+            .map(|_| path)
+    };
+    write("json", serde_json::to_vec(handoff).map_err(io::Error::from))?;
+    let env_path = base_path.with_extension("env");
+    write(
+        "env",
+        Ok(env_file(handoff, authlib_injector, &env_path).into_bytes()),
+    )
+}
+
+/// Hand-offs nobody took, e.g. because the launch was cancelled.
+fn remove_stale(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let max_age = Duration::from_secs(HANDOFF_MAX_AGE);
+    for entry in entries.flatten() {
+        let stale = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .is_ok_and(|modified| modified.elapsed().is_ok_and(|age| age > max_age));
+        if stale {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+/// The variables, then a line deleting the file, so sourcing it consumes the token.
+fn env_file(handoff: &Handoff, authlib_injector: &Path, path: &Path) -> String {
+    let javaagent = format!(
+        "-javaagent:{}={}",
+        authlib_injector.display(),
+        handoff.api_url
+    );
+    [
+        ("MMCAI_JAVAAGENT", javaagent.as_str()),
+        ("MMCAI_API_URL", &handoff.api_url),
+        ("MMCAI_ACCESS_TOKEN", &handoff.access_token),
+        ("MMCAI_UUID", &handoff.uuid),
+        ("MMCAI_NAME", &handoff.name),
+    ]
+    .iter()
+    .map(|(key, value)| format!("{}=\"{}\"\n", key, quote(value)))
+    .chain(std::iter::once(format!(
+        "rm -f \"{}\"\n",
+        quote(&path.to_string_lossy())
+    )))
+    .collect()
+}
+
+/// Escapes what a POSIX shell would expand inside double quotes.
+fn quote(value: &str) -> String {
+    value
+        .chars()
+        .flat_map(|c| match c {
+            '"' | '\\' | '$' | '`' => vec!['\\', c],
+            c => vec![c],
+        })
+        .collect()
+}
+
+/// The hand-off from this launch's pre-launch command, if there is a recent one for the
+/// account being launched. It is removed either way, so the token is only used once and
+/// doesn't stay on disk.
+pub fn take(username: &str, api_url: &str) -> Option<Handoff> {
+    let base_path = base_path()?;
+    let path = base_path.with_extension("json");
+    let contents = fs::read(&path).ok()?;
+    let _ = fs::remove_file(&path);
+    let _ = fs::remove_file(base_path.with_extension("env"));
+    let handoff: Handoff = serde_json::from_slice(&contents).ok()?;
+    handoff
+        .is_for(username, api_url, dates::unix_now())
+        .then_some(handoff)
+}
+
+impl Handoff {
+    fn is_for(&self, username: &str, api_url: &str, now: u64) -> bool {
+        self.username == username
+            && self.account_api_url == api_url
+            && now < self.written_at.saturating_add(HANDOFF_MAX_AGE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_file() {
+        let handoff = Handoff {
+            username: "steve@example.com".into(),
+            account_api_url: "https://auth.example.com/".into(),
+            api_url: "https://auth.example.com/api".into(),
+            prefetched_data: None,
+            client_token: "c".into(),
+            access_token: "t".into(),
+            uuid: "0f8d".into(),
+            name: "$teve".into(),
+            written_at: 0,
+        };
+        let env = env_file(
+            &handoff,
+            Path::new("/opt/authlib-injector.jar"),
+            Path::new("/data/prelaunch/0f.env"),
+        );
+        assert!(env.starts_with(
+            "MMCAI_JAVAAGENT=\"-javaagent:/opt/authlib-injector.jar=https://auth.example.com/api\"\n"
+        ));
+        assert!(env.ends_with("MMCAI_NAME=\"\\$teve\"\nrm -f \"/data/prelaunch/0f.env\"\n"));
+
+        assert!(handoff.is_for("steve@example.com", "https://auth.example.com/", 60));
+        assert!(!handoff.is_for("alex@example.com", "https://auth.example.com/", 60));
+        assert!(!handoff.is_for("steve@example.com", "https://other.example.com/", 60));
+        assert!(!handoff.is_for("steve@example.com", "https://auth.example.com/", 3600));
+    }
+}
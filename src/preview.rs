@@ -0,0 +1,167 @@
+use crate::png::Image;
+use crate::skin::Model;
+
+/// The front view is 16×32 skin pixels: head, body, arms and legs side by side.
+const WIDTH: u32 = 16;
+const HEIGHT: u32 = 32;
+
+/// A rectangle of the skin, in the coordinates of a 64 pixel wide one.
+struct Part {
+    /// Where the base layer's front face is.
+    base: (u32, u32),
+    /// Where the second layer's (hat, jacket, sleeves, pants) front face is.
+    overlay: Option<(u32, u32)>,
+    size: (u32, u32),
+    /// Where it goes in the front view.
+    at: (u32, u32),
+    /// Old 64×32 skins have no left limbs, the game mirrors the right ones.
+    mirror_of: Option<(u32, u32)>,
+}
+
+fn parts(model: Model) -> Vec<Part> {
+    let arm = match model {
+        Model::Classic => 4,
+        Model::Slim => 3,
+    };
+    let part = |base, overlay, size, at| Part {
+        base,
+        overlay,
+        size,
+        at,
+        mirror_of: None,
+    };
+    vec![
+        part((8, 8), Some((40, 8)), (8, 8), (4, 0)),
+        part((20, 20), Some((20, 36)), (8, 12), (4, 8)),
+        part((44, 20), Some((44, 36)), (arm, 12), (4 - arm, 8)),
+        Part {
+            mirror_of: Some((44, 20)),
+            ..part((36, 52), Some((52, 52)), (arm, 12), (12, 8))
+        },
+        part((4, 20), Some((4, 36)), (4, 12), (4, 20)),
+        Part {
+            mirror_of: Some((4, 20)),
+            ..part((20, 52), Some((4, 52)), (4, 12), (8, 20))
+        },
+    ]
+}
+
+/// Draws the skin from the front, second layer over the first, as the game shows it.
+/// HD skins are sampled down to 64×64. Parts of the second layer a legacy skin lacks
+/// fall outside the image and are transparent.
+pub fn front_view(skin: &Image, model: Model) -> Image {
+    let scale = (skin.width / 64).max(1);
+    let legacy = skin.height * 2 == skin.width;
+    let sample = |x: u32, y: u32| skin.get(x * scale, y * scale);
+
+    let mut view = Image::new(WIDTH, HEIGHT);
+    for part in parts(model) {
+        let (width, height) = part.size;
+        for dy in 0..height {
+            for dx in 0..width {
+                let mut pixel = match part.mirror_of {
+                    Some((x, y)) if legacy => sample(x + width - 1 - dx, y + dy),
+                    _ => sample(part.base.0 + dx, part.base.1 + dy),
+                };
+                pixel[3] = 255;
+                if let Some((x, y)) = part.overlay {
+                    pixel = blend(pixel, sample(x + dx, y + dy));
+                }
+                view.set(part.at.0 + dx, part.at.1 + dy, pixel);
+            }
+        }
+    }
+    view
+}
+
+/// `over` on top of `under`, which is opaque.
+fn blend(under: [u8; 4], over: [u8; 4]) -> [u8; 4] {
+    let alpha = over[3] as u16;
+    let mix = |a: u8, b: u8| ((b as u16 * alpha + a as u16 * (255 - alpha)) / 255) as u8;
+    [
+        mix(under[0], over[0]),
+        mix(under[1], over[1]),
+        mix(under[2], over[2]),
+        255,
+    ]
+}
+
+/// Each pixel blown up to a `factor`×`factor` square.
+pub fn enlarge(image: &Image, factor: u32) -> Image {
+    let mut large = Image::new(image.width * factor, image.height * factor);
+    for y in 0..large.height {
+        for x in 0..large.width {
+            large.set(x, y, image.get(x / factor, y / factor));
+        }
+    }
+    large
+}
+
+/// Renders the image with `▀`, each character showing two pixels above each other in
+/// 24-bit ANSI colors. Transparent pixels are left to the terminal's background.
+pub fn render_terminal(image: &Image) -> String {
+    let mut out = String::new();
+    for y in (0..image.height).step_by(2) {
+        for x in 0..image.width {
+            let (top, bottom) = (image.get(x, y), image.get(x, y + 1));
+            match (top[3] > 0, bottom[3] > 0) {
+                (false, false) => out.push(' '),
+                (true, false) => out.push_str(&format!("{}▀\x1b[0m", fg(top))),
+                (false, true) => out.push_str(&format!("{}▄\x1b[0m", fg(bottom))),
+                (true, true) => out.push_str(&format!(
+                    "{}\x1b[48;2;{};{};{}m▀\x1b[0m",
+                    fg(top),
+                    bottom[0],
+                    bottom[1],
+                    bottom[2]
+                )),
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn fg(rgba: [u8; 4]) -> String {
+    format!("\x1b[38;2;{};{};{}m", rgba[0], rgba[1], rgba[2])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_front_view() {
+        let mut skin = Image::new(64, 64);
+        // the face, with half of a hat over it
+        for y in 8..16 {
+            for x in 8..16 {
+                skin.set(x, y, [200, 0, 0, 255]);
+            }
+        }
+        skin.set(40, 8, [0, 0, 200, 255]);
+        skin.set(44, 20, [0, 200, 0, 255]);
+        let view = front_view(&skin, Model::Slim);
+        assert_eq!(view.get(4, 0), [0, 0, 200, 255]);
+        assert_eq!(view.get(5, 0), [200, 0, 0, 255]);
+        // slim arms start a pixel further in
+        assert_eq!(view.get(1, 8), [0, 200, 0, 255]);
+        assert_eq!(view.get(0, 8), [0; 4]);
+    }
+
+    #[test]
+    fn test_legacy_mirroring() {
+        let mut skin = Image::new(64, 32);
+        skin.set(4, 20, [1, 2, 3, 255]);
+        let view = front_view(&skin, Model::Classic);
+        assert_eq!(view.get(4, 20), [1, 2, 3, 255]);
+        assert_eq!(view.get(11, 20), [1, 2, 3, 255]);
+    }
+
+    #[test]
+    fn test_render_terminal() {
+        let mut image = Image::new(2, 2);
+        image.set(0, 0, [1, 2, 3, 255]);
+        assert_eq!(render_terminal(&image), "\x1b[38;2;1;2;3m▀\x1b[0m \n");
+    }
+}
@@ -0,0 +1,328 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde_json::{json, Value};
+
+use crate::accounts::{Account, AccountProfile, Accounts};
+use crate::dates;
+use crate::errors::MmcaiError;
+use crate::i18n::tr;
+use crate::paths;
+use crate::Result;
+
+const ACCOUNTS_FILE_NAME: &str = "accounts.json";
+
+/// Where Prism forks that support authlib-injector keep an account's server. Upstream
+/// Prism has no such accounts, but its legacy `Mojang` accounts are Yggdrasil ones too.
+const SERVER_KEYS: &[&str] = &[
+    "authlibInjectorUrl",
+    "authlibInjectorApiLocation",
+    "authServerUrl",
+];
+
+/// `mmcai_rs --cmd import prism [accounts.json]`: adds Prism's Yggdrasil accounts to the
+/// patcher's. Without a path, Prism's own `accounts.json` is read.
+pub fn import_accounts(path: Option<PathBuf>) -> Result<()> {
+    let not_found = |path: PathBuf| MmcaiError::ReadAccountsFailed {
+        path,
+        source: io::ErrorKind::NotFound.into(),
+    };
+    let path = match path.or_else(default_accounts_path) {
+        Some(path) => path,
+        None => return Err(not_found(ACCOUNTS_FILE_NAME.into())),
+    };
+    let imported = read_accounts(&path)?;
+    // without a home directory there is nowhere to keep the accounts
+    let store = Accounts::path().ok_or_else(|| MmcaiError::WriteAccountsFailed {
+        path: ACCOUNTS_FILE_NAME.into(),
+        source: io::ErrorKind::NotFound.into(),
+    })?;
+    let mut accounts = Accounts::load(&store)?;
+    for account in &imported {
+        let name = account
+            .profile
+            .as_ref()
+            .map_or(account.username.as_str(), |profile| profile.name.as_str());
+        if accounts.upsert(account.clone()) {
+            println!(
+                "{}",
+                tr!("import-added", account = account.username, name = name)
+            );
+        } else {
+            println!(
+                "{}",
+                tr!("import-updated", account = account.username, name = name)
+            );
+        }
+    }
+    accounts.save(&store)?;
+    println!(
+        "{}",
+        tr!(
+            "import-done",
+            count = imported.len(),
+            path = format!("{:?}", store)
+        )
+    );
+    if !imported.is_empty() {
+        println!("{}", tr!("import-no-passwords"));
+    }
+    Ok(())
+}
+
+/// Prism's `accounts.json`, in the first Prism data directory that has one.
+fn default_accounts_path() -> Option<PathBuf> {
+    paths::prism_dirs()
+        .into_iter()
+        .map(|dir| dir.join(ACCOUNTS_FILE_NAME))
+        .find(|path| path.is_file())
+}
+
+/// A session from the auth server, as `export prism` writes it into Prism's accounts.
+pub struct Session<'a> {
+    pub username: &'a str,
+    /// The API URL authlib-injector is given.
+    pub api_url: &'a str,
+    pub client_token: &'a str,
+    pub access_token: &'a str,
+    pub uuid: &'a str,
+    pub name: &'a str,
+}
+
+/// `mmcai_rs --cmd export prism`: adds the session to Prism's `accounts.json`, or updates the
+/// account with the same username and server. Returns the file that was written.
+pub fn export_session(path: Option<PathBuf>, session: &Session) -> Result<PathBuf> {
+    let path = match path.or_else(default_accounts_path) {
+        Some(path) => path,
+        None => {
+            return Err(MmcaiError::ReadAccountsFailed {
+                path: ACCOUNTS_FILE_NAME.into(),
+                source: io::ErrorKind::NotFound.into(),
+            })
+        }
+    };
+    let mut json = match fs::read_to_string(&path) {
+        Ok(text) => serde_json::from_str(&text).map_err(|e| MmcaiError::InvalidAccounts {
+            path: path.clone(),
+            message: e.to_string(),
+        })?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            json!({"accounts": [], "formatVersion": 3})
+        }
+        Err(source) => return Err(MmcaiError::ReadAccountsFailed { path, source }),
+    };
+    upsert_session(&mut json, session, dates::unix_now()).map_err(|message| {
+        MmcaiError::InvalidAccounts {
+            path: path.clone(),
+            message,
+        }
+    })?;
+    let text = serde_json::to_string_pretty(&json).map_err(|e| MmcaiError::InvalidAccounts {
+        path: path.clone(),
+        message: e.to_string(),
+    })?;
+    fs::write(&path, text).map_err(|source| MmcaiError::WriteAccountsFailed {
+        path: path.clone(),
+        source,
+    })?;
+    Ok(path)
+}
+
+/// Updates the entry for the session in the parsed `accounts.json`, keeping whatever
+/// else Prism stored in it, or adds one as the authlib-injector forks write them.
+fn upsert_session(
+    json: &mut Value,
+    session: &Session,
+    now: u64,
+) -> std::result::Result<(), String> {
+    let entries = json
+        .get_mut("accounts")
+        .and_then(Value::as_array_mut)
+        .ok_or("no \"accounts\" list, this is not Prism's accounts.json")?;
+    let is_same = |entry: &Value| {
+        parse_account(entry).is_some_and(|account| {
+            account.username.eq_ignore_ascii_case(session.username)
+                && account.api_url.as_deref() == Some(session.api_url)
+        })
+    };
+    let entry = match entries.iter().position(is_same) {
+        Some(index) => &mut entries[index],
+        None => {
+            entries.push(json!({"type": "AuthlibInjector"}));
+            entries.last_mut().unwrap()
+        }
+    };
+    let Some(entry) = entry.as_object_mut() else {
+        return Err("an account is not an object".into());
+    };
+    entry.insert("authlibInjectorUrl".into(), session.api_url.into());
+    let ygg = entry.entry("ygg").or_insert_with(|| json!({}));
+    ygg["token"] = session.access_token.into();
+    ygg["iat"] = now.into();
+    let extra = ygg
+        .as_object_mut()
+        .ok_or("an account's \"ygg\" is not an object")?
+        .entry("extra")
+        .or_insert_with(|| json!({}));
+    extra["clientToken"] = session.client_token.into();
+    extra["userName"] = session.username.into();
+    let profile = entry
+        .entry("profile")
+        .or_insert_with(|| json!({"capes": []}));
+    profile["id"] = session.uuid.into();
+    profile["name"] = session.name.into();
+    Ok(())
+}
+
+/// Reads the Yggdrasil accounts from Prism's `accounts.json` (format version 3).
+/// Microsoft and offline accounts are skipped; Prism doesn't store passwords.
+pub fn read_accounts(path: &Path) -> Result<Vec<Account>> {
+    let invalid = |message: String| MmcaiError::InvalidAccounts {
+        path: path.to_path_buf(),
+        message,
+    };
+    let text = fs::read_to_string(path).map_err(|source| MmcaiError::ReadAccountsFailed {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let json: Value = serde_json::from_str(&text).map_err(|e| invalid(e.to_string()))?;
+    let entries = json
+        .get("accounts")
+        .and_then(Value::as_array)
+        .ok_or_else(|| invalid("no \"accounts\" list, this is not Prism's accounts.json".into()))?;
+    Ok(entries.iter().filter_map(parse_account).collect())
+}
+
+fn parse_account(entry: &Value) -> Option<Account> {
+    let kind = entry
+        .get("type")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    if kind.eq_ignore_ascii_case("msa") || kind.eq_ignore_ascii_case("offline") {
+        return None;
+    }
+    let username = entry
+        .pointer("/ygg/extra/userName")
+        .and_then(Value::as_str)
+        .filter(|username| !username.is_empty())?;
+    let api_url = SERVER_KEYS
+        .iter()
+        .find_map(|key| entry.get(*key).and_then(Value::as_str))
+        .map(str::to_string);
+    let profile = entry.get("profile").and_then(|profile| {
+        Some(AccountProfile {
+            id: profile.get("id")?.as_str()?.to_string(),
+            name: profile.get("name")?.as_str()?.to_string(),
+        })
+    });
+    Some(Account {
+        name: String::new(),
+        username: username.to_string(),
+        api_url,
+        password: None,
+        profile,
+        client_token: entry
+            .pointer("/ygg/extra/clientToken")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_accounts() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let path = dir.path().join("accounts.json");
+        fs::write(
+            &path,
+            r#"{
+                "formatVersion": 3,
+                "accounts": [
+                    {"type": "MSA", "ygg": {"extra": {"userName": "ms@example.com"}}},
+                    {"type": "Offline", "profile": {"id": "1", "name": "Offline"}},
+                    {
+                        "type": "Mojang",
+                        "authlibInjectorUrl": "https://auth.example.com/api",
+                        "ygg": {"extra": {"clientToken": "c", "userName": "steve@example.com"}, "token": "t"},
+                        "profile": {"id": "0f8d", "name": "Steve", "capes": []}
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            read_accounts(&path).unwrap(),
+            vec![Account {
+                name: String::new(),
+                username: "steve@example.com".into(),
+                api_url: Some("https://auth.example.com/api".into()),
+                password: None,
+                profile: Some(AccountProfile {
+                    id: "0f8d".into(),
+                    name: "Steve".into(),
+                }),
+                client_token: Some("c".into()),
+            }]
+        );
+
+        fs::write(&path, r#"{"instances": []}"#).unwrap();
+        assert!(matches!(
+            read_accounts(&path),
+            Err(MmcaiError::InvalidAccounts { .. })
+        ));
+    }
+
+    #[test]
+    fn test_upsert_session() {
+        let session = Session {
+            username: "steve@example.com",
+            api_url: "https://auth.example.com/api",
+            client_token: "c2",
+            access_token: "t2",
+            uuid: "0f8d",
+            name: "Steve",
+        };
+        let mut json = json!({
+            "formatVersion": 3,
+            "accounts": [{
+                "type": "AuthlibInjector",
+                "active": true,
+                "authlibInjectorUrl": "https://auth.example.com/api",
+                "ygg": {"extra": {"clientToken": "c", "userName": "Steve@example.com"}, "token": "t"},
+                "profile": {"id": "0f8d", "name": "Alex", "capes": []}
+            }]
+        });
+        upsert_session(&mut json, &session, 1700000000).unwrap();
+        let entry = &json["accounts"][0];
+        assert_eq!(json["accounts"].as_array().unwrap().len(), 1);
+        assert_eq!(entry["active"], true);
+        assert_eq!(entry["ygg"]["token"], "t2");
+        assert_eq!(entry["ygg"]["iat"], 1700000000);
+        assert_eq!(entry["ygg"]["extra"]["clientToken"], "c2");
+        assert_eq!(entry["profile"]["name"], "Steve");
+        assert_eq!(entry["profile"]["capes"], json!([]));
+
+        let mut other_server = json.clone();
+        let session = Session {
+            api_url: "https://other.example.com/api",
+            ..session
+        };
+        upsert_session(&mut other_server, &session, 1700000000).unwrap();
+        let accounts: Vec<Account> = other_server["accounts"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(parse_account)
+            .collect();
+        assert_eq!(accounts.len(), 2);
+        assert_eq!(
+            accounts[1].api_url.as_deref(),
+            Some("https://other.example.com/api")
+        );
+    }
+}
@@ -0,0 +1,219 @@
+use serde::{Deserialize, Deserializer};
+use serde_json::{Map, Value};
+
+/// How much of a response body error messages quote.
+const EXCERPT_LENGTH: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    String,
+    /// A number, or a string holding one.
+    Number,
+    Array,
+    Object,
+}
+
+impl Kind {
+    fn matches(self, value: &Value) -> bool {
+        match self {
+            Kind::String => value.is_string(),
+            Kind::Number => {
+                value.is_number()
+                    || value
+                        .as_str()
+                        .is_some_and(|s| s.trim().parse::<f64>().is_ok())
+            }
+            Kind::Array => value.is_array(),
+            Kind::Object => value.is_object(),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Kind::String => "a string",
+            Kind::Number => "a number",
+            Kind::Array => "an array",
+            Kind::Object => "an object",
+        }
+    }
+}
+
+/// A field the patcher reads, by its dotted path in the response. `null` is accepted
+/// for every field that isn't required.
+pub struct Field {
+    pub path: &'static str,
+    pub kind: Kind,
+    pub required: bool,
+}
+
+impl Field {
+    pub const fn required(path: &'static str, kind: Kind) -> Field {
+        Field {
+            path,
+            kind,
+            required: true,
+        }
+    }
+
+    pub const fn optional(path: &'static str, kind: Kind) -> Field {
+        Field {
+            path,
+            kind,
+            required: false,
+        }
+    }
+}
+
+/// Says which field of `value` doesn't match `fields`, like "`data.uuid` is missing".
+/// Required fields are only checked when their parent object is present. With `strict`,
+/// fields that aren't listed are errors too, so server developers notice typos.
+pub fn check(value: &Value, fields: &[Field], strict: bool) -> Result<(), String> {
+    let Value::Object(root) = value else {
+        return Err(format!("expected an object, got {}", describe(value)));
+    };
+    for field in fields {
+        let (parent, name) = match field.path.rsplit_once('.') {
+            Some((parent, name)) => (lookup(root, parent), name),
+            None => (Some(root), field.path),
+        };
+        let Some(parent) = parent else { continue };
+        match parent.get(name) {
+            None | Some(Value::Null) if field.required => {
+                return Err(format!("`{}` is missing", field.path));
+            }
+            None | Some(Value::Null) => {}
+            Some(value) if !field.kind.matches(value) => {
+                return Err(format!(
+                    "`{}` should be {}, got {}",
+                    field.path,
+                    field.kind.name(),
+                    describe(value)
+                ));
+            }
+            Some(_) => {}
+        }
+    }
+    if strict {
+        if let Some(path) = unknown_field(root, "", fields) {
+            return Err(format!("unknown field `{}`", path));
+        }
+    }
+    Ok(())
+}
+
+fn lookup<'a>(root: &'a Map<String, Value>, path: &str) -> Option<&'a Map<String, Value>> {
+    path.split('.')
+        .try_fold(root, |object, name| object.get(name)?.as_object())
+}
+
+fn unknown_field(object: &Map<String, Value>, prefix: &str, fields: &[Field]) -> Option<String> {
+    for (name, value) in object {
+        let path = format!("{}{}", prefix, name);
+        if !fields.iter().any(|field| field.path == path) {
+            return Some(path);
+        }
+        if let Value::Object(child) = value {
+            if let Some(path) = unknown_field(child, &format!("{}.", path), fields) {
+                return Some(path);
+            }
+        }
+    }
+    None
+}
+
+fn describe(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(value) => format!("the boolean {}", value),
+        Value::Number(value) => format!("the number {}", value),
+        Value::String(value) => format!("the string {:?}", excerpt(value, 40)),
+        Value::Array(_) => "an array".to_string(),
+        Value::Object(_) => "an object".to_string(),
+    }
+}
+
+/// The start of a response body for error messages, with the full length when cut.
+pub fn body_excerpt(body: &str) -> String {
+    let length = body.chars().count();
+    if length <= EXCERPT_LENGTH {
+        body.to_string()
+    } else {
+        format!("{}… ({} characters)", excerpt(body, EXCERPT_LENGTH), length)
+    }
+}
+
+fn excerpt(value: &str, length: usize) -> String {
+    value.chars().take(length).collect()
+}
+
+/// `#[serde(deserialize_with)]` for fields some servers send as `null` instead of
+/// leaving them out.
+pub fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Option::unwrap_or_default)
+}
+
+/// `#[serde(deserialize_with)]` for status codes sent as `401` or `"401"`.
+pub fn lenient_u16<'de, D>(deserializer: D) -> Result<u16, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<Value>::deserialize(deserializer)? {
+        Some(Value::Number(number)) => {
+            Ok(number.as_u64().and_then(|n| n.try_into().ok()).unwrap_or(0))
+        }
+        Some(Value::String(text)) => Ok(text.trim().parse().unwrap_or(0)),
+        _ => Ok(0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    const FIELDS: &[Field] = &[
+        Field::optional("status", Kind::String),
+        Field::optional("statusCode", Kind::Number),
+        Field::optional("data", Kind::Object),
+        Field::required("data.uuid", Kind::String),
+        Field::optional("data.skin", Kind::String),
+    ];
+
+    #[test]
+    fn test_check() {
+        let ok = json!({"status": null, "statusCode": "200", "data": {"uuid": "u"}});
+        assert_eq!(check(&ok, FIELDS, false), Ok(()));
+        assert_eq!(check(&json!({"data": null}), FIELDS, false), Ok(()));
+        assert_eq!(
+            check(&json!({"data": {}}), FIELDS, false),
+            Err("`data.uuid` is missing".into())
+        );
+        assert_eq!(
+            check(&json!({"data": {"uuid": 5}}), FIELDS, false),
+            Err("`data.uuid` should be a string, got the number 5".into())
+        );
+        assert_eq!(
+            check(&json!("Bad Gateway"), FIELDS, false),
+            Err("expected an object, got the string \"Bad Gateway\"".into())
+        );
+
+        let extra = json!({"data": {"uuid": "u", "skn": "x"}});
+        assert_eq!(check(&extra, FIELDS, false), Ok(()));
+        assert_eq!(
+            check(&extra, FIELDS, true),
+            Err("unknown field `data.skn`".into())
+        );
+    }
+
+    #[test]
+    fn test_body_excerpt() {
+        assert_eq!(body_excerpt("short"), "short");
+        let long = "x".repeat(EXCERPT_LENGTH + 10);
+        assert!(body_excerpt(&long).ends_with(&format!("x… ({} characters)", long.len())));
+    }
+}
@@ -0,0 +1,266 @@
+use std::{
+    error::Error as StdError,
+    io,
+    time::{Duration, SystemTime},
+};
+
+use rand::Rng;
+use reqwest::{header::RETRY_AFTER, RequestBuilder, Response, StatusCode};
+
+use tracing::info;
+
+use crate::config::RetryConfig;
+use crate::dates;
+use crate::errors::MmcaiError;
+use crate::i18n::tr;
+use crate::trace::HttpTrace;
+use crate::Result;
+
+/// Retries requests that failed for reasons that have nothing to do with the request itself:
+/// connect timeouts, connection resets and gateway errors. Anything the server actually
+/// answered (including rejected credentials) is returned as-is.
+///
+/// HTTP 429 is handled separately: the policy waits for as long as `Retry-After` asks,
+/// as long as the total time spent waiting on rate limits stays below `max_rate_limit_wait`.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    attempts: u32,
+    backoff: Duration,
+    max_backoff: Duration,
+    jitter: bool,
+    max_rate_limit_wait: Duration,
+}
+
+impl From<&RetryConfig> for RetryPolicy {
+    fn from(config: &RetryConfig) -> Self {
+        RetryPolicy {
+            attempts: config.attempts.max(1),
+            backoff: Duration::from_millis(config.backoff_ms),
+            max_backoff: Duration::from_millis(config.max_backoff_ms),
+            jitter: config.jitter,
+            max_rate_limit_wait: Duration::from_secs(config.max_rate_limit_wait),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub async fn send<F>(
+        &self,
+        build_request: F,
+        trace: Option<&HttpTrace>,
+    ) -> reqwest::Result<Response>
+    where
+        F: Fn() -> RequestBuilder,
+    {
+        let mut attempt = 1;
+        let mut rate_limit_waited = Duration::ZERO;
+        loop {
+            let result = match trace {
+                Some(trace) => trace.send(build_request()).await,
+                None => build_request().send().await,
+            };
+
+            if let Ok(response) = &result {
+                if response.status() == StatusCode::TOO_MANY_REQUESTS {
+                    let wait = retry_after(response)
+                        .unwrap_or_else(|| self.delay(attempt))
+                        .max(Duration::from_secs(1));
+                    if rate_limit_waited + wait > self.max_rate_limit_wait {
+                        // the caller turns this into MmcaiError::RateLimited
+                        return result;
+                    }
+                    info!("{}", tr!("rate-limited-waiting", seconds = wait.as_secs()));
+                    tokio::time::sleep(wait).await;
+                    rate_limit_waited += wait;
+                    continue;
+                }
+            }
+
+            let retryable = match &result {
+                Ok(response) => is_retryable_status(response.status()),
+                Err(e) => is_transient_error(e),
+            };
+            if !retryable || attempt >= self.attempts {
+                return result;
+            }
+
+            let delay = self.delay(attempt);
+            let message = tr!(
+                "request-retry",
+                reason = describe(&result),
+                delay = format!("{:.1}", delay.as_secs_f32()),
+                attempt = attempt + 1,
+                attempts = self.attempts,
+            );
+            info!("{}", message);
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    fn delay(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .backoff
+            .saturating_mul(2u32.saturating_pow(attempt - 1))
+            .min(self.max_backoff);
+        if !self.jitter {
+            return exponential;
+        }
+        // "equal jitter": keep half of the delay, randomize the other half
+        let half = exponential / 2;
+        half + half.mul_f64(rand::rng().random::<f64>())
+    }
+}
+
+/// Turns a response the retry policy gave up on because of rate limiting into an error.
+pub fn check_rate_limit(response: &Response) -> Result<()> {
+    if response.status() != StatusCode::TOO_MANY_REQUESTS {
+        return Ok(());
+    }
+    Err(MmcaiError::RateLimited {
+        retry_after: retry_after(response).map_or(60, |wait| wait.as_secs().max(1)),
+    })
+}
+
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?;
+    parse_retry_after(value, SystemTime::now())
+}
+
+/// `Retry-After` is either a number of seconds or an HTTP date.
+fn parse_retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = parse_http_date(value)?;
+    Some(date.duration_since(now).unwrap_or(Duration::ZERO))
+}
+
+/// Parses the IMF-fixdate format, e.g. `Wed, 21 Oct 2015 07:28:00 GMT`.
+fn parse_http_date(value: &str) -> Option<SystemTime> {
+    let mut parts = value.split_whitespace();
+    let _weekday = parts.next()?;
+    let day: u32 = parts.next()?.parse().ok()?;
+    let month = match parts.next()? {
+        "Jan" => 1,
+        "Feb" => 2,
+        "Mar" => 3,
+        "Apr" => 4,
+        "May" => 5,
+        "Jun" => 6,
+        "Jul" => 7,
+        "Aug" => 8,
+        "Sep" => 9,
+        "Oct" => 10,
+        "Nov" => 11,
+        "Dec" => 12,
+        _ => return None,
+    };
+    let year: i64 = parts.next()?.parse().ok()?;
+    let mut time = parts.next()?.split(':').map(|p| p.parse::<u32>().ok());
+    let (hours, minutes, seconds) = (time.next()??, time.next()??, time.next()??);
+    if parts.next()? != "GMT" {
+        return None;
+    }
+    dates::from_civil(year, month, day, hours, minutes, seconds)
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+fn is_transient_error(error: &reqwest::Error) -> bool {
+    if error.is_connect() || error.is_timeout() {
+        return true;
+    }
+    let mut source = error.source();
+    while let Some(e) = source {
+        if let Some(io_error) = e.downcast_ref::<io::Error>() {
+            if matches!(
+                io_error.kind(),
+                io::ErrorKind::ConnectionReset | io::ErrorKind::ConnectionAborted
+            ) {
+                return true;
+            }
+        }
+        source = e.source();
+    }
+    false
+}
+
+fn describe(result: &reqwest::Result<Response>) -> String {
+    match result {
+        Ok(response) => format!("HTTP {}", response.status()),
+        Err(e) if e.is_timeout() => "timed out".to_string(),
+        Err(e) if e.is_connect() => "could not connect".to_string(),
+        Err(_) => "connection reset".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use super::*;
+
+    #[test]
+    fn test_retryable_status() {
+        assert!(is_retryable_status(StatusCode::BAD_GATEWAY));
+        assert!(is_retryable_status(StatusCode::GATEWAY_TIMEOUT));
+        assert!(!is_retryable_status(StatusCode::UNAUTHORIZED));
+        assert!(!is_retryable_status(StatusCode::FORBIDDEN));
+        assert!(!is_retryable_status(StatusCode::OK));
+    }
+
+    #[test]
+    fn test_delay() {
+        let policy = RetryPolicy {
+            attempts: 10,
+            backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_millis(3000),
+            jitter: false,
+            max_rate_limit_wait: Duration::ZERO,
+        };
+        assert_eq!(policy.delay(1), Duration::from_millis(500));
+        assert_eq!(policy.delay(2), Duration::from_millis(1000));
+        assert_eq!(policy.delay(3), Duration::from_millis(2000));
+        assert_eq!(policy.delay(4), Duration::from_millis(3000));
+        assert_eq!(policy.delay(40), Duration::from_millis(3000));
+
+        let jittered = RetryPolicy {
+            jitter: true,
+            ..policy
+        };
+        for attempt in 1..6 {
+            let delay = jittered.delay(attempt);
+            assert!(delay >= policy.delay(attempt) / 2 && delay <= policy.delay(attempt));
+        }
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let now = UNIX_EPOCH + Duration::from_secs(1445412470);
+        assert_eq!(
+            parse_retry_after("120", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT", now),
+            Some(Duration::from_secs(10))
+        );
+        // dates in the past mean "now"
+        assert_eq!(
+            parse_retry_after("Thu, 01 Jan 1970 00:00:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+        assert_eq!(
+            parse_retry_after("Wed, 21 Foo 2015 07:28:00 GMT", now),
+            None
+        );
+    }
+}
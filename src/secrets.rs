@@ -0,0 +1,28 @@
+//! Stored passwords, kept in the OS credential store rather than in `accounts.json`: the
+//! Credential Manager on Windows, the login keychain on macOS and the Secret Service
+//! elsewhere.
+
+use std::io;
+
+use keyring::Entry;
+
+/// What the entries are filed under in the credential store.
+const SERVICE: &str = "mmcai_rs";
+
+/// The password stored for `key`, `None` when there is none or the store is unavailable.
+pub fn get(key: &str) -> Option<String> {
+    Entry::new(SERVICE, key).ok()?.get_password().ok()
+}
+
+pub fn set(key: &str, secret: &str) -> io::Result<()> {
+    Entry::new(SERVICE, key)
+        .and_then(|entry| entry.set_password(secret))
+        .map_err(io::Error::other)
+}
+
+/// Removes the password stored for `key`; nothing happens when there is none.
+pub fn delete(key: &str) {
+    if let Ok(entry) = Entry::new(SERVICE, key) {
+        let _ = entry.delete_credential();
+    }
+}
@@ -0,0 +1,152 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use ring::digest::{digest, SHA256};
+use serde::{Deserialize, Serialize};
+
+use tracing::warn;
+
+use crate::i18n::tr;
+use crate::{dates, paths};
+
+/// The result of the last successful login of an account against a server, kept for
+/// launching while the auth server is down (see `[offline]` in the config).
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct CachedSession {
+    /// The API URL the session was obtained from, after API root discovery.
+    pub api_url: String,
+    pub access_token: String,
+    pub uuid: String,
+    pub name: String,
+    /// Expiry reported by the server, in seconds since the Unix epoch.
+    pub expires_at: Option<u64>,
+    pub obtained_at: u64,
+}
+
+impl CachedSession {
+    /// Whether the session can still be used. Sessions without a server-provided expiry
+    /// are considered valid for `max_age` seconds after they were obtained.
+    pub fn is_usable(&self, max_age: u64, now: u64) -> bool {
+        match self.expires_at {
+            Some(expires_at) => now < expires_at,
+            None => now < self.obtained_at.saturating_add(max_age),
+        }
+    }
+}
+
+/// Sessions are kept under the account's cache key (`Account::cache_key`).
+fn session_path(key: &str) -> Option<PathBuf> {
+    let hash: String = digest(&SHA256, key.as_bytes())
+        .as_ref()
+        .iter()
+        .take(16)
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    Some(
+        paths::data_dir()?
+            .join("sessions")
+            .join(format!("{}.json", hash)),
+    )
+}
+
+pub fn load(key: &str) -> Option<CachedSession> {
+    let path = session_path(key)?;
+    serde_json::from_slice(&fs::read(path).ok()?).ok()
+}
+
+/// Forgets the session, e.g. once its password was changed.
+pub fn remove(key: &str) {
+    if let Some(path) = session_path(key) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Saves the session; failures only cost the offline fallback, so they are just reported.
+pub fn store(key: &str, session: &CachedSession) {
+    let Some(path) = session_path(key) else {
+        return;
+    };
+    let written = serde_json::to_vec(session)
+        .map_err(io::Error::from)
+        .and_then(|contents| write_private(&path, &contents));
+    if let Err(e) = written {
+        warn!("{}", tr!("session-save-failed", error = e));
+    }
+}
+
+/// Writes the file readable by the current user only, since it contains an access token.
+pub fn write_private(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        paths::create_private_dir(parent)?;
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    paths::restrict(path, 0o600)?;
+    file.write_all(contents)
+}
+
+/// Converts the server's `expiredDate` into seconds since the Unix epoch.
+pub fn parse_expiry(expired_date: Option<&str>) -> Option<u64> {
+    let expires_at = dates::parse_iso8601(expired_date?)?;
+    Some(
+        expires_at
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()?
+            .as_secs(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_usable() {
+        let mut session = CachedSession {
+            api_url: "https://example.com/api".to_string(),
+            access_token: "token".to_string(),
+            uuid: "uuid".to_string(),
+            name: "Steve".to_string(),
+            expires_at: None,
+            obtained_at: 1000,
+        };
+        assert!(session.is_usable(3600, 2000));
+        assert!(!session.is_usable(3600, 5000));
+
+        // the server's expiry wins over the configured maximum age
+        session.expires_at = Some(10_000);
+        assert!(session.is_usable(3600, 9000));
+        assert!(!session.is_usable(3600, 10_000));
+    }
+
+    #[test]
+    fn test_parse_expiry() {
+        assert_eq!(parse_expiry(Some("2015-10-21T07:28:00Z")), Some(1445412480));
+        assert_eq!(parse_expiry(Some("never")), None);
+        assert_eq!(parse_expiry(None), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = assert_fs::TempDir::new().unwrap();
+        let path = dir.path().join("sessions.json");
+        fs::write(&path, "{}").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        write_private(&path, b"[]").unwrap();
+        let metadata = fs::metadata(&path).unwrap();
+        assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+        assert_eq!(fs::read(&path).unwrap(), b"[]");
+    }
+}
@@ -0,0 +1,149 @@
+use base64::prelude::*;
+use ring::signature::{UnparsedPublicKey, RSA_PKCS1_2048_8192_SHA1_FOR_LEGACY_USE_ONLY};
+use tracing::{debug, warn};
+
+use crate::http::HttpClient;
+use crate::i18n::tr;
+use crate::pinning::der_element;
+use crate::skin;
+
+/// How the `textures` property compares to the server's key.
+#[derive(Debug, PartialEq)]
+pub enum Verdict {
+    Valid,
+    /// Signed, but not by the key from the API metadata.
+    Mismatch,
+    Unsigned,
+}
+
+/// Checks the signature of the profile's `textures` property, as the game will, against
+/// the `signaturePublickey` of the metadata handed to authlib-injector, `prefetched` being
+/// the base64 value of `-Dauthlibinjector.yggdrasil.prefetched`. A mismatch means
+/// the session server and the metadata come from different servers, e.g. behind a
+/// misconfigured reverse proxy, and the game will show the default skin; it is only
+/// reported, the launch goes on.
+pub async fn check_textures(http: &HttpClient, api_url: &str, uuid: &str, prefetched: &str) {
+    let Some(public_key) = prefetched_public_key(prefetched) else {
+        debug!("the API metadata has no usable signaturePublickey, not checking textures");
+        return;
+    };
+    let profile = match skin::session_profile(http, api_url, uuid, true).await {
+        Ok(profile) => profile,
+        Err(e) => {
+            debug!("cannot fetch the profile to check its textures: {}", e);
+            return;
+        }
+    };
+    let Some(property) = profile.property("textures") else {
+        return;
+    };
+    match verify(&public_key, &property.value, property.signature.as_deref()) {
+        Verdict::Valid => debug!("the textures signature matches the server's key"),
+        Verdict::Mismatch => warn!("{}", tr!("texture-signature-mismatch", url = api_url)),
+        Verdict::Unsigned => warn!("{}", tr!("texture-signature-missing", url = api_url)),
+    }
+}
+
+pub fn verify(public_key: &[u8], value: &str, signature: Option<&str>) -> Verdict {
+    let Some(signature) = signature.and_then(|s| BASE64_STANDARD.decode(s).ok()) else {
+        return Verdict::Unsigned;
+    };
+    let key = UnparsedPublicKey::new(&RSA_PKCS1_2048_8192_SHA1_FOR_LEGACY_USE_ONLY, public_key);
+    match key.verify(value.as_bytes(), &signature) {
+        Ok(()) => Verdict::Valid,
+        Err(_) => Verdict::Mismatch,
+    }
+}
+
+/// The key in the base64 encoded metadata passed to authlib-injector as `prefetched`.
+pub fn prefetched_public_key(prefetched: &str) -> Option<Vec<u8>> {
+    let metadata = BASE64_STANDARD.decode(prefetched).ok()?;
+    public_key(std::str::from_utf8(&metadata).ok()?)
+}
+
+/// The PKCS#1 RSA key in the metadata's PEM `signaturePublickey`, which ring expects
+/// without the `SubjectPublicKeyInfo` around it.
+pub fn public_key(metadata: &str) -> Option<Vec<u8>> {
+    let metadata: serde_json::Value = serde_json::from_str(metadata).ok()?;
+    let pem = metadata.get("signaturePublickey")?.as_str()?;
+    let base64: String = pem
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .flat_map(|line| line.trim().chars())
+        .collect();
+    let spki = BASE64_STANDARD.decode(base64).ok()?;
+    let (_, spki, _) = der_element(&spki)?;
+    // the algorithm identifier, then the key as a BIT STRING
+    let (_, _, rest) = der_element(spki)?;
+    let (_, bit_string, _) = der_element(rest)?;
+    match bit_string.split_first()? {
+        // no unused bits
+        (0, key) => Some(key.to_vec()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_public_key() {
+        // SEQUENCE { SEQUENCE {}, BIT STRING { 0, 0x30 0x00 } }
+        let spki = BASE64_STANDARD.encode([0x30, 0x07, 0x30, 0x00, 0x03, 0x03, 0x00, 0x30, 0x00]);
+        let metadata = format!(
+            r#"{{"signaturePublickey": "-----BEGIN PUBLIC KEY-----\n{}\n-----END PUBLIC KEY-----\n"}}"#,
+            spki
+        );
+        assert_eq!(public_key(&metadata), Some(vec![0x30, 0x00]));
+        assert_eq!(public_key(r#"{"meta": {}}"#), None);
+        assert_eq!(public_key(r#"{"signaturePublickey": "not base64!"}"#), None);
+    }
+
+    const PUBLIC_KEY: &str = "-----BEGIN PUBLIC KEY-----
+MIIBIjANBgkqhkiG9w0BAQEFAAOCAQ8AMIIBCgKCAQEAnnPnxCxkLUwDMX2RviLv
+sv8374G1LEcCoVCx4b7jhi6v53xyivfekIv4pkoKpL2x3yN+4Y0SK7buzswVpU2W
+59YQiNXIrIcrrz87NVc5vFbWQF+TsUDx0k3uuOdH5NlWcT0Q/VshEF4LP9luy0cX
+ST6h9G4Sb/OVtvtImfyDrShdpb68FBz0ZRkObuyljQS4sGMW354Z1CeTWHIzgJuO
+eocUj3InIz3uC1LhhttFVmHLeYP4fb3ruMS/12df9gU3H2zIdWPgd+L/GIlq0GZ9
+d/E95UCzsEwXONqApRi5kb9Dh2clC6cCk/UGzeTZAT5M8hY7xOsi7jaKscsZ0fQg
+uwIDAQAB
+-----END PUBLIC KEY-----
+";
+
+    // {"timestamp":0,"profileId":"00000000000000000000000000000001","profileName":"Steve","textures":{}}
+    const TEXTURES: &str = "eyJ0aW1lc3RhbXAiOjAsInByb2ZpbGVJZCI6IjAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAxIiwicHJvZmlsZU5hbWUiOiJTdGV2ZSIsInRleHR1cmVzIjp7fX0=";
+
+    // `openssl dgst -sha1 -sign` of TEXTURES with the private half of PUBLIC_KEY
+    const SIGNATURE: &str = "i+K79/XbuAprc+0947v4Iohu6pZ2/OePWJIt/UwsyO18m82YvNZdmreYnFkchfGh3nPaQSYQDjlGoFkquiYMquDT5E8GbzvmlcPKSAhg1nFKRetcv08PHIQMF8T3fE/WcYkkd9Ei+FWEFpvXtl/p8QzbumQM4cuBnEyu7Y+4Md68pyw1VRIoyx5g+Y2LTEEA/gzqVYdH2C2RFfhGvE1JKZB1csWpZmcZxylFZn7Fq6vho+MALWywBNT9sUtAOvk1VBddbwbHIOGc2zrunWR5v86O2RyNzQKHQN8e8dRl9Wk4hAlseGgLsTWG71ir81cpV4iQUa+u+cLbQ0Bqds3aHA==";
+
+    #[test]
+    fn test_prefetched_textures() {
+        let metadata = serde_json::json!({
+            "meta": {"serverName": "Marallys"},
+            "skinDomains": [],
+            "signaturePublickey": PUBLIC_KEY,
+        });
+        // encoded the way the login stores it for authlib-injector
+        let prefetched = BASE64_STANDARD.encode(metadata.to_string());
+        let key = prefetched_public_key(&prefetched).unwrap();
+        assert_eq!(verify(&key, TEXTURES, Some(SIGNATURE)), Verdict::Valid);
+
+        let tampered = BASE64_STANDARD.encode(
+            r#"{"timestamp":0,"profileId":"00000000000000000000000000000001","profileName":"Alex","textures":{}}"#,
+        );
+        assert_eq!(verify(&key, &tampered, Some(SIGNATURE)), Verdict::Mismatch);
+        assert_eq!(verify(&key, TEXTURES, None), Verdict::Unsigned);
+
+        assert_eq!(prefetched_public_key(&metadata.to_string()), None);
+    }
+
+    #[test]
+    fn test_verify() {
+        assert_eq!(verify(&[0x30, 0x00], "e30=", None), Verdict::Unsigned);
+        assert_eq!(
+            verify(&[0x30, 0x00], "e30=", Some("AAAA")),
+            Verdict::Mismatch
+        );
+    }
+}
@@ -0,0 +1,1009 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process,
+    time::SystemTime,
+};
+
+use base64::prelude::*;
+use reqwest::{multipart, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::config::UuidFormat;
+use crate::errors::MmcaiError;
+use crate::http::HttpClient;
+use crate::i18n::tr;
+use crate::{
+    dates, logging, picker, png, preview, response, skin_library, skin_sync, texture_cache, Result,
+};
+
+const USAGE: &str = "skin upload [<account>] <file.png> [--slim] | \
+                     skin download [<account>] [-o <file.png>] | \
+                     skin preview [<account>] [-o <file.png>] | \
+                     skin render [<account>] [-o <file.png>] [--open] | \
+                     skin sync [<account>] --from mojang:<username>|<username>@<api url> | \
+                     skin save [<account>] <name> | skin apply [<account>] <name> | skin list | \
+                     skin reset [<account>] [--cape] [--yes] | \
+                     skin cape [upload [<account>] <file.png> | remove [<account>] | <account>]";
+pub const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// The kinds of texture the Yggdrasil texture API manages.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextureType {
+    Skin,
+    Cape,
+}
+
+impl TextureType {
+    /// The name in the texture API's URLs.
+    fn name(self) -> &'static str {
+        match self {
+            TextureType::Skin => "skin",
+            TextureType::Cape => "cape",
+        }
+    }
+}
+
+/// The skin and cloak the server reported at login.
+#[derive(Debug, Default, Clone)]
+pub struct Textures {
+    pub skin_url: Option<String>,
+    pub skin_guid: Option<String>,
+    pub cloak_url: Option<String>,
+    pub cloak_guid: Option<String>,
+    pub full_skin_url: Option<String>,
+}
+
+/// A texture's URL and ID for display, `none` without either.
+pub fn describe(url: Option<&str>, guid: Option<&str>) -> String {
+    match (url, guid) {
+        (Some(url), Some(guid)) => format!("{} ({})", url, guid),
+        (Some(value), None) | (None, Some(value)) => value.to_string(),
+        (None, None) => tr!("whoami-none"),
+    }
+}
+
+/// The player model a skin is drawn on.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Model {
+    /// Steve's, with 4 pixel wide arms.
+    #[default]
+    Classic,
+    /// Alex's, with 3 pixel wide arms.
+    Slim,
+}
+
+impl Model {
+    fn name(self) -> &'static str {
+        match self {
+            Model::Classic => "classic",
+            Model::Slim => "slim",
+        }
+    }
+}
+
+/// A `skin` subcommand. `account` is the stored account to use, the default one when
+/// not given.
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Upload {
+        account: Option<String>,
+        texture: TextureType,
+        file: PathBuf,
+        model: Model,
+    },
+    /// Saves the skin and cape, with their IDs, for a backup. `output` defaults to
+    /// `<profile name>.png`.
+    Download {
+        account: Option<String>,
+        output: Option<PathBuf>,
+    },
+    /// Shows the skin from the front in the terminal, or saves that view as a PNG.
+    Preview {
+        account: Option<String>,
+        output: Option<PathBuf>,
+    },
+    /// The server's pre-rendered image of the skin (`fullSkinUrl`): printed, saved to
+    /// `output` or opened in the browser.
+    Render {
+        account: Option<String>,
+        output: Option<PathBuf>,
+        open: bool,
+    },
+    /// Copies another player's skin, e.g. the Mojang account's, with its model.
+    Sync {
+        account: Option<String>,
+        source: skin_sync::Source,
+    },
+    /// Keeps the current skin in the local library under `name`, failing if the
+    /// session server can't say which model it is on.
+    Save {
+        account: Option<String>,
+        name: String,
+    },
+    /// Uploads the skin saved under `name`, on the model it was saved with.
+    Apply {
+        account: Option<String>,
+        name: String,
+    },
+    /// Lists the library, without logging in.
+    List,
+    /// Shows the cape the account wears.
+    ShowCape { account: Option<String> },
+    /// Back to the default skin, or no cape. Asks first unless `confirmed` (`--yes`).
+    Remove {
+        account: Option<String>,
+        texture: TextureType,
+        confirmed: bool,
+    },
+}
+
+impl Command {
+    /// Parses `skin ...`, the arguments after the program name.
+    pub fn parse(args: &[String]) -> Result<Command> {
+        let usage = || MmcaiError::CommandUsage(format!("{} --cmd {}", args[0], USAGE));
+        let mut output = None;
+        let mut from = None;
+        let mut rest = Vec::new();
+        let mut iter = args[2..].iter().map(String::as_str);
+        while let Some(arg) = iter.next() {
+            match arg {
+                "-o" | "--output" => output = Some(PathBuf::from(iter.next().ok_or_else(usage)?)),
+                "--from" => from = Some(iter.next().ok_or_else(usage)?),
+                arg => rest.push(arg),
+            }
+        }
+        let (flags, positional): (Vec<&str>, Vec<&str>) =
+            rest.into_iter().partition(|arg| arg.starts_with("--"));
+        let account = |rest: &[&str]| rest.first().map(|account| account.to_string());
+        match positional[..] {
+            ["download", ref rest @ ..] if rest.len() <= 1 && flags.is_empty() => {
+                Ok(Command::Download {
+                    account: account(rest),
+                    output,
+                })
+            }
+            ["preview", ref rest @ ..] if rest.len() <= 1 && flags.is_empty() => {
+                Ok(Command::Preview {
+                    account: account(rest),
+                    output,
+                })
+            }
+            ["render", ref rest @ ..] if rest.len() <= 1 => {
+                let open = match flags[..] {
+                    [] => false,
+                    ["--open"] => true,
+                    _ => return Err(usage()),
+                };
+                Ok(Command::Render {
+                    account: account(rest),
+                    output,
+                    open,
+                })
+            }
+            _ if output.is_some() => Err(usage()),
+            ["sync", ref rest @ ..] if rest.len() <= 1 && flags.is_empty() => {
+                let source = from.and_then(skin_sync::Source::parse).ok_or_else(usage)?;
+                Ok(Command::Sync {
+                    account: account(rest),
+                    source,
+                })
+            }
+            _ if from.is_some() => Err(usage()),
+            ["upload", ref rest @ .., file] if rest.len() <= 1 => {
+                let model = match flags[..] {
+                    [] => Model::Classic,
+                    ["--slim"] => Model::Slim,
+                    _ => return Err(usage()),
+                };
+                Ok(Command::Upload {
+                    account: account(rest),
+                    texture: TextureType::Skin,
+                    file: PathBuf::from(file),
+                    model,
+                })
+            }
+            ["reset", ref rest @ ..] if rest.len() <= 1 => {
+                let (mut texture, mut confirmed) = (TextureType::Skin, false);
+                for flag in &flags {
+                    match *flag {
+                        "--cape" => texture = TextureType::Cape,
+                        "--yes" => confirmed = true,
+                        _ => return Err(usage()),
+                    }
+                }
+                Ok(Command::Remove {
+                    account: account(rest),
+                    texture,
+                    confirmed,
+                })
+            }
+            ["cape", "remove", ref rest @ ..] if rest.len() <= 1 => {
+                let confirmed = match flags[..] {
+                    [] => false,
+                    ["--yes"] => true,
+                    _ => return Err(usage()),
+                };
+                Ok(Command::Remove {
+                    account: account(rest),
+                    texture: TextureType::Cape,
+                    confirmed,
+                })
+            }
+            _ if !flags.is_empty() => Err(usage()),
+            ["save", ref rest @ .., name] if rest.len() <= 1 => Ok(Command::Save {
+                account: account(rest),
+                name: name.to_string(),
+            }),
+            ["apply", ref rest @ .., name] if rest.len() <= 1 => Ok(Command::Apply {
+                account: account(rest),
+                name: name.to_string(),
+            }),
+            ["list"] => Ok(Command::List),
+            ["cape", "upload", ref rest @ .., file] if rest.len() <= 1 => Ok(Command::Upload {
+                account: account(rest),
+                texture: TextureType::Cape,
+                file: PathBuf::from(file),
+                model: Model::Classic,
+            }),
+            ["cape", ref rest @ ..] if rest.len() <= 1 => Ok(Command::ShowCape {
+                account: account(rest),
+            }),
+            _ => Err(usage()),
+        }
+    }
+
+    pub fn account(&self) -> Option<&str> {
+        match self {
+            Command::Upload { account, .. }
+            | Command::Download { account, .. }
+            | Command::Preview { account, .. }
+            | Command::Render { account, .. }
+            | Command::Sync { account, .. }
+            | Command::Save { account, .. }
+            | Command::Apply { account, .. }
+            | Command::ShowCape { account }
+            | Command::Remove { account, .. } => account.as_deref(),
+            Command::List => None,
+        }
+    }
+}
+
+/// Who is logged in where, for the texture API.
+pub struct Target<'a> {
+    /// The API root authlib-injector is given.
+    pub api_url: &'a str,
+    pub access_token: &'a str,
+    pub uuid: &'a str,
+    /// The profile's name, for the messages.
+    pub name: &'a str,
+    /// What the login reported the profile wears.
+    pub textures: &'a Textures,
+}
+
+impl Target<'_> {
+    /// The UUID undashed, as the APIs expect it in URLs.
+    fn undashed_uuid(&self) -> String {
+        UuidFormat::Undashed
+            .apply(self.uuid)
+            .unwrap_or_else(|| self.uuid.to_string())
+    }
+
+    /// `/api/user/profile/{uuid}/{textureType}`.
+    fn texture_url(&self, texture: TextureType) -> String {
+        format!(
+            "{}/api/user/profile/{}/{}",
+            self.api_url.trim_end_matches('/'),
+            self.undashed_uuid(),
+            texture.name()
+        )
+    }
+}
+
+pub async fn run(command: &Command, http: &HttpClient, target: &Target<'_>) -> Result<()> {
+    match command {
+        Command::Upload {
+            texture,
+            file,
+            model,
+            ..
+        } => {
+            let png = fs::read(file).map_err(|source| MmcaiError::ReadTextureFailed {
+                path: file.clone(),
+                source,
+            })?;
+            let Some((width, height)) = png::dimensions(&png) else {
+                return Err(MmcaiError::InvalidTexture(file.clone()));
+            };
+            if *texture == TextureType::Skin && !is_skin_size(width, height) {
+                return Err(MmcaiError::UnreadableSkin(tr!(
+                    "skin-wrong-size",
+                    width = width,
+                    height = height
+                )));
+            }
+            upload(http, target, *texture, *model, png).await?;
+            let message = match texture {
+                TextureType::Skin => tr!("skin-uploaded", name = target.name),
+                TextureType::Cape => tr!("cape-uploaded", name = target.name),
+            };
+            info!(target: logging::SUCCESS_TARGET, "{}", message);
+        }
+        Command::Download { output, .. } => {
+            let output = output
+                .clone()
+                .unwrap_or_else(|| PathBuf::from(format!("{}.png", default_file_stem(target))));
+            download(http, target, &output).await?;
+            let path = format!("{:?}", output);
+            info!(
+                target: logging::SUCCESS_TARGET,
+                "{}",
+                tr!("skin-downloaded", name = target.name, path = path)
+            );
+        }
+        Command::Preview { output, .. } => {
+            let view = preview(http, target).await?;
+            match output {
+                Some(output) => {
+                    write_texture(output, &png::encode(&preview::enlarge(&view, 8)))?;
+                    let path = format!("{:?}", output);
+                    info!(
+                        target: logging::SUCCESS_TARGET,
+                        "{}",
+                        tr!("skin-preview-saved", name = target.name, path = path)
+                    );
+                }
+                None => print!("{}", preview::render_terminal(&view)),
+            }
+        }
+        Command::Render { output, open, .. } => {
+            let Some(url) = target.textures.full_skin_url.as_deref() else {
+                return Err(MmcaiError::NoSkin(target.name.to_string()));
+            };
+            if let Some(output) = output {
+                // the URL usually stays the same when the skin changes, so it isn't cached
+                write_texture(output, &texture_cache::download(http, url).await?)?;
+                let path = format!("{:?}", output);
+                info!(
+                    target: logging::SUCCESS_TARGET,
+                    "{}",
+                    tr!("skin-render-saved", name = target.name, path = path)
+                );
+            }
+            if *open {
+                if let Err(e) = open_in_browser(url) {
+                    warn!("{}", tr!("browser-open-failed", url = url, error = e));
+                }
+            }
+            if output.is_none() && !open {
+                println!("{}", url);
+            }
+        }
+        Command::Sync { source, .. } => {
+            let (png, model) = skin_sync::fetch(http, source).await?;
+            upload(http, target, TextureType::Skin, model, png).await?;
+            info!(
+                target: logging::SUCCESS_TARGET,
+                "{}",
+                tr!(
+                    "skin-synced",
+                    name = target.name,
+                    source = source.username.as_str(),
+                    model = model.name()
+                )
+            );
+        }
+        Command::Save { name, .. } => {
+            let textures = target.textures;
+            let Some(skin_url) = textures.skin_url.as_deref() else {
+                return Err(MmcaiError::NoSkin(target.name.to_string()));
+            };
+            let png = texture_cache::fetch(http, skin_url, textures.skin_guid.as_deref()).await?;
+            // The entry decides the model `apply` uploads on, so guessing one is worse
+            // than failing.
+            let model = profile_textures(http, target).await?.model;
+            let entry = skin_library::Entry {
+                model,
+                saved_from: Some(target.name.to_string()),
+                saved_at: Some(dates::format_iso8601(SystemTime::now())),
+            };
+            skin_library::save(name, &png, &entry)?;
+            info!(
+                target: logging::SUCCESS_TARGET,
+                "{}",
+                tr!("skin-saved", name = target.name, skin = name.as_str())
+            );
+        }
+        Command::Apply { name, .. } => {
+            let (png, entry) = skin_library::load(name)?;
+            upload(http, target, TextureType::Skin, entry.model, png).await?;
+            info!(
+                target: logging::SUCCESS_TARGET,
+                "{}",
+                tr!("skin-applied", name = target.name, skin = name.as_str())
+            );
+        }
+        Command::List => print!("{}", list()),
+        Command::ShowCape { .. } => {
+            let textures = target.textures;
+            match (&textures.cloak_url, &textures.cloak_guid) {
+                (None, None) => println!("{}", tr!("cape-none", name = target.name)),
+                (url, guid) => println!(
+                    "{}",
+                    tr!(
+                        "whoami-cloak",
+                        texture = describe(url.as_deref(), guid.as_deref())
+                    )
+                ),
+            }
+        }
+        Command::Remove {
+            texture, confirmed, ..
+        } => {
+            let (question, done) = match texture {
+                TextureType::Skin => ("skin-reset-confirm", "skin-reset"),
+                TextureType::Cape => ("cape-remove-confirm", "cape-removed"),
+            };
+            if !confirmed && !confirm(tr!(question, name = target.name)).await {
+                info!("{}", tr!("skin-reset-cancelled"));
+                return Ok(());
+            }
+            remove(http, target, *texture).await?;
+            info!(target: logging::SUCCESS_TARGET, "{}", tr!(done, name = target.name));
+        }
+    }
+    Ok(())
+}
+
+/// Uploads a PNG as the player's skin or cape, as `multipart/form-data` with the
+/// `model` and `file` fields.
+pub async fn upload(
+    http: &HttpClient,
+    target: &Target<'_>,
+    texture: TextureType,
+    model: Model,
+    png: Vec<u8>,
+) -> Result<()> {
+    let url = target.texture_url(texture);
+    let response = http
+        .send(|client| {
+            client
+                .put(&url)
+                .bearer_auth(target.access_token)
+                .multipart(upload_form(model, png.clone()))
+        })
+        .await
+        .map_err(MmcaiError::TextureRequestFailed)?;
+    check(response).await
+}
+
+/// What `skin download` writes next to the PNGs.
+#[derive(Serialize, Debug, PartialEq)]
+struct Backup<'a> {
+    name: &'a str,
+    uuid: &'a str,
+    skin: Option<BackupTexture<'a>>,
+    cape: Option<BackupTexture<'a>>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+struct BackupTexture<'a> {
+    /// The PNG's file name, next to the JSON.
+    file: String,
+    url: &'a str,
+    guid: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    model: Option<&'static str>,
+}
+
+/// Saves the skin to `output`, the cape to `<output>-cape.png` and their URLs, IDs and
+/// the skin's model to `<output>.json`.
+async fn download(http: &HttpClient, target: &Target<'_>, output: &Path) -> Result<()> {
+    let textures = target.textures;
+    let Some(skin_url) = textures.skin_url.as_deref() else {
+        return Err(MmcaiError::NoSkin(target.name.to_string()));
+    };
+    let model = current_model(http, target).await;
+    let stem = output
+        .file_stem()
+        .map_or_else(|| target.name.into(), |stem| stem.to_string_lossy());
+    let cape_output = output.with_file_name(format!("{}-cape.png", stem));
+
+    let skin = texture_cache::fetch(http, skin_url, textures.skin_guid.as_deref()).await?;
+    write_texture(output, &skin)?;
+    let cape = match textures.cloak_url.as_deref() {
+        Some(cape_url) => {
+            let cape = texture_cache::fetch(http, cape_url, textures.cloak_guid.as_deref()).await?;
+            write_texture(&cape_output, &cape)?;
+            Some(BackupTexture {
+                file: file_name(&cape_output),
+                url: cape_url,
+                guid: textures.cloak_guid.as_deref(),
+                model: None,
+            })
+        }
+        None => None,
+    };
+    let backup = Backup {
+        name: target.name,
+        uuid: target.uuid,
+        skin: Some(BackupTexture {
+            file: file_name(output),
+            url: skin_url,
+            guid: textures.skin_guid.as_deref(),
+            model: model.map(Model::name),
+        }),
+        cape,
+    };
+    let json = serde_json::to_vec_pretty(&backup).expect("the backup serializes");
+    write_texture(&output.with_extension("json"), &json)
+}
+
+/// Asks before changing what other players see. Without a terminal or dialog to ask
+/// in, the answer is no.
+async fn confirm(question: String) -> bool {
+    let items = [tr!("confirm-no"), tr!("confirm-yes")];
+    let answer = tokio::task::spawn_blocking(move || picker::pick(&question, &items, 0)).await;
+    answer.ok().flatten() == Some(1)
+}
+
+/// Hands the URL to the desktop's default browser.
+fn open_in_browser(url: &str) -> std::io::Result<()> {
+    let mut command = if cfg!(windows) {
+        let mut command = process::Command::new("cmd");
+        // `start` takes the first quoted argument as the window title
+        command.args(["/C", "start", ""]);
+        command
+    } else if cfg!(target_os = "macos") {
+        process::Command::new("open")
+    } else {
+        process::Command::new("xdg-open")
+    };
+    command
+        .arg(url)
+        .stdin(process::Stdio::null())
+        .stdout(process::Stdio::null())
+        .stderr(process::Stdio::null())
+        .spawn()
+        .map(drop)
+}
+
+/// The saved skins, one per line with their model.
+pub fn list() -> String {
+    let skins = skin_library::names();
+    if skins.is_empty() {
+        return tr!("skin-library-empty") + "\n";
+    }
+    skins
+        .iter()
+        .map(|(name, entry)| format!("{} ({})\n", name, entry.model.name()))
+        .collect()
+}
+
+/// The front view of the current skin, on the model the session server reports.
+async fn preview(http: &HttpClient, target: &Target<'_>) -> Result<png::Image> {
+    let textures = target.textures;
+    let Some(skin_url) = textures.skin_url.as_deref() else {
+        return Err(MmcaiError::NoSkin(target.name.to_string()));
+    };
+    let skin = texture_cache::fetch(http, skin_url, textures.skin_guid.as_deref()).await?;
+    let skin = png::decode(&skin).map_err(MmcaiError::UnreadableSkin)?;
+    let model = current_model(http, target).await.unwrap_or_default();
+    Ok(preview::front_view(&skin, model))
+}
+
+/// The profile name when it is a valid player name, which can't leave the current
+/// directory, and the UUID otherwise.
+fn default_file_stem(target: &Target) -> String {
+    let valid = !target.name.is_empty()
+        && target
+            .name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if valid {
+        target.name.to_string()
+    } else {
+        target.undashed_uuid()
+    }
+}
+
+/// 64×64 and legacy 64×32, or HD skins of a multiple of those.
+fn is_skin_size(width: u32, height: u32) -> bool {
+    width >= 64 && width.is_multiple_of(64) && (height == width || height * 2 == width)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn write_texture(path: &Path, contents: &[u8]) -> Result<()> {
+    fs::write(path, contents).map_err(|source| MmcaiError::WriteTextureFailed {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// The profile as the session server hands it to the game.
+#[derive(Deserialize)]
+pub struct SessionProfile {
+    #[serde(default)]
+    pub properties: Vec<Property>,
+}
+
+impl SessionProfile {
+    pub fn property(&self, name: &str) -> Option<&Property> {
+        self.properties
+            .iter()
+            .find(|property| property.name == name)
+    }
+}
+
+#[derive(Deserialize)]
+pub struct Property {
+    pub name: String,
+    /// Base64 of the property's JSON.
+    pub value: String,
+    /// Base64 of the server's SHA1withRSA signature of `value`, only sent when asked for.
+    pub signature: Option<String>,
+}
+
+/// The decoded `textures` property. Only the skin's model is read from it, the URLs
+/// come with the login.
+#[derive(Deserialize, Default)]
+struct TexturesProperty {
+    #[serde(default)]
+    textures: TexturesPayload,
+}
+
+#[derive(Deserialize, Default)]
+struct TexturesPayload {
+    #[serde(rename = "SKIN")]
+    skin: Option<TextureEntry>,
+}
+
+#[derive(Deserialize)]
+struct TextureEntry {
+    url: Option<String>,
+    #[serde(default)]
+    metadata: TextureMetadata,
+}
+
+#[derive(Deserialize, Default)]
+struct TextureMetadata {
+    model: Option<String>,
+}
+
+/// What the session server reports about the profile's textures.
+struct ProfileTextures {
+    model: Model,
+}
+
+/// `/sessionserver/session/minecraft/profile/{uuid}`, with the properties' signatures
+/// when `signed`.
+pub async fn session_profile(
+    http: &HttpClient,
+    api_url: &str,
+    uuid: &str,
+    signed: bool,
+) -> Result<SessionProfile> {
+    let profiles_url = format!(
+        "{}/sessionserver/session/minecraft/profile",
+        api_url.trim_end_matches('/')
+    );
+    fetch_session_profile(http, &profiles_url, uuid, signed).await
+}
+
+/// `{profiles_url}/{uuid}`, for session servers that aren't under an API root, like
+/// Mojang's.
+pub async fn fetch_session_profile(
+    http: &HttpClient,
+    profiles_url: &str,
+    uuid: &str,
+    signed: bool,
+) -> Result<SessionProfile> {
+    let uuid = UuidFormat::Undashed
+        .apply(uuid)
+        .unwrap_or_else(|| uuid.to_string());
+    let url = format!(
+        "{}/{}{}",
+        profiles_url,
+        uuid,
+        if signed { "?unsigned=false" } else { "" }
+    );
+    http.send(|client| client.get(&url))
+        .await
+        .and_then(Response::error_for_status)
+        .map_err(MmcaiError::TextureRequestFailed)?
+        .json()
+        .await
+        .map_err(MmcaiError::TextureRequestFailed)
+}
+
+/// The current skin's model; `None` when the session server can't tell.
+async fn current_model(http: &HttpClient, target: &Target<'_>) -> Option<Model> {
+    match profile_textures(http, target).await {
+        Ok(textures) => Some(textures.model),
+        Err(e) => {
+            debug!("cannot read the skin model from the profile: {}", e);
+            None
+        }
+    }
+}
+
+/// Reads the profile's `textures` property from the session server.
+async fn profile_textures(http: &HttpClient, target: &Target<'_>) -> Result<ProfileTextures> {
+    let profile = session_profile(http, target.api_url, target.uuid, false).await?;
+    let property = profile
+        .property("textures")
+        .map(|property| decode_textures(&property.value))
+        .unwrap_or_default();
+    Ok(ProfileTextures {
+        model: skin_model(&property),
+    })
+}
+
+/// The skin's URL and model from the profile's `textures` property.
+pub fn skin_of(profile: &SessionProfile) -> Option<(String, Model)> {
+    let property = decode_textures(&profile.property("textures")?.value);
+    let url = property.textures.skin.as_ref()?.url.clone()?;
+    Some((url, skin_model(&property)))
+}
+
+fn decode_textures(value: &str) -> TexturesProperty {
+    BASE64_STANDARD
+        .decode(value)
+        .ok()
+        .and_then(|json| serde_json::from_slice(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Skins without the `model` metadata are classic ones.
+fn skin_model(property: &TexturesProperty) -> Model {
+    let model = property
+        .textures
+        .skin
+        .as_ref()
+        .and_then(|skin| skin.metadata.model.as_deref());
+    match model {
+        Some("slim") => Model::Slim,
+        _ => Model::Classic,
+    }
+}
+
+/// Takes the texture off the player, who falls back to the default skin or no cape.
+pub async fn remove(http: &HttpClient, target: &Target<'_>, texture: TextureType) -> Result<()> {
+    let url = target.texture_url(texture);
+    let response = http
+        .send(|client| client.delete(&url).bearer_auth(target.access_token))
+        .await
+        .map_err(MmcaiError::TextureRequestFailed)?;
+    check(response).await
+}
+
+fn upload_form(model: Model, png: Vec<u8>) -> multipart::Form {
+    let model = match model {
+        Model::Classic => "",
+        Model::Slim => "slim",
+    };
+    let file = multipart::Part::bytes(png)
+        .file_name("texture.png")
+        .mime_str("image/png")
+        .expect("image/png is a valid MIME type");
+    multipart::Form::new()
+        .text("model", model)
+        .part("file", file)
+}
+
+/// Yggdrasil's error answer.
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct ErrorResponse {
+    error: String,
+    error_message: String,
+}
+
+/// The texture API answers 204 when it made the change.
+async fn check(response: Response) -> Result<()> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    let body = response.text().await.unwrap_or_default();
+    let message = match serde_json::from_str::<ErrorResponse>(&body) {
+        Ok(error) if !error.error_message.is_empty() => error.error_message,
+        Ok(error) if !error.error.is_empty() => error.error,
+        _ => response::body_excerpt(&body),
+    };
+    if status == StatusCode::FORBIDDEN {
+        return Err(MmcaiError::TextureForbidden(message));
+    }
+    Err(MmcaiError::TextureRejected {
+        status_code: status.as_u16(),
+        message,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        ["mmcai_rs", "skin"]
+            .iter()
+            .chain(args)
+            .map(|arg| arg.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Command::parse(&args(&["upload", "alt", "steve.png", "--slim"])).unwrap(),
+            Command::Upload {
+                account: Some("alt".into()),
+                texture: TextureType::Skin,
+                file: "steve.png".into(),
+                model: Model::Slim,
+            }
+        );
+        let command = Command::parse(&args(&["upload", "steve.png"])).unwrap();
+        assert_eq!(command.account(), None);
+        assert!(Command::parse(&args(&["upload"])).is_err());
+        assert!(Command::parse(&args(&["upload", "steve.png", "--wide"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_download() {
+        assert_eq!(
+            Command::parse(&args(&["download", "alt", "-o", "backup/steve.png"])).unwrap(),
+            Command::Download {
+                account: Some("alt".into()),
+                output: Some("backup/steve.png".into()),
+            }
+        );
+        assert_eq!(
+            Command::parse(&args(&["download"])).unwrap(),
+            Command::Download {
+                account: None,
+                output: None,
+            }
+        );
+        assert!(Command::parse(&args(&["download", "-o"])).is_err());
+        assert_eq!(
+            Command::parse(&args(&["preview", "-o", "front.png"])).unwrap(),
+            Command::Preview {
+                account: None,
+                output: Some("front.png".into()),
+            }
+        );
+        assert!(Command::parse(&args(&["upload", "steve.png", "-o", "x.png"])).is_err());
+    }
+
+    #[test]
+    fn test_skin_model() {
+        let encode = |json: &str| BASE64_STANDARD.encode(json);
+        let slim = encode(
+            r#"{"profileName":"Alex","textures":{"SKIN":{"url":"https://t/a","metadata":{"model":"slim"}}}}"#,
+        );
+        assert_eq!(skin_model(&decode_textures(&slim)), Model::Slim);
+        let classic = encode(r#"{"textures":{"SKIN":{"url":"https://t/s"}}}"#);
+        assert_eq!(skin_model(&decode_textures(&classic)), Model::Classic);
+        assert_eq!(skin_model(&decode_textures("not base64")), Model::Classic);
+    }
+
+    #[test]
+    fn test_parse_library() {
+        assert_eq!(
+            Command::parse(&args(&["save", "halloween"])).unwrap(),
+            Command::Save {
+                account: None,
+                name: "halloween".into(),
+            }
+        );
+        assert_eq!(
+            Command::parse(&args(&["apply", "alt", "halloween"])).unwrap(),
+            Command::Apply {
+                account: Some("alt".into()),
+                name: "halloween".into(),
+            }
+        );
+        assert_eq!(Command::parse(&args(&["list"])).unwrap(), Command::List);
+        let command = Command::parse(&args(&["sync", "--from", "mojang:Notch"])).unwrap();
+        assert!(matches!(command, Command::Sync { account: None, .. }));
+        assert!(Command::parse(&args(&["sync"])).is_err());
+        assert!(Command::parse(&args(&["sync", "--from", "Notch"])).is_err());
+        assert!(Command::parse(&args(&["list", "--from", "mojang:Notch"])).is_err());
+        assert_eq!(
+            Command::parse(&args(&["render", "--open"])).unwrap(),
+            Command::Render {
+                account: None,
+                output: None,
+                open: true,
+            }
+        );
+        assert!(Command::parse(&args(&["list", "alt"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_cape() {
+        assert_eq!(
+            Command::parse(&args(&["cape", "upload", "cape.png"])).unwrap(),
+            Command::Upload {
+                account: None,
+                texture: TextureType::Cape,
+                file: "cape.png".into(),
+                model: Model::Classic,
+            }
+        );
+        assert_eq!(
+            Command::parse(&args(&["cape", "remove", "alt"])).unwrap(),
+            Command::Remove {
+                account: Some("alt".into()),
+                texture: TextureType::Cape,
+                confirmed: false,
+            }
+        );
+        assert_eq!(
+            Command::parse(&args(&["reset", "--cape", "--yes"])).unwrap(),
+            Command::Remove {
+                account: None,
+                texture: TextureType::Cape,
+                confirmed: true,
+            }
+        );
+        assert!(matches!(
+            Command::parse(&args(&["reset"])).unwrap(),
+            Command::Remove {
+                texture: TextureType::Skin,
+                confirmed: false,
+                ..
+            }
+        ));
+        assert!(Command::parse(&args(&["reset", "--slim"])).is_err());
+        assert_eq!(
+            Command::parse(&args(&["cape"])).unwrap(),
+            Command::ShowCape { account: None }
+        );
+        assert!(Command::parse(&args(&["cape", "upload", "cape.png", "--slim"])).is_err());
+        assert!(Command::parse(&args(&["cape", "remove", "alt", "extra"])).is_err());
+    }
+
+    #[test]
+    fn test_is_skin_size() {
+        assert!(is_skin_size(64, 64));
+        assert!(is_skin_size(64, 32));
+        assert!(is_skin_size(128, 128));
+        assert!(!is_skin_size(100, 100));
+        assert!(!is_skin_size(64, 48));
+        assert!(!is_skin_size(32, 32));
+    }
+
+    #[test]
+    fn test_texture_url() {
+        let textures = Textures::default();
+        let target = Target {
+            api_url: "https://auth.example.com/api/",
+            access_token: "t",
+            uuid: "0f8d2c3e-5a4b-4c1d-9e8f-7a6b5c4d3e2f",
+            name: "Steve",
+            textures: &textures,
+        };
+        assert_eq!(
+            target.texture_url(TextureType::Skin),
+            "https://auth.example.com/api/api/user/profile/0f8d2c3e5a4b4c1d9e8f7a6b5c4d3e2f/skin"
+        );
+        assert!(target.texture_url(TextureType::Cape).ends_with("/cape"));
+        assert_eq!(default_file_stem(&target), "Steve");
+        for name in ["../../.bashrc", "C:\\Windows\\x", ""] {
+            let target = Target { name, ..target };
+            assert_eq!(
+                default_file_stem(&target),
+                "0f8d2c3e5a4b4c1d9e8f7a6b5c4d3e2f"
+            );
+        }
+    }
+}
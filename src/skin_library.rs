@@ -0,0 +1,149 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::MmcaiError;
+use crate::skin::Model;
+use crate::{paths, Result};
+
+const LIBRARY_DIR_NAME: &str = "skins";
+
+/// What is kept next to a saved skin's PNG.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Entry {
+    pub model: Model,
+    /// The profile it was saved from, when it was the current skin of one.
+    #[serde(default)]
+    pub saved_from: Option<String>,
+    #[serde(default)]
+    pub saved_at: Option<String>,
+}
+
+/// `skins` in the per-user data directory, a `<name>.png` and `<name>.json` per skin.
+fn dir() -> Result<PathBuf> {
+    paths::data_dir()
+        .map(|dir| dir.join(LIBRARY_DIR_NAME))
+        .ok_or_else(|| MmcaiError::WriteTextureFailed {
+            path: LIBRARY_DIR_NAME.into(),
+            source: io::ErrorKind::NotFound.into(),
+        })
+}
+
+/// Names become file names, so only plain ones are accepted.
+fn check_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    match valid {
+        true => Ok(()),
+        false => Err(MmcaiError::InvalidSkinName(name.to_string())),
+    }
+}
+
+pub fn save(name: &str, png: &[u8], entry: &Entry) -> Result<()> {
+    check_name(name)?;
+    save_in(&dir()?, name, png, entry)
+}
+
+fn save_in(dir: &Path, name: &str, png: &[u8], entry: &Entry) -> Result<()> {
+    let write = |path: PathBuf, contents: &[u8]| {
+        paths::create_private_dir(dir)
+            .and_then(|_| fs::write(&path, contents))
+            .map_err(|source| MmcaiError::WriteTextureFailed { path, source })
+    };
+    let json = serde_json::to_vec_pretty(entry).expect("the entry serializes");
+    write(dir.join(format!("{}.png", name)), png)?;
+    write(dir.join(format!("{}.json", name)), &json)
+}
+
+/// The saved skin's PNG and what was kept with it.
+pub fn load(name: &str) -> Result<(Vec<u8>, Entry)> {
+    check_name(name)?;
+    load_in(&dir()?, name)
+}
+
+fn load_in(dir: &Path, name: &str) -> Result<(Vec<u8>, Entry)> {
+    let path = dir.join(format!("{}.png", name));
+    let png = fs::read(&path).map_err(|source| match source.kind() {
+        io::ErrorKind::NotFound => MmcaiError::UnknownSkin(name.to_string()),
+        _ => MmcaiError::ReadTextureFailed { path, source },
+    })?;
+    // a PNG dropped into the folder by hand has no JSON: it is taken as a classic skin
+    let entry = fs::read(dir.join(format!("{}.json", name)))
+        .ok()
+        .and_then(|json| serde_json::from_slice(&json).ok())
+        .unwrap_or(Entry {
+            model: Model::Classic,
+            saved_from: None,
+            saved_at: None,
+        });
+    Ok((png, entry))
+}
+
+/// The saved skins, sorted by name.
+pub fn names() -> Vec<(String, Entry)> {
+    dir().map(|dir| names_in(&dir)).unwrap_or_default()
+}
+
+fn names_in(dir: &Path) -> Vec<(String, Entry)> {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = read_dir
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let name = path.file_stem()?.to_str()?.to_string();
+            (path.extension()? == "png" && check_name(&name).is_ok()).then_some(name)
+        })
+        .collect();
+    names.sort();
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let (_, entry) = load_in(dir, &name).ok()?;
+            Some((name, entry))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let entry = Entry {
+            model: Model::Slim,
+            saved_from: Some("Alex".into()),
+            saved_at: None,
+        };
+        save_in(dir.path(), "halloween", b"PNG", &entry).unwrap();
+        let (png, loaded) = load_in(dir.path(), "halloween").unwrap();
+        assert_eq!(png, b"PNG");
+        assert_eq!(loaded, entry);
+
+        fs::write(dir.path().join("plain.png"), b"PNG").unwrap();
+        let names: Vec<String> = names_in(dir.path())
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, ["halloween", "plain"]);
+        assert!(matches!(
+            load_in(dir.path(), "winter"),
+            Err(MmcaiError::UnknownSkin(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_name() {
+        assert!(check_name("winter-2025").is_ok());
+        assert!(check_name("../accounts").is_err());
+        assert!(check_name("").is_err());
+    }
+}
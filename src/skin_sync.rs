@@ -0,0 +1,98 @@
+use reqwest::{Response, StatusCode};
+use serde::Deserialize;
+
+use crate::errors::MmcaiError;
+use crate::http::HttpClient;
+use crate::skin::{self, Model};
+use crate::{texture_cache, Result};
+
+const MOJANG_PROFILES_URL: &str = "https://api.mojang.com/profiles/minecraft";
+const MOJANG_SESSION_URL: &str = "https://sessionserver.mojang.com/session/minecraft/profile";
+
+/// Where `skin sync` takes the skin from: `mojang:<username>`, or `<username>@<api url>`
+/// for another authlib-injector server.
+#[derive(Debug, PartialEq)]
+pub struct Source {
+    pub username: String,
+    /// Looks names up, `POST`ing a JSON list of them.
+    profiles_url: String,
+    /// `{session_url}/{uuid}` is the profile with its textures.
+    session_url: String,
+}
+
+impl Source {
+    pub fn parse(source: &str) -> Option<Source> {
+        if let Some(username) = source.strip_prefix("mojang:") {
+            return (!username.is_empty()).then(|| Source {
+                username: username.to_string(),
+                profiles_url: MOJANG_PROFILES_URL.into(),
+                session_url: MOJANG_SESSION_URL.into(),
+            });
+        }
+        let (username, api_url) = source.split_once('@')?;
+        if username.is_empty() || !api_url.starts_with("http") {
+            return None;
+        }
+        let api_url = api_url.trim_end_matches('/');
+        Some(Source {
+            username: username.to_string(),
+            profiles_url: format!("{}/api/profiles/minecraft", api_url),
+            session_url: format!("{}/sessionserver/session/minecraft/profile", api_url),
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct NamedProfile {
+    id: String,
+}
+
+/// The source player's skin as a PNG, with the model it is drawn on there.
+pub async fn fetch(http: &HttpClient, source: &Source) -> Result<(Vec<u8>, Model)> {
+    // the other service doesn't have the auth server's certificate
+    let http = &http.public();
+    let not_found = || MmcaiError::SourceProfileNotFound(source.username.clone());
+    let response = http
+        .send(|client| client.post(&source.profiles_url).json(&[&source.username]))
+        .await
+        .and_then(Response::error_for_status)
+        .map_err(MmcaiError::TextureRequestFailed)?;
+    if response.status() == StatusCode::NO_CONTENT {
+        return Err(not_found());
+    }
+    let profiles: Vec<NamedProfile> = response
+        .json()
+        .await
+        .map_err(MmcaiError::TextureRequestFailed)?;
+    let profile = profiles.first().ok_or_else(not_found)?;
+
+    let profile =
+        skin::fetch_session_profile(http, &source.session_url, &profile.id, false).await?;
+    let (url, model) =
+        skin::skin_of(&profile).ok_or_else(|| MmcaiError::NoSkin(source.username.clone()))?;
+    let png = texture_cache::fetch(http, &url, None).await?;
+    Ok((png, model))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let mojang = Source::parse("mojang:Notch").unwrap();
+        assert_eq!(mojang.username, "Notch");
+        assert_eq!(mojang.session_url, MOJANG_SESSION_URL);
+
+        let other = Source::parse("Steve@https://skins.example.com/api/yggdrasil/").unwrap();
+        assert_eq!(other.username, "Steve");
+        assert_eq!(
+            other.profiles_url,
+            "https://skins.example.com/api/yggdrasil/api/profiles/minecraft"
+        );
+
+        assert_eq!(Source::parse("mojang:"), None);
+        assert_eq!(Source::parse("Steve"), None);
+        assert_eq!(Source::parse("Steve@example.com"), None);
+    }
+}
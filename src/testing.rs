@@ -0,0 +1,527 @@
+//! A stand-in for a Marallys auth server, for testing launchers and the patcher without
+//! a real one: a [`wiremock`] server answering from responses the test scripts per route.
+
+use std::{
+    future::Future,
+    io,
+    sync::atomic::{AtomicU8, Ordering},
+    thread,
+};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use wiremock::{
+    matchers::{any, method, path},
+    Mock, ResponseTemplate,
+};
+
+use crate::model::{AuthData, AuthResponse};
+
+/// Where [`MockServer::marallys`] serves the API, as on the real servers.
+pub const API_PATH: &str = "/api/authlib/minecraft";
+pub const SIGNIN_PATH: &str = "/api/auth/signin";
+
+/// What the server answers to a request.
+#[derive(Clone, Debug)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl MockResponse {
+    pub fn new(status: u16, body: impl Into<String>) -> MockResponse {
+        MockResponse {
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    pub fn json(status: u16, body: &impl Serialize) -> MockResponse {
+        let body = serde_json::to_string(body).unwrap_or_default();
+        MockResponse::new(status, body).with_header("Content-Type", "application/json")
+    }
+
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Authlib-injector API metadata with the fields the patcher checks.
+    pub fn metadata() -> MockResponse {
+        MockResponse::json(
+            200,
+            &json!({
+                "meta": {"serverName": "Mock", "implementationName": "mmcai_rs"},
+                "skinDomains": ["127.0.0.1"],
+                "signaturePublickey": "-----BEGIN PUBLIC KEY-----\n-----END PUBLIC KEY-----\n",
+            }),
+        )
+    }
+
+    /// A successful signin as the account.
+    pub fn signin(data: AuthData) -> MockResponse {
+        MockResponse::json(200, &AuthResponse::success(data))
+    }
+
+    /// A failed signin, with the status code in the body like the server sends it; the
+    /// HTTP status stays 200, as it does there.
+    pub fn signin_failure(status_code: u16, message: &str) -> MockResponse {
+        MockResponse::json(200, &AuthResponse::failure(status_code, message))
+    }
+}
+
+/// A request the server received.
+#[derive(Clone, Debug)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl RecordedRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The body as JSON, `null` when it isn't.
+    pub fn json(&self) -> Value {
+        serde_json::from_str(&self.body).unwrap_or_default()
+    }
+}
+
+impl MockResponse {
+    fn template(&self) -> ResponseTemplate {
+        self.headers.iter().fold(
+            ResponseTemplate::new(self.status).set_body_bytes(self.body.as_bytes()),
+            |template, (name, value)| template.append_header(name.as_str(), value.as_str()),
+        )
+    }
+}
+
+impl From<wiremock::Request> for RecordedRequest {
+    fn from(request: wiremock::Request) -> Self {
+        let path = match request.url.query() {
+            Some(query) => format!("{}?{}", request.url.path(), query),
+            None => request.url.path().to_string(),
+        };
+        RecordedRequest {
+            method: request.method.to_string(),
+            path,
+            headers: request
+                .headers
+                .iter()
+                .map(|(name, value)| {
+                    let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+                    (name.to_string(), value)
+                })
+                .collect(),
+            body: String::from_utf8_lossy(&request.body).into_owned(),
+        }
+    }
+}
+
+/// Responses queued with `respond_once` go first, in order.
+const ONCE_PRIORITY: u8 = 1;
+/// Anything without a route gets a 404.
+const FALLBACK_PRIORITY: u8 = u8::MAX;
+
+/// The server, listening on a free port of 127.0.0.1 until dropped.
+pub struct MockServer {
+    server: wiremock::MockServer,
+    /// The priority of the next `respond`, so a later one takes over the route.
+    next_priority: AtomicU8,
+}
+
+impl MockServer {
+    /// A server without routes, answering 404 to everything.
+    pub fn start() -> io::Result<MockServer> {
+        let server = block_on(wiremock::MockServer::builder().start());
+        let not_found = MockResponse::json(404, &json!({"error": "NotFound"}));
+        block_on(
+            Mock::given(any())
+                .respond_with(not_found.template())
+                .with_priority(FALLBACK_PRIORITY)
+                .mount(&server),
+        );
+        Ok(MockServer {
+            server,
+            next_priority: AtomicU8::new(FALLBACK_PRIORITY - 1),
+        })
+    }
+
+    /// A server like Marallys: the API metadata at [`API_PATH`], and signin at
+    /// [`SIGNIN_PATH`] succeeding for the player Steve.
+    pub fn marallys() -> io::Result<MockServer> {
+        let server = MockServer::start()?;
+        server.respond("GET", API_PATH, MockResponse::metadata());
+        server.respond(
+            "POST",
+            SIGNIN_PATH,
+            MockResponse::signin(
+                AuthData::new("4b5c2a1e9f3d4e6a8b7c0d1e2f3a4b5c", "Steve", "mock-token")
+                    .with_expired_date("2099-01-01T00:00:00.000Z"),
+            ),
+        );
+        Ok(server)
+    }
+
+    /// The URL of the API root, to pass as the API URL.
+    pub fn api_url(&self) -> String {
+        self.url(API_PATH)
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.server.uri(), path)
+    }
+
+    /// Answers every request to the route with the response, once the ones queued with
+    /// `respond_once` are used up.
+    pub fn respond(&self, method: &str, path: &str, response: MockResponse) {
+        let priority = self
+            .next_priority
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |priority| {
+                Some(priority.saturating_sub(1).max(ONCE_PRIORITY + 1))
+            })
+            .unwrap_or(ONCE_PRIORITY + 1);
+        self.mount(route(method, path, &response).with_priority(priority));
+    }
+
+    /// Answers the next request to the route with the response.
+    pub fn respond_once(&self, method: &str, path: &str, response: MockResponse) {
+        let mock = route(method, path, &response)
+            .up_to_n_times(1)
+            .with_priority(ONCE_PRIORITY);
+        self.mount(mock);
+    }
+
+    /// The requests received for the path, oldest first.
+    pub fn requests(&self, path: &str) -> Vec<RecordedRequest> {
+        block_on(self.server.received_requests())
+            .unwrap_or_default()
+            .into_iter()
+            .map(RecordedRequest::from)
+            .filter(|request| request.path.split('?').next() == Some(path))
+            .collect()
+    }
+
+    fn mount(&self, mock: Mock) {
+        block_on(self.server.register(mock));
+    }
+}
+
+fn route(method_name: &str, route_path: &str, response: &MockResponse) -> Mock {
+    // the query doesn't select the route
+    Mock::given(method(method_name))
+        .and(path(route_path))
+        .respond_with(response.template())
+}
+
+/// Runs one of wiremock's async calls for the blocking API above, on a thread of its own
+/// so that tests already inside a runtime can use it too.
+fn block_on<F>(future: F) -> F::Output
+where
+    F: Future + Send,
+    F::Output: Send,
+{
+    thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .expect("a runtime for the mock server")
+                    .block_on(future)
+            })
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::Account;
+    use crate::auth::login_with_fallback;
+    use crate::config::Config;
+    use crate::errors::MmcaiError;
+    use crate::events::EventSink;
+    use crate::http::HttpClient;
+    use crate::model::Profile;
+    use crate::timing::Timings;
+
+    /// A config that keeps the tests off the disk and the network beyond the server.
+    fn config() -> Config {
+        let mut config = Config::default();
+        config.audit.enabled = false;
+        config.server.diagnose_network = false;
+        config.retry.attempts = 1;
+        config
+    }
+
+    fn login(server: &MockServer, config: &Config) -> crate::Result<crate::auth::LoginResult> {
+        let account = Account::unstored("steve".into(), "hunter2".into(), server.api_url());
+        let http = HttpClient::new(config, None)?;
+        let events = EventSink::default();
+        let timings = Timings::new(&events);
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(login_with_fallback(
+            &account,
+            "client-token",
+            &[&server.api_url()],
+            &http,
+            config,
+            &timings,
+        ))
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_login_patch_spawn() {
+        use std::os::unix::fs::PermissionsExt;
+
+        use crate::cli::Options;
+        use crate::launch::launch_game;
+
+        let server = MockServer::marallys().unwrap();
+        let dir = assert_fs::TempDir::new().unwrap();
+
+        let injector = dir.path().join("authlib-injector-mock.jar");
+        std::fs::write(&injector, "").unwrap();
+
+        // a script stands in for Java and keeps its arguments and what it gets on stdin
+        let java = dir.path().join("java");
+        std::fs::write(
+            &java,
+            format!(
+                "#!/bin/sh\nprintf '%s\\n' \"$@\" > '{0}/args'\ncat > '{0}/params'\n",
+                dir.path().display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&java, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let params = dir.path().join("launcher-params");
+        std::fs::write(
+            &params,
+            "param --username\nparam Player\nparam --accessToken\nparam 0\nlaunch\n",
+        )
+        .unwrap();
+        let options = Options {
+            params_file: Some(params),
+            java: Some(java),
+            authlib_injector: Some(injector.clone()),
+            ..Default::default()
+        };
+        let args = [
+            "mmcai_rs",
+            "steve",
+            "hunter2",
+            &server.api_url(),
+            "java",
+            "-Xmx1G",
+            "net.minecraft.client.main.Main",
+        ]
+        .map(String::from);
+        let config = config();
+        let events = EventSink::default();
+        let status = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(launch_game(
+                &options,
+                &args,
+                &config,
+                &events,
+                &Timings::new(&events),
+            ))
+            .unwrap();
+        assert!(status.success());
+
+        let signins = server.requests(SIGNIN_PATH);
+        assert_eq!(signins.len(), 1);
+        assert_eq!(
+            signins[0].json(),
+            json!({"login": "steve", "password": "hunter2", "accessToken": "null"})
+        );
+
+        let java_args = std::fs::read_to_string(dir.path().join("args")).unwrap();
+        let javaagent = format!("-javaagent:{}={}", injector.display(), server.api_url());
+        assert!(java_args.starts_with(&javaagent), "{}", java_args);
+        assert!(java_args.contains("-Dauthlibinjector.yggdrasil.prefetched="));
+        assert!(java_args.ends_with("-Xmx1G\nnet.minecraft.client.main.Main\n"));
+
+        let received = std::fs::read_to_string(dir.path().join("params")).unwrap();
+        assert!(received.contains("param Steve\n"), "{}", received);
+        assert!(received.contains("param mock-token\n"), "{}", received);
+        assert!(!received.contains("param Player\n"));
+        assert!(received.ends_with("launch\n"));
+    }
+
+    #[test]
+    fn test_login_failures() {
+        let server = MockServer::marallys().unwrap();
+        let config = config();
+        server.respond_once(
+            "POST",
+            SIGNIN_PATH,
+            MockResponse::signin_failure(401, "Invalid password"),
+        );
+        assert!(matches!(
+            login(&server, &config),
+            Err(MmcaiError::InvalidCredentials { message }) if message == "Invalid password"
+        ));
+
+        server.respond_once(
+            "POST",
+            SIGNIN_PATH,
+            MockResponse::new(429, "").with_header("Retry-After", "120"),
+        );
+        assert!(matches!(
+            login(&server, &config),
+            Err(MmcaiError::RateLimited { .. })
+        ));
+
+        let api_url = server.api_url();
+        drop(server);
+        let account = Account::unstored("steve".into(), "hunter2".into(), api_url.clone());
+        let http = HttpClient::new(&config, None).unwrap();
+        let events = EventSink::default();
+        let error = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(login_with_fallback(
+                &account,
+                "client-token",
+                &[&api_url],
+                &http,
+                &config,
+                &Timings::new(&events),
+            ))
+            .unwrap_err();
+        assert!(error.is_unreachable(), "{:?}", error);
+    }
+
+    #[test]
+    fn test_profile_selection() {
+        let server = MockServer::marallys().unwrap();
+        let steve = Profile::new("0000000000000000000000000000000a", "Steve");
+        let alex = Profile::new("0000000000000000000000000000000b", "Alex");
+        server.respond(
+            "POST",
+            SIGNIN_PATH,
+            MockResponse::signin(
+                AuthData::new("0000000000000000000000000000000a", "Steve", "steve-token")
+                    .with_available_profiles(vec![steve, alex.clone()]),
+            ),
+        );
+        let refresh_path = format!("{}/authserver/refresh", API_PATH);
+        server.respond(
+            "POST",
+            &refresh_path,
+            MockResponse::json(
+                200,
+                &json!({"accessToken": "alex-token", "clientToken": "client-token", "selectedProfile": alex}),
+            ),
+        );
+        let mut config = config();
+        config.server.profile = Some("alex".into());
+        let login_result = login(&server, &config).unwrap();
+        assert_eq!(login_result.access_token, "alex-token");
+        assert_eq!(login_result.selected_profile.name, "Alex");
+        let refresh = &server.requests(&refresh_path)[0];
+        assert_eq!(refresh.json()["accessToken"], "steve-token");
+        assert_eq!(refresh.json()["selectedProfile"]["name"], "Alex");
+
+        config.server.profile = Some("Herobrine".into());
+        let error = login(&server, &config).unwrap_err();
+        assert!(matches!(error, MmcaiError::UnknownProfile { .. }));
+        assert_eq!(error.exit_code(), 20);
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn test_client_signin_endpoint() {
+        use crate::client::YggdrasilClient;
+
+        let server = MockServer::marallys().unwrap();
+        server.respond(
+            "POST",
+            "/login",
+            MockResponse::signin(AuthData::new("u", "Alex", "t")),
+        );
+        let mut config = config();
+        config.server.endpoints.signin = Some("/login".into());
+        let client = YggdrasilClient::builder()
+            .api_url(server.api_url())
+            .config(config)
+            .build()
+            .unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let session = runtime
+            .block_on(client.authenticate("alex", "hunter2"))
+            .unwrap();
+        assert_eq!(session.selected_profile.name, "Alex");
+        assert!(server.requests(SIGNIN_PATH).is_empty());
+    }
+
+    #[test]
+    fn test_skin_upload() {
+        use crate::skin::{self, Model, Target, TextureType, Textures};
+
+        let server = MockServer::start().unwrap();
+        let path = format!(
+            "{}/api/user/profile/4b5c2a1e9f3d4e6a8b7c0d1e2f3a4b5c/skin",
+            API_PATH
+        );
+        server.respond("PUT", &path, MockResponse::new(204, ""));
+        let config = config();
+        let http = HttpClient::new(&config, None).unwrap();
+        let api_url = server.api_url();
+        let textures = Textures::default();
+        let target = Target {
+            api_url: &api_url,
+            access_token: "mock-token",
+            uuid: "4b5c2a1e-9f3d-4e6a-8b7c-0d1e2f3a4b5c",
+            name: "Steve",
+            textures: &textures,
+        };
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(skin::upload(
+                &http,
+                &target,
+                TextureType::Skin,
+                Model::Slim,
+                b"PNG".to_vec(),
+            ))
+            .unwrap();
+
+        let request = &server.requests(&path)[0];
+        assert_eq!(request.header("Authorization"), Some("Bearer mock-token"));
+        let boundary = request
+            .header("Content-Type")
+            .and_then(|value| value.strip_prefix("multipart/form-data; boundary="))
+            .unwrap();
+        assert!(request.body.contains("name=\"model\"\r\n\r\nslim\r\n"));
+        assert!(request.body.contains(
+            "name=\"file\"; filename=\"texture.png\"\r\nContent-Type: image/png\r\n\r\nPNG\r\n"
+        ));
+        assert!(request.body.ends_with(&format!("--{}--\r\n", boundary)));
+    }
+}
@@ -0,0 +1,97 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use reqwest::Response;
+use ring::digest::{digest, SHA256};
+use tracing::debug;
+
+use crate::errors::MmcaiError;
+use crate::http::HttpClient;
+use crate::skin::PNG_SIGNATURE;
+use crate::{paths, Result};
+
+/// Fetches a skin or cape, from the cache when it was downloaded before. The server
+/// gives every texture file a new GUID, so a cached texture never goes stale; textures
+/// reported without one are cached by their URL instead.
+pub async fn fetch(http: &HttpClient, url: &str, guid: Option<&str>) -> Result<Vec<u8>> {
+    let path = cache_path(url, guid);
+    if let Some(png) = path.as_ref().and_then(|path| fs::read(path).ok()) {
+        if png.starts_with(PNG_SIGNATURE) {
+            debug!("using the cached texture {}", url);
+            return Ok(png);
+        }
+    }
+    let png = download(http, url).await?;
+    if let Some(path) = path {
+        store(&path, &png);
+    }
+    Ok(png)
+}
+
+/// Fetches the image without looking at the cache.
+pub async fn download(http: &HttpClient, url: &str) -> Result<Vec<u8>> {
+    let response = http
+        .public()
+        .send(|client| client.get(url))
+        .await
+        .and_then(Response::error_for_status)
+        .map_err(MmcaiError::TextureRequestFailed)?;
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(MmcaiError::TextureRequestFailed)?;
+    Ok(bytes.to_vec())
+}
+
+fn cache_path(url: &str, guid: Option<&str>) -> Option<PathBuf> {
+    let key = match guid {
+        Some(guid) if is_safe_key(guid) => guid.to_ascii_lowercase(),
+        _ => digest(&SHA256, url.as_bytes())
+            .as_ref()
+            .iter()
+            .take(16)
+            .map(|b| format!("{:02x}", b))
+            .collect(),
+    };
+    Some(
+        paths::cache_dir()?
+            .join("textures")
+            .join(format!("{}.png", key)),
+    )
+}
+
+/// GUIDs come from the server and become file names, so only plain ones are used.
+fn is_safe_key(guid: &str) -> bool {
+    !guid.is_empty()
+        && guid.len() <= 64
+        && guid
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Caching is an optimization, failing to write the cache only costs a download.
+fn store(path: &Path, png: &[u8]) {
+    if !png.starts_with(PNG_SIGNATURE) {
+        return;
+    }
+    if let Some(parent) = path.parent() {
+        let _ = paths::create_private_dir(parent).and_then(|_| fs::write(path, png));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_path() {
+        let by_guid = cache_path("https://t/a", Some("3F2a-b_c")).unwrap();
+        assert!(by_guid.ends_with("textures/3f2a-b_c.png"));
+        // unsafe GUIDs fall back to the URL, so they can't escape the cache directory
+        let by_url = cache_path("https://t/a", Some("../../etc/passwd")).unwrap();
+        assert_eq!(by_url, cache_path("https://t/a", None).unwrap());
+        assert_ne!(by_url, cache_path("https://t/b", None).unwrap());
+    }
+}
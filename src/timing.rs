@@ -0,0 +1,119 @@
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use tracing::info;
+
+use crate::events::{Event, EventSink};
+use crate::i18n::tr;
+
+/// The parts of a launch the wrapper can be blamed for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Stage {
+    /// Fetching the API metadata.
+    Prefetch,
+    /// `POST /auth/signin`.
+    Signin,
+    /// Waiting for and reading the launcher's param block.
+    ParamRead,
+    /// Starting Java and handing it the params.
+    Spawn,
+}
+
+impl Stage {
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::Prefetch => "prefetch",
+            Stage::Signin => "signin",
+            Stage::ParamRead => "param_read",
+            Stage::Spawn => "spawn",
+        }
+    }
+}
+
+/// How long each stage took. Prefetch and signin run in parallel, so the stages can
+/// add up to more than the wall-clock time. Stages repeated for fallback servers are
+/// added together.
+pub struct Timings<'a> {
+    stages: Mutex<BTreeMap<Stage, Duration>>,
+    events: &'a EventSink,
+}
+
+impl<'a> Timings<'a> {
+    pub fn new(events: &'a EventSink) -> Timings<'a> {
+        Timings {
+            stages: Mutex::new(BTreeMap::new()),
+            events,
+        }
+    }
+
+    pub fn record(&self, stage: Stage, duration: Duration) {
+        if let Ok(mut stages) = self.stages.lock() {
+            *stages.entry(stage).or_default() += duration;
+        }
+    }
+
+    /// Like `record`, but replaces what an earlier run of the stage took.
+    pub fn set(&self, stage: Stage, duration: Duration) {
+        if let Ok(mut stages) = self.stages.lock() {
+            stages.insert(stage, duration);
+        }
+    }
+
+    pub async fn measure<F: Future>(&self, stage: Stage, future: F) -> F::Output {
+        let started = Instant::now();
+        let output = future.await;
+        self.record(stage, started.elapsed());
+        output
+    }
+
+    pub fn stages(&self) -> BTreeMap<Stage, Duration> {
+        self.stages
+            .lock()
+            .map(|stages| stages.clone())
+            .unwrap_or_default()
+    }
+
+    /// Prints the summary and sends it as a `timings` event, once the game is running.
+    pub fn report(&self) {
+        let stages = self.stages();
+        info!("{}", summary(&stages));
+        self.events.emit(Event::Timings {
+            stages_ms: stages
+                .iter()
+                .map(|(stage, duration)| (stage.name(), duration.as_millis() as u64))
+                .collect(),
+        });
+    }
+}
+
+fn summary(stages: &BTreeMap<Stage, Duration>) -> String {
+    let stages: Vec<String> = stages
+        .iter()
+        .map(|(stage, duration)| format!("{} {:.2}s", stage.name(), duration.as_secs_f64()))
+        .collect();
+    tr!("timings", stages = stages.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary() {
+        let events = EventSink::default();
+        let timings = Timings::new(&events);
+        timings.record(Stage::Spawn, Duration::from_millis(20));
+        timings.record(Stage::Signin, Duration::from_millis(400));
+        timings.record(Stage::Signin, Duration::from_millis(250));
+        timings.record(Stage::Prefetch, Duration::from_millis(1234));
+
+        assert_eq!(
+            summary(&timings.stages()),
+            "Timings: prefetch 1.23s, signin 0.65s, spawn 0.02s"
+        );
+    }
+}
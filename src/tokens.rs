@@ -0,0 +1,151 @@
+use futures_util::future;
+use reqwest::StatusCode;
+use serde::Serialize;
+
+use crate::accounts::{self, Account, Accounts};
+use crate::color::{self, Color, Stream};
+use crate::http::HttpClient;
+use crate::i18n::tr;
+use crate::session::{self, CachedSession};
+use crate::{dates, Result};
+
+/// What the server says about a cached access token.
+#[derive(Debug, PartialEq)]
+pub enum Status {
+    Valid,
+    /// Expired or revoked: the account needs a fresh login.
+    Invalid,
+    /// No session is cached for the account.
+    NotCached,
+    /// The server couldn't be asked, or gave an answer that says neither.
+    Unknown(String),
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ValidateRequest<'a> {
+    access_token: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_token: Option<&'a str>,
+}
+
+/// Asks the server whether the session's token is still good.
+pub async fn validate(
+    http: &HttpClient,
+    session: &CachedSession,
+    client_token: Option<&str>,
+) -> Status {
+    validate_token(http, &session.api_url, &session.access_token, client_token).await
+}
+
+/// Asks the server whether the token is still good, with Yggdrasil's
+/// `/authserver/validate` (204 when it is, 403 when it isn't).
+pub async fn validate_token(
+    http: &HttpClient,
+    api_url: &str,
+    access_token: &str,
+    client_token: Option<&str>,
+) -> Status {
+    let url = format!("{}/authserver/validate", api_url.trim_end_matches('/'));
+    let request = ValidateRequest {
+        access_token,
+        client_token,
+    };
+    match http
+        .send_once(|client| client.post(&url).json(&request))
+        .await
+    {
+        Ok(response) if response.status().is_success() => Status::Valid,
+        Ok(response)
+            if [StatusCode::UNAUTHORIZED, StatusCode::FORBIDDEN].contains(&response.status()) =>
+        {
+            Status::Invalid
+        }
+        Ok(response) => Status::Unknown(format!("HTTP {}", response.status().as_u16())),
+        Err(e) => Status::Unknown(e.to_string()),
+    }
+}
+
+/// `mmcai_rs --cmd account list --check`: the accounts, each with its cached token's age and
+/// expiry and whether the server still accepts it.
+pub async fn print_accounts(http: &HttpClient) -> Result<()> {
+    let accounts = Accounts::load(&accounts::store_path()?)?;
+    if accounts.accounts.is_empty() {
+        println!("{}", tr!("account-none"));
+        return Ok(());
+    }
+    let checks = accounts.accounts.iter().map(|account| check(http, account));
+    let checks = future::join_all(checks).await;
+    let now = dates::unix_now();
+    for (line, (session, status)) in accounts::list_lines(&accounts).into_iter().zip(checks) {
+        println!("{}", line);
+        println!("    {}", format_status(session.as_ref(), &status, now));
+    }
+    Ok(())
+}
+
+async fn check(http: &HttpClient, account: &Account) -> (Option<CachedSession>, Status) {
+    let Some(session) = session::load(&account.cache_key()) else {
+        return (None, Status::NotCached);
+    };
+    let status = validate(http, &session, account.client_token.as_deref()).await;
+    (Some(session), status)
+}
+
+fn format_status(session: Option<&CachedSession>, status: &Status, now: u64) -> String {
+    let (text, color) = match status {
+        Status::Valid => (tr!("token-valid"), Color::Green),
+        Status::Invalid => (tr!("token-invalid"), Color::Red),
+        Status::NotCached => (tr!("token-none"), Color::Yellow),
+        Status::Unknown(reason) => (tr!("token-unknown", reason = reason), Color::Yellow),
+    };
+    let mut line = String::new();
+    if let Some(session) = session {
+        let age = format_age(now.saturating_sub(session.obtained_at));
+        line += &tr!("token-obtained", age = age);
+        if let Some(expires_at) = session.expires_at {
+            line += ", ";
+            line += &if expires_at > now {
+                tr!("token-expires", time = format_age(expires_at - now))
+            } else {
+                tr!("token-expired", time = format_age(now - expires_at))
+            };
+        }
+        line += ": ";
+    }
+    line + &color::paint(&text, color, Stream::Stdout)
+}
+
+/// A rough duration, like `5m`, `3h` or `12d`.
+fn format_age(seconds: u64) -> String {
+    match seconds {
+        0..3600 => format!("{}m", seconds / 60),
+        3600..172800 => format!("{}h", seconds / 3600),
+        _ => format!("{}d", seconds / 86400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_status() {
+        let session = CachedSession {
+            api_url: "https://auth.example.com/api".into(),
+            access_token: "t".into(),
+            uuid: "0f8d".into(),
+            name: "Steve".into(),
+            expires_at: Some(10_000 + 3 * 86400),
+            obtained_at: 10_000 - 2 * 3600,
+        };
+        assert_eq!(
+            format_status(Some(&session), &Status::Valid, 10_000),
+            "obtained 2h ago, expires in 3d: valid"
+        );
+        assert_eq!(
+            format_status(None, &Status::NotCached, 10_000),
+            "no cached login"
+        );
+    }
+}
@@ -0,0 +1,228 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    path::Path,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Mutex,
+    },
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use reqwest::{header::HeaderMap, RequestBuilder, Response, ResponseBuilderExt};
+use serde_json::Value;
+
+use tracing::info;
+
+use crate::errors::MmcaiError;
+use crate::i18n::tr;
+use crate::Result;
+
+const MAX_BODY_LENGTH: usize = 2048;
+const REDACTED: &str = "<redacted>";
+
+/// Records request/response metadata of every HTTP exchange to a file (`--trace-http`).
+/// Credentials are redacted so the file can be shared with server admins.
+pub struct HttpTrace {
+    file: Mutex<File>,
+    next_id: AtomicU32,
+}
+
+impl HttpTrace {
+    pub fn open(path: &Path) -> Result<HttpTrace> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|source| MmcaiError::OpenTraceFileFailed {
+                path: path.to_path_buf(),
+                source,
+            })?;
+        info!("{}", tr!("trace-enabled", path = format!("{:?}", path)));
+        Ok(HttpTrace {
+            file: Mutex::new(file),
+            next_id: AtomicU32::new(1),
+        })
+    }
+
+    pub async fn send(&self, request: RequestBuilder) -> reqwest::Result<Response> {
+        let (client, request) = request.build_split();
+        let request = request?;
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+
+        let body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(redact_body)
+            .unwrap_or_default();
+        self.write(&format!(
+            "#{} --> {} {}\n{}{}",
+            id,
+            request.method(),
+            request.url(),
+            format_headers(request.headers()),
+            format_body(&body),
+        ));
+
+        let started = Instant::now();
+        let result = client.execute(request).await;
+        let elapsed = started.elapsed().as_millis();
+
+        let mut response = match result {
+            Ok(response) => response,
+            Err(e) => {
+                self.write(&format!("#{} <-- error after {}ms: {:?}", id, elapsed, e));
+                return Err(e);
+            }
+        };
+
+        // the body has to be read to be logged, so the response is rebuilt around it afterwards
+        let status = response.status();
+        let version = response.version();
+        let url = response.url().clone();
+        let headers = response.headers().clone();
+        let extensions = std::mem::take(response.extensions_mut());
+        let bytes = response.bytes().await?;
+
+        self.write(&format!(
+            "#{} <-- {} {} ({}ms)\n{}{}",
+            id,
+            status,
+            url,
+            elapsed,
+            format_headers(&headers),
+            format_body(&redact_body(&bytes)),
+        ));
+
+        let mut builder = http::Response::builder()
+            .status(status)
+            .version(version)
+            .url(url);
+        if let Some(builder_headers) = builder.headers_mut() {
+            *builder_headers = headers;
+        }
+        if let Some(builder_extensions) = builder.extensions_mut() {
+            *builder_extensions = extensions;
+        }
+        Ok(builder
+            .body(bytes)
+            .expect("status, version and headers come from a valid response")
+            .into())
+    }
+
+    fn write(&self, entry: &str) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        if let Ok(mut file) = self.file.lock() {
+            // tracing is best effort, it must never break the login
+            let _ = writeln!(file, "[{:.3}] {}", timestamp, entry);
+        }
+    }
+}
+
+fn format_headers(headers: &HeaderMap) -> String {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if is_sensitive_header(name.as_str()) {
+                REDACTED
+            } else {
+                value.to_str().unwrap_or("<binary>")
+            };
+            format!("    {}: {}\n", name, value)
+        })
+        .collect()
+}
+
+fn format_body(body: &str) -> String {
+    if body.is_empty() {
+        return String::new();
+    }
+    if body.len() <= MAX_BODY_LENGTH {
+        return format!("    {}\n", body);
+    }
+    let cut = (0..=MAX_BODY_LENGTH)
+        .rev()
+        .find(|i| body.is_char_boundary(*i))
+        .unwrap_or(0);
+    format!("    {}... ({} bytes total)\n", &body[..cut], body.len())
+}
+
+fn is_sensitive_header(name: &str) -> bool {
+    matches!(
+        name.to_ascii_lowercase().as_str(),
+        "authorization" | "proxy-authorization" | "cookie" | "set-cookie"
+    )
+}
+
+fn is_sensitive_field(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    name.contains("password") || name.contains("token")
+}
+
+/// JSON bodies get their credential fields replaced; anything else is logged as text.
+fn redact_body(body: &[u8]) -> String {
+    match serde_json::from_slice::<Value>(body) {
+        Ok(mut json) => {
+            redact_json(&mut json);
+            json.to_string()
+        }
+        Err(_) => String::from_utf8_lossy(body).into_owned(),
+    }
+}
+
+fn redact_json(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if is_sensitive_field(key) && !value.is_null() {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact_json(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_json),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::{HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+
+    use super::*;
+
+    #[test]
+    fn test_redact_body() {
+        let body = br#"{"login":"steve","password":"hunter2","data":{"accessToken":"abc","name":"Steve"}}"#;
+        let redacted = redact_body(body);
+        assert!(!redacted.contains("hunter2"));
+        assert!(!redacted.contains("abc"));
+        assert!(redacted.contains("steve"));
+        assert!(redacted.contains("Steve"));
+
+        assert_eq!(redact_body(b"<html>502</html>"), "<html>502</html>");
+    }
+
+    #[test]
+    fn test_format_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer secret"));
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        let formatted = format_headers(&headers);
+        assert!(!formatted.contains("secret"));
+        assert!(formatted.contains("authorization: <redacted>"));
+        assert!(formatted.contains("content-type: application/json"));
+    }
+
+    #[test]
+    fn test_format_body_truncates() {
+        let body = "é".repeat(MAX_BODY_LENGTH);
+        let formatted = format_body(&body);
+        assert!(formatted.len() < body.len());
+        assert!(formatted.ends_with(&format!("({} bytes total)\n", body.len())));
+    }
+}